@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use cs::output::TreeFormatter;
-use cs::{CodeReference, SearchResult, SearchResultCache, TranslationEntry};
+use cs::{CodeReference, Confidence, SearchResult, SearchResultCache, TranslationEntry};
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -18,6 +18,8 @@ fn cache_impact_test(c: &mut Criterion) {
             line: 1,
             key: "test.key".to_string(),
             value: "test value".to_string(),
+            branch: None,
+            locale: None,
         }],
         code_references: vec![CodeReference {
             file: PathBuf::from("test.ts"),
@@ -27,6 +29,7 @@ fn cache_impact_test(c: &mut Criterion) {
             key_path: "test.key".to_string(),
             context_before: vec!["// before".to_string()],
             context_after: vec!["// after".to_string()],
+            confidence: Confidence::Exact,
         }],
     };
 
@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use cs::output::TreeFormatter;
-use cs::{CodeReference, SearchQuery, SearchResult, TranslationEntry};
+use cs::{CodeReference, Confidence, SearchQuery, SearchResult, TranslationEntry};
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -24,6 +24,8 @@ fn generate_search_results(num_translation_entries: usize, num_code_refs: usize)
             line: i + 1,
             key: format!("app.section_{}.key_{}", i % 5, i),
             value: format!("Translation value for key {}", i),
+            branch: None,
+            locale: None,
         });
     }
 
@@ -44,6 +46,7 @@ fn generate_search_results(num_translation_entries: usize, num_code_refs: usize)
             key_path: format!("app.section_{}.key_{}", i % 5, i),
             context_before,
             context_after,
+            confidence: Confidence::Exact,
         });
     }
 
@@ -122,6 +125,7 @@ fn bench_context_processing(c: &mut Criterion) {
                 key_path: format!("data.key_{}", i),
                 context_before,
                 context_after,
+                confidence: Confidence::Exact,
             });
         }
 
@@ -194,6 +198,7 @@ fn bench_context_overlap_merging(c: &mut Criterion) {
                 key_path: format!("key_{}", i),
                 context_before,
                 context_after,
+                confidence: Confidence::Exact,
             });
         }
 
@@ -273,6 +278,7 @@ fn bench_memory_usage(c: &mut Criterion) {
                     ),
                     context_before,
                     context_after,
+                    confidence: Confidence::Exact,
                 });
             }
         }
@@ -386,6 +392,7 @@ fn bench_special_character_handling(c: &mut Criterion) {
                 key_path: format!("key_{}", i),
                 context_before: vec![format!("// Before: {}", content_template)],
                 context_after: vec![format!("// After: {}", content_template)],
+                confidence: Confidence::Exact,
             });
         }
 
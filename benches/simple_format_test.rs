@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use cs::output::TreeFormatter;
-use cs::{CodeReference, SearchResult, TranslationEntry};
+use cs::{CodeReference, Confidence, SearchResult, TranslationEntry};
 use std::path::PathBuf;
 
 fn simple_benchmark_test(c: &mut Criterion) {
@@ -12,6 +12,8 @@ fn simple_benchmark_test(c: &mut Criterion) {
             line: 1,
             key: "test.key".to_string(),
             value: "test value".to_string(),
+            branch: None,
+            locale: None,
         }],
         code_references: vec![CodeReference {
             file: PathBuf::from("test.ts"),
@@ -21,6 +23,7 @@ fn simple_benchmark_test(c: &mut Criterion) {
             key_path: "test.key".to_string(),
             context_before: vec!["// before".to_string()],
             context_after: vec!["// after".to_string()],
+            confidence: Confidence::Exact,
         }],
     };
 
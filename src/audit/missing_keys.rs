@@ -0,0 +1,304 @@
+use crate::config::{self, ProjectConfig};
+use crate::error::Result;
+use crate::parse::locale::locale_pattern;
+use crate::parse::{KeyExtractor, TranslationEntry};
+use crate::search::PatternMatcher;
+use crate::tree::Location;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Key differences of one locale file against its siblings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LocaleDiff {
+    pub locale: String,
+    pub file: PathBuf,
+    /// Keys present in the reference set but not in this file
+    pub missing: Vec<String>,
+    /// Keys present in this file but not in the base locale
+    pub extra: Vec<String>,
+}
+
+/// A set of sibling locale files, e.g. `config/locales/{locale}.yml`
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleGroup {
+    /// Path pattern shared by the siblings, relative to the project root
+    pub pattern: String,
+    /// Locale the others are compared against; `None` when the base locale
+    /// has no file in this group and every file is compared to the union
+    pub base_locale: Option<String>,
+    pub locales: Vec<LocaleDiff>,
+}
+
+/// A key referenced from code that no locale file defines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UndefinedKey {
+    pub key: String,
+    pub locations: Vec<Location>,
+}
+
+/// Result of a missing-keys audit
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingKeysReport {
+    pub groups: Vec<LocaleGroup>,
+    pub undefined_keys: Vec<UndefinedKey>,
+}
+
+impl MissingKeysReport {
+    /// Whether every locale is complete and every referenced key is defined
+    pub fn is_clean(&self) -> bool {
+        self.undefined_keys.is_empty()
+            && self
+                .groups
+                .iter()
+                .flat_map(|g| &g.locales)
+                .all(|l| l.missing.is_empty() && l.extra.is_empty())
+    }
+}
+
+/// Compares sibling locale files and checks code references against them.
+///
+/// Locale files are grouped by their path with the locale replaced (see
+/// [`locale_pattern`]). Within a group, each locale's flattened key set is
+/// compared to the base locale (`en` by default). Independently, every key
+/// captured by the default i18n patterns in code is looked up in the keys of
+/// all locale files.
+pub struct MissingKeyFinder {
+    base_dir: PathBuf,
+    base_locale: String,
+    exclusions: Vec<String>,
+    verbose: bool,
+    config: ProjectConfig,
+}
+
+impl MissingKeyFinder {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            base_locale: "en".to_string(),
+            exclusions: Vec::new(),
+            verbose: false,
+            config: ProjectConfig::default(),
+        }
+    }
+
+    /// Locale whose keys the other locales are expected to have
+    pub fn with_base_locale(mut self, locale: impl Into<String>) -> Self {
+        self.base_locale = locale.into();
+        self
+    }
+
+    /// Additional exclusions on top of the project-type defaults
+    pub fn with_exclusions(mut self, exclusions: Vec<String>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Patterns, exclusions and locale directories from `.cs.toml`
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn run(&self) -> Result<MissingKeysReport> {
+        let project_type = config::detect_project_type(&self.base_dir);
+        let mut exclusions = self.config.exclusions(project_type);
+        exclusions.extend(self.exclusions.iter().cloned());
+
+        let mut extractor = KeyExtractor::new();
+        extractor.set_exclusions(exclusions.clone());
+        extractor.set_locale_dirs(self.config.locale_dirs());
+        extractor.set_verbose(self.verbose);
+        extractor.set_quiet(true);
+        let entries = extractor.extract_all(&self.base_dir)?;
+
+        let mut matcher =
+            PatternMatcher::with_patterns(self.config.patterns(), self.base_dir.clone());
+        matcher.set_exclusions(exclusions);
+        matcher.set_locale_dirs(self.config.locale_dirs());
+        let references = matcher.find_all_references()?;
+
+        let defined = DefinedKeys::new(entries.iter().map(|e| e.key.as_str()));
+        let mut undefined: BTreeMap<String, Vec<Location>> = BTreeMap::new();
+        for reference in references {
+            if is_dynamic_key(&reference.key_path) || defined.covers(&reference.key_path) {
+                continue;
+            }
+            undefined
+                .entry(reference.key_path)
+                .or_default()
+                .push(Location::new(reference.file, reference.line));
+        }
+
+        Ok(MissingKeysReport {
+            groups: self.compare_siblings(&entries),
+            undefined_keys: undefined
+                .into_iter()
+                .map(|(key, locations)| UndefinedKey { key, locations })
+                .collect(),
+        })
+    }
+
+    fn compare_siblings(&self, entries: &[TranslationEntry]) -> Vec<LocaleGroup> {
+        // pattern -> locale -> (file, keys)
+        type Siblings<'a> = BTreeMap<String, (&'a Path, BTreeSet<&'a str>)>;
+        let mut groups: BTreeMap<String, Siblings> = BTreeMap::new();
+
+        for entry in entries {
+            let relative = entry
+                .file
+                .strip_prefix(&self.base_dir)
+                .unwrap_or(&entry.file);
+            if let Some((locale, pattern)) = locale_pattern(relative) {
+                groups
+                    .entry(pattern)
+                    .or_default()
+                    .entry(locale)
+                    .or_insert_with(|| (entry.file.as_path(), BTreeSet::new()))
+                    .1
+                    .insert(entry.key.as_str());
+            }
+        }
+
+        groups
+            .into_iter()
+            .filter(|(_, siblings)| siblings.len() > 1)
+            .map(|(pattern, siblings)| {
+                let base = siblings.get(&self.base_locale).map(|(_, keys)| keys);
+                let union: BTreeSet<&str> = siblings
+                    .values()
+                    .flat_map(|(_, keys)| keys.iter().copied())
+                    .collect();
+                let reference = base.unwrap_or(&union);
+
+                let locales = siblings
+                    .iter()
+                    .map(|(locale, (file, keys))| LocaleDiff {
+                        locale: locale.clone(),
+                        file: file.to_path_buf(),
+                        missing: reference.difference(keys).map(|k| k.to_string()).collect(),
+                        extra: keys.difference(reference).map(|k| k.to_string()).collect(),
+                    })
+                    .collect();
+
+                LocaleGroup {
+                    pattern,
+                    base_locale: base.map(|_| self.base_locale.clone()),
+                    locales,
+                }
+            })
+            .collect()
+    }
+}
+
+/// All keys defined in locale files, with their namespaces and suffixes.
+///
+/// A code reference counts as defined when it names a key, a namespace of
+/// keys (`t('invoice.labels')`), or a key relative to a cached namespace
+/// (`labels.t('add_new')`), mirroring `generate_partial_keys`.
+struct DefinedKeys<'a> {
+    keys: HashSet<&'a str>,
+    namespaces: HashSet<&'a str>,
+    suffixes: HashSet<&'a str>,
+}
+
+impl<'a> DefinedKeys<'a> {
+    fn new(keys: impl Iterator<Item = &'a str>) -> Self {
+        let mut defined = Self {
+            keys: HashSet::new(),
+            namespaces: HashSet::new(),
+            suffixes: HashSet::new(),
+        };
+        for key in keys {
+            defined.keys.insert(key);
+            for (i, _) in key.match_indices('.') {
+                defined.namespaces.insert(&key[..i]);
+                defined.suffixes.insert(&key[i + 1..]);
+            }
+        }
+        defined
+    }
+
+    fn covers(&self, key: &str) -> bool {
+        self.keys.contains(key) || self.namespaces.contains(key) || self.suffixes.contains(key)
+    }
+}
+
+/// Keys built at runtime or resolved relative to the current view can't be
+/// checked statically
+fn is_dynamic_key(key: &str) -> bool {
+    key.starts_with('.')
+        || key.ends_with('.')
+        || key.contains("#{")
+        || key.contains("${")
+        || key.contains("{{")
+        || key.contains(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_defined_keys_cover_partial_keys() {
+        let defined = DefinedKeys::new(["invoice.labels.add_new"].into_iter());
+        assert!(defined.covers("invoice.labels.add_new"));
+        assert!(defined.covers("invoice.labels"));
+        assert!(defined.covers("labels.add_new"));
+        assert!(!defined.covers("invoice.labels.edit"));
+        assert!(!defined.covers("invoice.label"));
+    }
+
+    #[test]
+    fn test_dynamic_keys_are_skipped() {
+        assert!(is_dynamic_key(".title"));
+        assert!(is_dynamic_key("errors.#{code}"));
+        assert!(is_dynamic_key("status.${state}"));
+        assert!(!is_dynamic_key("errors.not_found"));
+    }
+
+    #[test]
+    fn test_compares_sibling_locales() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("locales/en"))?;
+        fs::create_dir_all(dir.path().join("locales/de"))?;
+        fs::write(
+            dir.path().join("locales/en/common.json"),
+            r#"{"nav": {"home": "Home", "about": "About"}}"#,
+        )?;
+        fs::write(
+            dir.path().join("locales/de/common.json"),
+            r#"{"nav": {"home": "Start", "legacy": "Alt"}}"#,
+        )?;
+        fs::write(
+            dir.path().join("app.js"),
+            "t('nav.home');\nt('nav.contact');\n",
+        )?;
+
+        let report = MissingKeyFinder::new(dir.path().to_path_buf()).run()?;
+
+        assert_eq!(report.groups.len(), 1);
+        let group = &report.groups[0];
+        assert_eq!(group.pattern, "locales/{locale}/common.json");
+        assert_eq!(group.base_locale.as_deref(), Some("en"));
+
+        let de = group.locales.iter().find(|l| l.locale == "de").unwrap();
+        assert_eq!(de.missing, vec!["nav.about"]);
+        assert_eq!(de.extra, vec!["nav.legacy"]);
+        let en = group.locales.iter().find(|l| l.locale == "en").unwrap();
+        assert!(en.missing.is_empty() && en.extra.is_empty());
+
+        assert_eq!(report.undefined_keys.len(), 1);
+        assert_eq!(report.undefined_keys[0].key, "nav.contact");
+        assert_eq!(report.undefined_keys[0].locations[0].line, 2);
+        assert!(!report.is_clean());
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+//! Whole-project translation audits.
+//!
+//! Where the default search mode starts from a piece of text and traces it
+//! to code, audits start from the locale files themselves and report on the
+//! project as a whole.
+
+pub mod missing_keys;
+pub mod unused_keys;
+
+pub use missing_keys::{
+    LocaleDiff, LocaleGroup, MissingKeyFinder, MissingKeysReport, UndefinedKey,
+};
+pub use unused_keys::{UnusedKey, UnusedKeyFinder, UnusedKeysReport};
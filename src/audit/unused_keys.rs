@@ -0,0 +1,178 @@
+use crate::config::{self, ProjectConfig};
+use crate::error::Result;
+use crate::generate_partial_keys;
+use crate::parse::{KeyExtractor, TranslationEntry};
+use crate::search::{KeyMatcher, PatternMatcher};
+use crate::tree::Location;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// A translation key that is never referenced from code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnusedKey {
+    /// The full dot-notation key without its locale root (e.g., "invoice.labels.save")
+    pub key: String,
+    /// Every locale file line defining this key
+    pub locations: Vec<Location>,
+}
+
+/// Result of an unused-keys audit
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusedKeysReport {
+    /// Number of distinct keys found in locale files
+    pub keys_checked: usize,
+    /// Keys with zero code references, sorted by key
+    pub unused_keys: Vec<UnusedKey>,
+}
+
+/// Finds translation keys that no code references.
+///
+/// Every flattened key from the project's locale files is looked up with
+/// [`PatternMatcher`], including the partial variants produced by
+/// [`generate_partial_keys`], so keys reached through a cached namespace
+/// (`labels = t('invoice.labels'); labels.t('add_new')`) count as used.
+pub struct UnusedKeyFinder {
+    base_dir: PathBuf,
+    exclusions: Vec<String>,
+    verbose: bool,
+    config: ProjectConfig,
+}
+
+impl UnusedKeyFinder {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            exclusions: Vec::new(),
+            verbose: false,
+            config: ProjectConfig::default(),
+        }
+    }
+
+    /// Additional exclusions on top of the project-type defaults
+    pub fn with_exclusions(mut self, exclusions: Vec<String>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Patterns, exclusions and locale directories from `.cs.toml`
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn run(&self) -> Result<UnusedKeysReport> {
+        let project_type = config::detect_project_type(&self.base_dir);
+        let mut exclusions = self.config.exclusions(project_type);
+        exclusions.extend(self.exclusions.iter().cloned());
+
+        let mut extractor = KeyExtractor::new();
+        extractor.set_exclusions(exclusions.clone());
+        extractor.set_locale_dirs(self.config.locale_dirs());
+        extractor.set_verbose(self.verbose);
+        extractor.set_quiet(true);
+        let entries = extractor.extract_all(&self.base_dir)?;
+
+        let mut matcher =
+            PatternMatcher::with_patterns(self.config.patterns(), self.base_dir.clone());
+        matcher.set_exclusions(exclusions);
+        matcher.set_locale_dirs(self.config.locale_dirs());
+
+        let keys = group_by_key(entries);
+        let keys_checked = keys.len();
+
+        // Look every key and partial key up in a single search
+        let variants =
+            KeyMatcher::from_keys(keys.keys().flat_map(|key| generate_partial_keys(key)));
+        let referenced: HashSet<String> = matcher
+            .find_usages_of(&variants)?
+            .into_iter()
+            .map(|r| r.key_path)
+            .collect();
+
+        let unused_keys = keys
+            .into_iter()
+            .filter(|(key, _)| {
+                !generate_partial_keys(key)
+                    .iter()
+                    .any(|variant| referenced.contains(variant))
+            })
+            .map(|(key, locations)| UnusedKey { key, locations })
+            .collect();
+
+        Ok(UnusedKeysReport {
+            keys_checked,
+            unused_keys,
+        })
+    }
+}
+
+/// Collapse entries into distinct keys (sorted), keeping every definition site
+fn group_by_key(entries: Vec<TranslationEntry>) -> BTreeMap<String, Vec<Location>> {
+    let mut keys: BTreeMap<String, Vec<Location>> = BTreeMap::new();
+    for entry in entries {
+        keys.entry(entry.key)
+            .or_default()
+            .push(Location::new(entry.file, entry.line));
+    }
+    for locations in keys.values_mut() {
+        locations.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_group_by_key_merges_definitions() {
+        let entry = |key: &str, file: &str, line| TranslationEntry {
+            key: key.to_string(),
+            value: String::new(),
+            line,
+            file: PathBuf::from(file),
+            branch: None,
+            locale: None,
+        };
+        let keys = group_by_key(vec![
+            entry("a.b", "en.json", 3),
+            entry("a.a", "en.json", 2),
+            entry("a.b", "de.json", 3),
+        ]);
+
+        assert_eq!(keys.keys().collect::<Vec<_>>(), vec!["a.a", "a.b"]);
+        assert_eq!(keys["a.b"][0].file, PathBuf::from("de.json"));
+    }
+
+    #[test]
+    fn test_finds_unused_keys() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("locales"))?;
+        fs::create_dir_all(dir.path().join("app"))?;
+        fs::write(
+            dir.path().join("locales/en.json"),
+            r#"{"home": {"title": "Home", "subtitle": "Welcome"}, "legacy": {"banner": "Old"}}"#,
+        )?;
+        fs::write(
+            dir.path().join("app/home.js"),
+            "const title = t('home.title');\n",
+        )?;
+
+        let report = UnusedKeyFinder::new(dir.path().to_path_buf()).run()?;
+
+        assert_eq!(report.keys_checked, 3);
+        let unused: Vec<_> = report.unused_keys.iter().map(|k| k.key.as_str()).collect();
+        // "home.subtitle" is covered by its parent namespace "home" only when
+        // that namespace is referenced; here it is not
+        assert_eq!(unused, vec!["home.subtitle", "legacy.banner"]);
+        Ok(())
+    }
+}
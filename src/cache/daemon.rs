@@ -0,0 +1,401 @@
+//! Query daemon: the cache server answering whole searches and traces.
+//!
+//! A one-shot `cs` run spends most of its time rediscovering the same things:
+//! walking the project, parsing every locale file and locating function
+//! definitions. The cache server already outlives single runs, so it keeps
+//! that state warm per project root ([`WarmProject`]) and answers complete
+//! [`SearchQuery`]/[`TraceQuery`] requests from memory.
+//!
+//! A filesystem watcher keeps the warm state honest: file creations,
+//! removals and renames drop the file list, every changed path is forgotten
+//! by the parsed translation store and the symbol index is refreshed before
+//! the next trace. Parsed files are also validated against their mtime and
+//! size, so an edit racing the watcher is never served stale.
+//!
+//! The CLI talks to the daemon through [`QueryDaemon`] when one is running
+//! and searches in-process otherwise.
+
+use super::{lock, CacheRequest, CacheResponse, RemoteCache, SymbolIndex};
+use crate::error::{Result, SearchError};
+use crate::parse::ParsedFiles;
+use crate::search::project_files;
+use crate::{CallNode, CallTree, FileMatch, ProjectConfig, SearchQuery, SearchResult, TraceQuery};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Projects kept warm at the same time; the least recently used is dropped
+const MAX_WARM_PROJECTS: usize = 8;
+
+/// State shared with the watcher thread
+#[derive(Default)]
+struct WatchedState {
+    files: Mutex<Option<Arc<Vec<PathBuf>>>>,
+    parsed: ParsedFiles,
+    index_stale: AtomicBool,
+}
+
+impl WatchedState {
+    fn on_event(&self, event: notify::Result<notify::Event>) {
+        let Ok(event) = event else {
+            // Events may have been lost: start over
+            self.invalidate_all();
+            return;
+        };
+        if event.need_rescan() {
+            self.invalidate_all();
+            return;
+        }
+
+        // Content edits keep the file list; anything else may change it
+        if !matches!(
+            event.kind,
+            EventKind::Modify(notify::event::ModifyKind::Data(_))
+        ) && !matches!(event.kind, EventKind::Access(_))
+        {
+            self.forget_files();
+        }
+        for path in &event.paths {
+            self.parsed.forget(path);
+        }
+        self.index_stale.store(true, Ordering::SeqCst);
+    }
+
+    fn invalidate_all(&self) {
+        self.forget_files();
+        self.parsed.clear();
+        self.index_stale.store(true, Ordering::SeqCst);
+    }
+
+    fn forget_files(&self) {
+        *lock(&self.files) = None;
+    }
+}
+
+/// Everything the daemon keeps in memory for one project root
+pub(crate) struct WarmProject {
+    root: PathBuf,
+    state: Arc<WatchedState>,
+    index: Mutex<Option<Arc<SymbolIndex>>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl WarmProject {
+    /// Start watching `root`. Fails if the watcher cannot be set up, in which
+    /// case the project must be searched cold.
+    fn watch(root: PathBuf) -> Result<Self> {
+        let state = Arc::new(WatchedState::default());
+        let handler_state = Arc::clone(&state);
+        let mut watcher = notify::recommended_watcher(move |event| {
+            handler_state.on_event(event);
+        })
+        .map_err(|e| SearchError::Generic(format!("Failed to watch project: {}", e)))?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| SearchError::Generic(format!("Failed to watch project: {}", e)))?;
+
+        Ok(Self {
+            root,
+            state,
+            index: Mutex::new(None),
+            _watcher: watcher,
+        })
+    }
+
+    /// Every file of the project that `.gitignore` doesn't exclude
+    pub(crate) fn files(&self) -> Arc<Vec<PathBuf>> {
+        let mut files = lock(&self.state.files);
+        Arc::clone(files.get_or_insert_with(|| Arc::new(project_files(&self.root))))
+    }
+
+    /// Translation files parsed so far
+    pub(crate) fn parsed_files(&self) -> ParsedFiles {
+        self.state.parsed.clone()
+    }
+
+    /// The project's symbol index, refreshed if the watcher saw changes
+    fn symbol_index(&self) -> Option<Arc<SymbolIndex>> {
+        let mut index = lock(&self.index);
+        // Clear the flag first so changes during the refresh are not lost
+        if self.state.index_stale.swap(false, Ordering::SeqCst) {
+            // Only possible once traces still using the index are done
+            let refreshed = index
+                .as_mut()
+                .and_then(Arc::get_mut)
+                .is_some_and(|index| index.refresh().is_ok());
+            if !refreshed {
+                *index = None;
+            }
+        }
+        if index.is_none() {
+            let mut opened = SymbolIndex::open(&self.root).ok()?;
+            opened.refresh().ok()?;
+            *index = Some(Arc::new(opened));
+        }
+        index.clone()
+    }
+
+    /// Close the symbol index. All projects share one database, which only a
+    /// single handle may hold open.
+    fn close_symbol_index(&self) {
+        *lock(&self.index) = None;
+    }
+}
+
+/// Warm projects of the running daemon, most recently used last.
+///
+/// The list is only locked to look a project up; searches of the same or
+/// different projects then run side by side on the server's workers.
+#[derive(Default)]
+pub(crate) struct WarmProjects {
+    projects: Mutex<Vec<Arc<WarmProject>>>,
+}
+
+impl WarmProjects {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop all warm state, e.g. when the cache is cleared
+    pub(crate) fn clear(&self) {
+        lock(&self.projects).clear();
+    }
+
+    /// Number of projects kept warm
+    pub(crate) fn len(&self) -> usize {
+        lock(&self.projects).len()
+    }
+
+    pub(crate) fn search(
+        &self,
+        mut query: SearchQuery,
+        config_files: &[PathBuf],
+    ) -> Result<(SearchResult, Vec<FileMatch>)> {
+        query.config = ProjectConfig::from_files(config_files)?;
+        query.quiet = true;
+        query.verbose = false;
+        let root = absolute_base_dir(&query.base_dir)?;
+        let project = if root.is_dir() {
+            self.project(&root)
+        } else {
+            None
+        };
+        crate::search_project(query, project.as_deref())
+    }
+
+    pub(crate) fn trace(
+        &self,
+        mut query: TraceQuery,
+        config_files: &[PathBuf],
+    ) -> Result<Option<CallTree>> {
+        query.config = ProjectConfig::from_files(config_files)?;
+        let root = absolute_base_dir(&query.base_dir)?;
+        crate::trace_project(query, |_| {
+            let project = self.project(&root)?;
+            for other in lock(&self.projects).iter() {
+                if other.root != root {
+                    other.close_symbol_index();
+                }
+            }
+            project.symbol_index()
+        })
+    }
+
+    /// The warm state for `root`, started on first use
+    fn project(&self, root: &Path) -> Option<Arc<WarmProject>> {
+        let mut projects = lock(&self.projects);
+        let project = match projects.iter().position(|p| p.root == root) {
+            Some(position) => projects.remove(position),
+            None => {
+                let project = Arc::new(WarmProject::watch(root.to_path_buf()).ok()?);
+                if projects.len() == MAX_WARM_PROJECTS {
+                    projects.remove(0);
+                }
+                project
+            }
+        };
+        projects.push(Arc::clone(&project));
+        Some(project)
+    }
+}
+
+fn absolute_base_dir(base_dir: &Option<PathBuf>) -> Result<PathBuf> {
+    base_dir
+        .clone()
+        .filter(|dir| dir.is_absolute())
+        .ok_or_else(|| SearchError::Generic("Query daemon needs an absolute base directory".into()))
+}
+
+/// Client for a running query daemon
+pub struct QueryDaemon {
+    remote: RemoteCache,
+}
+
+impl QueryDaemon {
+    /// Connect to the daemon if one is running. Never starts one; that is
+    /// left to the cache client of an in-process search.
+    pub fn connect() -> Option<Self> {
+        if std::env::var("CS_DISABLE_CACHE_SERVER").is_ok() {
+            return None;
+        }
+        RemoteCache::connect().ok().map(|remote| Self { remote })
+    }
+
+    /// Run `query` in the daemon. Result paths are spelled relative to the
+    /// query's base directory just like an in-process search would.
+    ///
+    /// `None` when the daemon could not answer: it went away, or does not
+    /// run this kind of query (single files). The caller then searches
+    /// in-process. Errors of the search itself come back as they would
+    /// in-process, so the caller must not retry them.
+    pub fn search(&self, mut query: SearchQuery) -> Option<Result<(SearchResult, Vec<FileMatch>)>> {
+        let paths = Relocation::new(&query.base_dir).ok()?;
+        query.base_dir = Some(paths.absolute.clone());
+        let config_files = query.config.files().to_vec();
+
+        let response = self
+            .remote
+            .send_request(CacheRequest::Search {
+                query,
+                config_files,
+            })
+            .ok()?;
+        match response {
+            CacheResponse::Search(Ok((mut result, mut file_matches))) => {
+                for entry in &mut result.translation_entries {
+                    paths.restore(&mut entry.file);
+                }
+                for reference in &mut result.code_references {
+                    paths.restore(&mut reference.file);
+                }
+                for file_match in &mut file_matches {
+                    paths.restore(&mut file_match.path);
+                }
+                Some(Ok((result, file_matches)))
+            }
+            CacheResponse::Search(Err(e)) => Some(Err(e.into())),
+            _ => None,
+        }
+    }
+
+    /// Trace `query` in the daemon, see [`search`](Self::search)
+    pub fn trace(&self, mut query: TraceQuery) -> Option<Result<Option<CallTree>>> {
+        let paths = Relocation::new(&query.base_dir).ok()?;
+        query.base_dir = Some(paths.absolute.clone());
+        if let Some((file, _)) = &mut query.definition {
+            *file = std::env::current_dir().ok()?.join(&*file);
+        }
+        let config_files = query.config.files().to_vec();
+
+        let response = self
+            .remote
+            .send_request(CacheRequest::Trace {
+                query,
+                config_files,
+            })
+            .ok()?;
+        match response {
+            CacheResponse::Trace(Ok(tree)) => Some(Ok(tree.map(|(mut root, mut callers)| {
+                paths.restore_node(&mut root);
+                for caller in &mut callers {
+                    paths.restore_node(caller);
+                }
+                CallTree { root, callers }
+            }))),
+            CacheResponse::Trace(Err(e)) => Some(Err(e.into())),
+            _ => None,
+        }
+    }
+}
+
+/// Maps paths under the absolute base directory sent to the daemon back
+/// under the base directory the user gave
+struct Relocation {
+    original: Option<PathBuf>,
+    absolute: PathBuf,
+}
+
+impl Relocation {
+    fn new(base_dir: &Option<PathBuf>) -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let absolute = match base_dir {
+            Some(dir) => cwd.join(dir),
+            None => cwd,
+        };
+        if !absolute.is_dir() {
+            // Single file searches are cheap enough in-process
+            return Err(SearchError::Generic(
+                "Query daemon only searches directories".into(),
+            ));
+        }
+        Ok(Self {
+            original: base_dir.clone().filter(|dir| dir.is_relative()),
+            absolute,
+        })
+    }
+
+    fn restore(&self, path: &mut PathBuf) {
+        let Some(original) = &self.original else {
+            return;
+        };
+        if let Ok(relative) = path.strip_prefix(&self.absolute) {
+            *path = if relative.as_os_str().is_empty() {
+                original.clone()
+            } else {
+                original.join(relative)
+            };
+        }
+    }
+
+    fn restore_node(&self, node: &mut CallNode) {
+        self.restore(&mut node.def.file);
+        for child in &mut node.children {
+            self.restore_node(child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relocation_restores_relative_paths() {
+        let cwd = std::env::current_dir().unwrap();
+        let paths = Relocation::new(&Some(PathBuf::from("src"))).unwrap();
+        assert_eq!(paths.absolute, cwd.join("src"));
+
+        let mut path = cwd.join("src/lib.rs");
+        paths.restore(&mut path);
+        assert_eq!(path, PathBuf::from("src/lib.rs"));
+
+        // Absolute bases are sent as they are
+        let paths = Relocation::new(&Some(cwd.join("src"))).unwrap();
+        let mut path = cwd.join("src/lib.rs");
+        paths.restore(&mut path);
+        assert_eq!(path, cwd.join("src/lib.rs"));
+
+        assert!(Relocation::new(&Some(PathBuf::from("src/lib.rs"))).is_err());
+    }
+
+    #[test]
+    fn test_watched_state_invalidation() {
+        let state = WatchedState::default();
+        *state.files.lock().unwrap() = Some(Arc::new(vec![PathBuf::from("a.js")]));
+
+        let edit = notify::Event::new(EventKind::Modify(notify::event::ModifyKind::Data(
+            notify::event::DataChange::Content,
+        )))
+        .add_path(PathBuf::from("a.js"));
+        state.on_event(Ok(edit));
+        assert!(state.files.lock().unwrap().is_some());
+        assert!(state.index_stale.swap(false, Ordering::SeqCst));
+
+        let create = notify::Event::new(EventKind::Create(notify::event::CreateKind::File))
+            .add_path(PathBuf::from("b.js"));
+        state.on_event(Ok(create));
+        assert!(state.files.lock().unwrap().is_none());
+        assert!(state.index_stale.load(Ordering::SeqCst));
+    }
+}
@@ -17,8 +17,11 @@
 //!
 //! 3. **Process-Level Concurrency**
 //!    - Background cache server process (optional)
-//!    - TCP communication between processes
+//!    - Versioned frames over a Unix socket only its owner can reach
+//!    - A bounded pool of worker threads answers clients side by side
 //!    - Demonstrates concurrency beyond threads
+//!    - The server doubles as a query daemon answering whole searches from
+//!      warm, watcher-invalidated state (see [`daemon`])
 //!
 //! ## Design Decisions
 //!
@@ -49,37 +52,79 @@
 //! - Clear ownership boundaries
 //! - Letting libraries handle concurrency (like `sled`)
 
+pub mod daemon;
+mod server;
+pub mod symbol_index;
+mod transport;
+
+pub use daemon::QueryDaemon;
+pub use symbol_index::{IndexStats, SymbolIndex, SymbolLocation};
+
 use crate::error::{Result, SearchError};
 use crate::parse::TranslationEntry;
+use crate::{CallNode, FileMatch, FunctionDef, SearchQuery, SearchResult, TraceQuery};
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use sled::Db;
 use std::fs;
-use std::io::{Read, Write};
-use std::net::{Shutdown, TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
 use std::time::{Duration, SystemTime};
+use transport::{MAX_RESPONSE_SIZE, PROTOCOL_VERSION};
 
 const CACHE_DIR_NAME: &str = "cs";
-const PORT_FILE: &str = "cache.port";
+/// sled tree holding the hit and miss counters
+const COUNTERS_TREE: &str = "counters";
+const HITS_KEY: &str = "hits";
+const MISSES_KEY: &str = "misses";
 const SERVER_FLAG: &str = "--cache-server";
 const FRONT_CACHE_CAP: usize = 512;
 const MAX_CACHE_SIZE: u64 = 1_000_000_000;
 const MAX_CACHE_AGE_SECS: u64 = 30 * 24 * 60 * 60;
 const CLEANUP_INTERVAL_SECS: u64 = 6 * 60 * 60;
-
-/// Cache value stored for each (file, query) pair
+/// Marks cache values written by `cs`, followed by [`CACHE_FORMAT_VERSION`]
+const CACHE_FORMAT_MAGIC: [u8; 4] = *b"csfc";
+/// Bump whenever `CacheValue` or `TranslationEntry` change shape, or parsers
+/// change what they produce, so values written by other releases read as
+/// misses instead of garbage
+const CACHE_FORMAT_VERSION: u32 = 2;
+
+/// Every entry of one translation file, stored under the file's path.
+///
+/// Entries are kept regardless of any search, so one parse serves every
+/// later query until the file's mtime or size changes.
 #[derive(Serialize, Deserialize, Clone)]
 struct CacheValue {
     mtime_secs: u64,
     file_size: u64,
     last_accessed: u64,
-    results: Vec<TranslationEntry>,
+    entries: Vec<TranslationEntry>,
 }
 
-/// Cross-process cache client: tries a background TCP server; falls back to local cache.
+impl CacheValue {
+    /// The value behind its format header
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = CACHE_FORMAT_MAGIC.to_vec();
+        bytes.extend_from_slice(&CACHE_FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self)
+            .map_err(|e| SearchError::Generic(format!("Failed to serialize cache: {}", e)))?;
+        Ok(bytes)
+    }
+
+    /// `None` for values of another format version or that don't decode
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let payload = bytes.strip_prefix(&CACHE_FORMAT_MAGIC)?;
+        let (version, payload) = payload.split_at_checked(4)?;
+        if version != CACHE_FORMAT_VERSION.to_le_bytes() {
+            return None;
+        }
+        bincode::deserialize(payload).ok()
+    }
+}
+
+/// Cache of parsed translation files, shared across processes: tries the
+/// background cache server; falls back to local cache.
 pub struct SearchResultCache {
     backend: CacheBackend,
 }
@@ -93,31 +138,150 @@ enum CacheBackend {
 enum CacheRequest {
     Get {
         file: PathBuf,
-        query: String,
-        case_sensitive: bool,
         mtime_secs: u64,
         file_size: u64,
     },
     Set {
         file: PathBuf,
-        query: String,
-        case_sensitive: bool,
         mtime_secs: u64,
         file_size: u64,
-        results: Vec<TranslationEntry>,
+        entries: Vec<TranslationEntry>,
     },
     Clear,
     Ping,
+    /// Run a whole search; the daemon loads the config from `config_files`
+    Search {
+        query: SearchQuery,
+        config_files: Vec<PathBuf>,
+    },
+    /// Run a whole trace, see `Search`
+    Trace {
+        query: TraceQuery,
+        config_files: Vec<PathBuf>,
+    },
+    Stats,
+    /// Remove entries last used longer ago than `older_than`
+    Prune {
+        older_than: Duration,
+    },
+    Status,
+    /// Exit once the requests already accepted are answered
+    Stop,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 enum CacheResponse {
     Get(Option<Vec<TranslationEntry>>),
     Ack(bool),
+    Search(std::result::Result<(SearchResult, Vec<FileMatch>), QueryError>),
+    /// A found call tree as its root and callers (see `CallTree`)
+    Trace(std::result::Result<Option<(CallNode, Vec<CallNode>)>, QueryError>),
+    Stats(std::result::Result<CacheStats, String>),
+    /// Number of entries removed
+    Pruned(std::result::Result<usize, String>),
+    Status(ServerStatus),
+}
+
+/// Why a search or trace failed in the daemon. The errors the CLI reports
+/// with their own guidance keep their fields; the rest travel as messages.
+#[derive(Serialize, Deserialize, Debug)]
+enum QueryError {
+    NoTranslationFiles {
+        text: String,
+        searched_paths: String,
+    },
+    ConfigError {
+        file: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    AmbiguousFunction {
+        name: String,
+        candidates: Vec<FunctionDef>,
+    },
+    Other(String),
+}
+
+impl From<SearchError> for QueryError {
+    fn from(error: SearchError) -> Self {
+        match error {
+            SearchError::NoTranslationFiles {
+                text,
+                searched_paths,
+            } => Self::NoTranslationFiles {
+                text,
+                searched_paths,
+            },
+            SearchError::ConfigError { file, line, reason } => {
+                Self::ConfigError { file, line, reason }
+            }
+            SearchError::AmbiguousFunction { name, candidates } => {
+                Self::AmbiguousFunction { name, candidates }
+            }
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<QueryError> for SearchError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::NoTranslationFiles {
+                text,
+                searched_paths,
+            } => Self::NoTranslationFiles {
+                text,
+                searched_paths,
+            },
+            QueryError::ConfigError { file, line, reason } => {
+                Self::ConfigError { file, line, reason }
+            }
+            QueryError::AmbiguousFunction { name, candidates } => {
+                Self::AmbiguousFunction { name, candidates }
+            }
+            QueryError::Other(message) => Self::Generic(message),
+        }
+    }
+}
+
+/// What the result cache holds (`cs cache stats`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Cached translation files
+    pub entries: usize,
+    /// Size of the cache database, in bytes
+    pub size_on_disk: u64,
+    /// Lookups answered from the cache since it was last cleared
+    pub hits: u64,
+    /// Lookups the cache could not answer since it was last cleared
+    pub misses: u64,
+    /// When the least recently used entry was last used
+    pub oldest_access: Option<SystemTime>,
+}
+
+/// A running cache server (`cs cache status`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub pid: u32,
+    /// Socket path, or `host:port` where Unix sockets are unavailable
+    pub address: String,
+    pub started: SystemTime,
+    /// The server exits after receiving no requests for this long
+    pub idle_timeout: Duration,
+    /// Projects the query daemon keeps warm
+    pub warm_projects: usize,
+    /// Connections closed unanswered because every worker was busy
+    pub turned_away: usize,
+}
+
+impl ServerStatus {
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed().unwrap_or_default()
+    }
 }
 
 impl SearchResultCache {
-    /// Create a cache client. Prefers a background TCP server unless disabled.
+    /// Create a cache client. Prefers the background cache server unless disabled.
     pub fn new() -> Result<Self> {
         if std::env::var("CS_DISABLE_CACHE_SERVER").is_ok() {
             return Ok(Self {
@@ -136,6 +300,42 @@ impl SearchResultCache {
         })
     }
 
+    /// Like [`new`](Self::new), but never starts a server: use the running one
+    /// if there is one, the cache database otherwise.
+    pub fn open_existing() -> Result<Self> {
+        if std::env::var("CS_DISABLE_CACHE_SERVER").is_err() {
+            if let Ok(remote) = RemoteCache::connect() {
+                return Ok(Self {
+                    backend: CacheBackend::Remote(remote),
+                });
+            }
+        }
+        Ok(Self {
+            backend: CacheBackend::Local(LocalCache::new()?),
+        })
+    }
+
+    /// Status of the running cache server, `None` if none is running.
+    ///
+    /// Fails with [`SearchError::ProtocolMismatch`] if the server belongs to
+    /// another release of `cs`.
+    pub fn server_status() -> Result<Option<ServerStatus>> {
+        match RemoteCache::connect() {
+            Ok(remote) => remote.status().map(Some),
+            Err(e @ SearchError::ProtocolMismatch { .. }) => Err(e),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Ask the running cache server to exit; `false` if none was running
+    pub fn stop_server() -> Result<bool> {
+        match RemoteCache::connect() {
+            Ok(remote) => remote.stop().map(|_| true),
+            Err(e @ SearchError::ProtocolMismatch { .. }) => Err(e),
+            Err(_) => Ok(false),
+        }
+    }
+
     /// Test helper: force cache to use a specific directory (local only).
     pub fn with_cache_dir(cache_dir: PathBuf) -> Result<Self> {
         Ok(Self {
@@ -143,41 +343,32 @@ impl SearchResultCache {
         })
     }
 
+    /// Every entry of `file`, if it was cached with this mtime and size
     pub fn get(
         &self,
         file: &Path,
-        query: &str,
-        case_sensitive: bool,
         current_mtime: SystemTime,
         current_size: u64,
     ) -> Option<Vec<TranslationEntry>> {
         match &self.backend {
-            CacheBackend::Local(inner) => {
-                inner.get(file, query, case_sensitive, current_mtime, current_size)
+            CacheBackend::Local(inner) => inner.get(file, current_mtime, current_size),
+            CacheBackend::Remote(remote) => {
+                remote.get(file, current_mtime, current_size).ok().flatten()
             }
-            CacheBackend::Remote(remote) => remote
-                .get(file, query, case_sensitive, current_mtime, current_size)
-                .ok()
-                .flatten(),
         }
     }
 
+    /// Store every entry of `file`, replacing what was cached for it
     pub fn set(
         &self,
         file: &Path,
-        query: &str,
-        case_sensitive: bool,
         mtime: SystemTime,
         file_size: u64,
-        results: &[TranslationEntry],
+        entries: &[TranslationEntry],
     ) -> Result<()> {
         match &self.backend {
-            CacheBackend::Local(inner) => {
-                inner.set(file, query, case_sensitive, mtime, file_size, results)
-            }
-            CacheBackend::Remote(remote) => {
-                remote.set(file, query, case_sensitive, mtime, file_size, results)
-            }
+            CacheBackend::Local(inner) => inner.set(file, mtime, file_size, entries),
+            CacheBackend::Remote(remote) => remote.set(file, mtime, file_size, entries),
         }
     }
 
@@ -188,9 +379,25 @@ impl SearchResultCache {
         }
     }
 
+    pub fn stats(&self) -> Result<CacheStats> {
+        match &self.backend {
+            CacheBackend::Local(inner) => inner.stats(),
+            CacheBackend::Remote(remote) => remote.stats(),
+        }
+    }
+
+    /// Remove entries last used longer ago than `older_than`, returning how
+    /// many were removed
+    pub fn prune(&self, older_than: Duration) -> Result<usize> {
+        match &self.backend {
+            CacheBackend::Local(inner) => inner.prune(older_than),
+            CacheBackend::Remote(remote) => remote.prune(older_than),
+        }
+    }
+
     /// Hidden entrypoint: block and run cache server.
     pub fn start_server_blocking() -> Result<()> {
-        run_cache_server()
+        server::run()
     }
 }
 
@@ -213,7 +420,7 @@ impl SearchResultCache {
 /// ```
 ///
 /// **Why `Mutex<HashMap>` for front_cache?**
-/// 1. **Small, hot data** - LRU cache for recent queries
+/// 1. **Small, hot data** - LRU cache for recently read files
 /// 2. **Infrequent writes** - Only on cache misses
 /// 3. **Short lock duration** - Just hash lookup/insert
 /// 4. **Simpler than alternatives** - No need for lock-free structures
@@ -237,6 +444,7 @@ impl SearchResultCache {
 /// - Choose the right tool for the job!
 struct LocalCache {
     db: Db,
+    counters: sled::Tree,
     last_cleanup: SystemTime,
     front_cache: Mutex<HashMap<Vec<u8>, CacheValue>>,
     cache_dir: PathBuf,
@@ -244,19 +452,22 @@ struct LocalCache {
 
 impl LocalCache {
     fn cache_dir() -> PathBuf {
-        dirs::cache_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(CACHE_DIR_NAME)
+        default_cache_dir()
     }
 
     fn with_cache_dir(cache_dir: PathBuf) -> Result<Self> {
-        fs::create_dir_all(&cache_dir)?;
+        transport::create_private_dir(&cache_dir)?;
         let db = sled::open(cache_dir.join("db"))
             .map_err(|e| SearchError::Generic(format!("Failed to open cache: {}", e)))?;
 
+        let counters = db
+            .open_tree(COUNTERS_TREE)
+            .map_err(|e| SearchError::Generic(format!("Failed to open cache: {}", e)))?;
+
         let last_cleanup = Self::read_last_cleanup_marker(&cache_dir)?;
         let cache = Self {
             db,
+            counters,
             last_cleanup,
             front_cache: Mutex::new(HashMap::new()),
             cache_dir,
@@ -272,19 +483,36 @@ impl LocalCache {
     fn get(
         &self,
         file: &Path,
-        query: &str,
-        case_sensitive: bool,
         current_mtime: SystemTime,
         current_size: u64,
     ) -> Option<Vec<TranslationEntry>> {
-        let key = self.make_key(file, query, case_sensitive);
+        let found = self.lookup(file, current_mtime, current_size);
+        self.increment(if found.is_some() {
+            HITS_KEY
+        } else {
+            MISSES_KEY
+        });
+        found
+    }
+
+    fn lookup(
+        &self,
+        file: &Path,
+        current_mtime: SystemTime,
+        current_size: u64,
+    ) -> Option<Vec<TranslationEntry>> {
+        let key = Self::make_key(file);
 
         if let Some(entries) = self.front_get(&key, current_mtime, current_size) {
             return Some(entries);
         }
 
         let cached_bytes = self.db.get(&key).ok()??;
-        let mut cached: CacheValue = bincode::deserialize(&cached_bytes).ok()?;
+        let Some(mut cached) = CacheValue::decode(&cached_bytes) else {
+            // Written by another release
+            let _ = self.db.remove(&key);
+            return None;
+        };
 
         let current_secs = current_mtime
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -306,12 +534,12 @@ impl LocalCache {
         if cached.mtime_secs == current_secs && cached.file_size == current_size {
             cached.last_accessed = now;
 
-            if let Ok(updated_bytes) = bincode::serialize(&cached) {
+            if let Ok(updated_bytes) = cached.encode() {
                 let _ = self.db.insert(&key, updated_bytes);
             }
 
             self.front_set(key.clone(), cached.clone());
-            Some(cached.results)
+            Some(cached.entries)
         } else {
             // File changed - delete stale entry
             let _ = self.db.remove(&key);
@@ -322,13 +550,11 @@ impl LocalCache {
     fn set(
         &self,
         file: &Path,
-        query: &str,
-        case_sensitive: bool,
         mtime: SystemTime,
         file_size: u64,
-        results: &[TranslationEntry],
+        entries: &[TranslationEntry],
     ) -> Result<()> {
-        let key = self.make_key(file, query, case_sensitive);
+        let key = Self::make_key(file);
 
         let mtime_secs = mtime
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -344,11 +570,10 @@ impl LocalCache {
             mtime_secs,
             file_size,
             last_accessed,
-            results: results.to_vec(),
+            entries: entries.to_vec(),
         };
 
-        let value_bytes = bincode::serialize(&value)
-            .map_err(|e| SearchError::Generic(format!("Failed to serialize cache: {}", e)))?;
+        let value_bytes = value.encode()?;
 
         self.front_set(key.clone(), value.clone());
 
@@ -362,6 +587,7 @@ impl LocalCache {
     fn clear(&self) -> Result<()> {
         self.db
             .clear()
+            .and_then(|_| self.counters.clear())
             .map_err(|e| SearchError::Generic(format!("Failed to clear cache: {}", e)))?;
         if let Ok(mut map) = self.front_cache.lock() {
             map.clear();
@@ -370,6 +596,72 @@ impl LocalCache {
         Ok(())
     }
 
+    fn stats(&self) -> Result<CacheStats> {
+        // Writes only count towards the size once they reach the disk
+        let _ = self.db.flush();
+        let size_on_disk = self
+            .db
+            .size_on_disk()
+            .map_err(|e| SearchError::Generic(format!("Failed to get cache size: {}", e)))?;
+        let oldest_access = self
+            .db
+            .iter()
+            .values()
+            .flatten()
+            .filter_map(|value| CacheValue::decode(&value))
+            .map(|value| value.last_accessed)
+            .min()
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+
+        Ok(CacheStats {
+            entries: self.db.len(),
+            size_on_disk,
+            hits: self.counter(HITS_KEY),
+            misses: self.counter(MISSES_KEY),
+            oldest_access,
+        })
+    }
+
+    fn prune(&self, older_than: Duration) -> Result<usize> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| SearchError::Generic(format!("Failed to get current time: {}", e)))?
+            .as_secs();
+        let cutoff = now.saturating_sub(older_than.as_secs());
+
+        let mut removed = 0;
+        for (key, value) in self.db.iter().flatten() {
+            // Entries that no longer decode are useless as well
+            let stale = CacheValue::decode(&value).is_none_or(|value| value.last_accessed < cutoff);
+            if stale && self.db.remove(&key).is_ok_and(|old| old.is_some()) {
+                removed += 1;
+            }
+        }
+        if let Ok(mut map) = self.front_cache.lock() {
+            map.retain(|_, value| value.last_accessed >= cutoff);
+        }
+        let _ = self.db.flush();
+        Ok(removed)
+    }
+
+    fn counter(&self, key: &str) -> u64 {
+        self.counters
+            .get(key)
+            .ok()
+            .flatten()
+            .and_then(|bytes| bytes.as_ref().try_into().ok())
+            .map_or(0, u64::from_le_bytes)
+    }
+
+    fn increment(&self, key: &str) {
+        let _ = self.counters.update_and_fetch(key, |old| {
+            let count = old
+                .and_then(|bytes| bytes.try_into().ok())
+                .map_or(0, u64::from_le_bytes);
+            Some((count + 1).to_le_bytes().to_vec())
+        });
+    }
+
     fn front_get(
         &self,
         key: &[u8],
@@ -383,7 +675,7 @@ impl LocalCache {
             .ok()?
             .as_secs();
         if entry.mtime_secs == current_secs && entry.file_size == current_size {
-            Some(entry.results.clone())
+            Some(entry.entries.clone())
         } else {
             None
         }
@@ -404,13 +696,8 @@ impl LocalCache {
         }
     }
 
-    fn make_key(&self, file: &Path, query: &str, case_sensitive: bool) -> Vec<u8> {
-        let normalized_query = if case_sensitive {
-            query.to_string()
-        } else {
-            query.to_lowercase()
-        };
-        format!("{}|{}", file.display(), normalized_query).into_bytes()
+    fn make_key(file: &Path) -> Vec<u8> {
+        file.display().to_string().into_bytes()
     }
 
     fn maybe_cleanup_on_open(&self) -> Result<()> {
@@ -458,15 +745,11 @@ impl LocalCache {
             .iter()
             .flatten()
             .filter_map(|(key, value)| {
-                // Try to deserialize, convert Result to Option
-                bincode::deserialize::<CacheValue>(&value)
-                    .ok()
-                    // Filter out expired entries
-                    .filter(|cache_value| {
-                        now.saturating_sub(cache_value.last_accessed) <= MAX_CACHE_AGE_SECS
-                    })
-                    // Map to the tuple we need
-                    .map(|cache_value| (key.to_vec(), cache_value.last_accessed))
+                // Values of another format version go first
+                let last_accessed = CacheValue::decode(&value).map_or(0, |v| v.last_accessed);
+                // Filter out expired entries
+                (now.saturating_sub(last_accessed) <= MAX_CACHE_AGE_SECS)
+                    .then(|| (key.to_vec(), last_accessed))
             })
             .collect();
 
@@ -521,33 +804,35 @@ impl LocalCache {
 }
 
 struct RemoteCache {
-    addr: String,
+    cache_dir: PathBuf,
 }
 
 impl RemoteCache {
+    /// Connect to a running server of this release
+    fn connect() -> Result<Self> {
+        let remote = Self {
+            cache_dir: LocalCache::cache_dir(),
+        };
+        remote.ping()?;
+        Ok(remote)
+    }
+
     fn connect_or_spawn() -> Result<Option<Self>> {
-        if let Some(addr) = read_port_file() {
-            if Self::ping_addr(&addr).is_ok() {
-                return Ok(Some(Self { addr }));
-            }
+        match Self::connect() {
+            Ok(remote) => return Ok(Some(remote)),
+            // A server of another release holds the socket; leave it alone
+            Err(SearchError::ProtocolMismatch { .. }) => return Ok(None),
+            Err(_) => {}
         }
 
         spawn_server()?;
-
-        if let Some(addr) = read_port_file() {
-            if Self::ping_addr(&addr).is_ok() {
-                return Ok(Some(Self { addr }));
-            }
-        }
-
-        Ok(None)
+        Ok(Self::connect().ok())
     }
 
-    fn ping_addr(addr: &str) -> Result<()> {
-        let client = Self {
-            addr: addr.to_string(),
-        };
-        match client.send_request(CacheRequest::Ping)? {
+    /// The version handshake: every frame carries the protocol version, and
+    /// a server of another release answers with its own
+    fn ping(&self) -> Result<()> {
+        match self.send_request(CacheRequest::Ping)? {
             CacheResponse::Ack(true) => Ok(()),
             _ => Err(SearchError::Generic(
                 "Cache server did not acknowledge ping".to_string(),
@@ -558,8 +843,6 @@ impl RemoteCache {
     fn get(
         &self,
         file: &Path,
-        query: &str,
-        case_sensitive: bool,
         current_mtime: SystemTime,
         current_size: u64,
     ) -> Result<Option<Vec<TranslationEntry>>> {
@@ -570,8 +853,6 @@ impl RemoteCache {
 
         let req = CacheRequest::Get {
             file: file.to_path_buf(),
-            query: query.to_string(),
-            case_sensitive,
             mtime_secs,
             file_size: current_size,
         };
@@ -585,11 +866,9 @@ impl RemoteCache {
     fn set(
         &self,
         file: &Path,
-        query: &str,
-        case_sensitive: bool,
         mtime: SystemTime,
         file_size: u64,
-        results: &[TranslationEntry],
+        entries: &[TranslationEntry],
     ) -> Result<()> {
         let mtime_secs = mtime
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -598,11 +877,9 @@ impl RemoteCache {
 
         let req = CacheRequest::Set {
             file: file.to_path_buf(),
-            query: query.to_string(),
-            case_sensitive,
             mtime_secs,
             file_size,
-            results: results.to_vec(),
+            entries: entries.to_vec(),
         };
 
         match self.send_request(req)? {
@@ -618,110 +895,62 @@ impl RemoteCache {
         }
     }
 
-    fn send_request(&self, req: CacheRequest) -> Result<CacheResponse> {
-        let mut stream = TcpStream::connect(&self.addr)
-            .map_err(|e| SearchError::Generic(format!("Failed to connect cache server: {}", e)))?;
-
-        let bytes = bincode::serialize(&req)
-            .map_err(|e| SearchError::Generic(format!("Failed to encode cache request: {}", e)))?;
-
-        stream
-            .write_all(&bytes)
-            .map_err(|e| SearchError::Generic(format!("Failed to write cache request: {}", e)))?;
-        let _ = stream.shutdown(Shutdown::Write);
-
-        let mut buf = Vec::new();
-        stream
-            .read_to_end(&mut buf)
-            .map_err(|e| SearchError::Generic(format!("Failed to read cache response: {}", e)))?;
-
-        let resp: CacheResponse = bincode::deserialize(&buf)
-            .map_err(|e| SearchError::Generic(format!("Failed to decode cache response: {}", e)))?;
-        Ok(resp)
+    fn stats(&self) -> Result<CacheStats> {
+        match self.send_request(CacheRequest::Stats)? {
+            CacheResponse::Stats(stats) => stats.map_err(SearchError::Generic),
+            _ => Err(SearchError::Generic("Invalid cache response".to_string())),
+        }
     }
-}
 
-/// ---------- Server ----------
-fn run_cache_server() -> Result<()> {
-    let cache_dir = LocalCache::cache_dir();
-    fs::create_dir_all(&cache_dir)?;
-
-    let listener = TcpListener::bind("127.0.0.1:0")
-        .map_err(|e| SearchError::Generic(format!("Failed to bind cache server: {}", e)))?;
-    let addr = listener
-        .local_addr()
-        .map_err(|e| SearchError::Generic(format!("Failed to get cache server address: {}", e)))?;
-    write_port_file(&cache_dir, &addr.to_string())?;
-
-    let local = LocalCache::with_cache_dir(cache_dir)?;
-    for stream in listener.incoming() {
-        match stream {
-            Ok(mut stream) => {
-                let _ = handle_connection(&local, &mut stream);
-            }
-            Err(_) => continue,
+    fn prune(&self, older_than: Duration) -> Result<usize> {
+        match self.send_request(CacheRequest::Prune { older_than })? {
+            CacheResponse::Pruned(removed) => removed.map_err(SearchError::Generic),
+            _ => Err(SearchError::Generic("Invalid cache response".to_string())),
         }
     }
-    Ok(())
-}
 
-fn handle_connection(local: &LocalCache, stream: &mut TcpStream) -> Result<()> {
-    let mut buf = Vec::new();
-    stream.read_to_end(&mut buf)?;
-    let req: CacheRequest = bincode::deserialize(&buf)
-        .map_err(|e| SearchError::Generic(format!("Failed to decode cache request: {}", e)))?;
-
-    let resp = match req {
-        CacheRequest::Get {
-            file,
-            query,
-            case_sensitive,
-            mtime_secs,
-            file_size,
-        } => {
-            let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(mtime_secs);
-            let hit = local.get(&file, &query, case_sensitive, ts, file_size);
-            CacheResponse::Get(hit)
-        }
-        CacheRequest::Set {
-            file,
-            query,
-            case_sensitive,
-            mtime_secs,
-            file_size,
-            results,
-        } => {
-            let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(mtime_secs);
-            let res = local.set(&file, &query, case_sensitive, ts, file_size, &results);
-            CacheResponse::Ack(res.is_ok())
+    fn status(&self) -> Result<ServerStatus> {
+        match self.send_request(CacheRequest::Status)? {
+            CacheResponse::Status(status) => Ok(status),
+            _ => Err(SearchError::Generic("Invalid cache response".to_string())),
         }
-        CacheRequest::Clear => {
-            let res = local.clear();
-            CacheResponse::Ack(res.is_ok())
+    }
+
+    fn stop(&self) -> Result<()> {
+        match self.send_request(CacheRequest::Stop)? {
+            CacheResponse::Ack(true) => Ok(()),
+            _ => Err(SearchError::Generic(
+                "Failed to stop cache server".to_string(),
+            )),
         }
-        CacheRequest::Ping => CacheResponse::Ack(true),
-    };
+    }
 
-    let resp_bytes = bincode::serialize(&resp)
-        .map_err(|e| SearchError::Generic(format!("Failed to encode cache response: {}", e)))?;
-    stream.write_all(&resp_bytes)?;
-    let _ = stream.shutdown(Shutdown::Write);
-    Ok(())
-}
+    fn send_request(&self, req: CacheRequest) -> Result<CacheResponse> {
+        let mut stream = transport::connect(&self.cache_dir)?;
+        let bytes = bincode::serialize(&req)
+            .map_err(|e| SearchError::Generic(format!("Failed to encode cache request: {}", e)))?;
+        transport::write_frame(&mut stream, PROTOCOL_VERSION, &bytes)?;
 
-/// ---------- Helpers ----------
-fn cache_port_path(cache_dir: &Path) -> PathBuf {
-    cache_dir.join(PORT_FILE)
+        let buf = transport::read_frame(&mut stream, MAX_RESPONSE_SIZE)?;
+        let resp: CacheResponse = bincode::deserialize(&buf)
+            .map_err(|e| SearchError::Generic(format!("Failed to decode cache response: {}", e)))?;
+        Ok(resp)
+    }
 }
 
-fn write_port_file(cache_dir: &Path, addr: &str) -> Result<()> {
-    fs::write(cache_port_path(cache_dir), addr)
-        .map_err(|e| SearchError::Generic(format!("Failed to write cache port: {}", e)))
+/// ---------- Helpers ----------
+/// Lock `mutex`, carrying on with the data if a panicking request poisoned it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
-fn read_port_file() -> Option<String> {
-    let path = cache_port_path(&LocalCache::cache_dir());
-    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+/// Per-user directory holding the result cache and the symbol index
+pub(crate) fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(CACHE_DIR_NAME)
 }
 
 fn spawn_server() -> Result<()> {
@@ -791,12 +1020,12 @@ mod tests {
             value: "test value".to_string(),
             file: file.path().to_path_buf(),
             line: 1,
+            branch: None,
+            locale: None,
         }];
 
-        cache
-            .set(file.path(), "query", false, mtime, size, &results)
-            .unwrap();
-        let cached = cache.get(file.path(), "query", false, mtime, size);
+        cache.set(file.path(), mtime, size, &results).unwrap();
+        let cached = cache.get(file.path(), mtime, size);
         assert!(cached.is_some());
         assert_eq!(cached.unwrap().len(), 1);
     }
@@ -817,11 +1046,11 @@ mod tests {
             value: "test value".to_string(),
             file: file.path().to_path_buf(),
             line: 1,
+            branch: None,
+            locale: None,
         }];
 
-        cache
-            .set(file.path(), "query", false, mtime, size, &results)
-            .unwrap();
+        cache.set(file.path(), mtime, size, &results).unwrap();
 
         std::thread::sleep(std::time::Duration::from_secs(1));
         fs::write(&file, "modified content with different size").unwrap();
@@ -832,33 +1061,128 @@ mod tests {
 
         assert!(new_size != size || new_mtime != mtime);
 
-        let cached = cache.get(file.path(), "query", false, new_mtime, new_size);
+        let cached = cache.get(file.path(), new_mtime, new_size);
         assert!(cached.is_none());
     }
 
     #[test]
-    fn test_case_insensitive_normalization_local() {
+    fn test_values_of_other_format_versions_are_misses_local() {
         let cache_dir = TempDir::new().unwrap();
-        let cache = SearchResultCache::with_cache_dir(cache_dir.path().to_path_buf()).unwrap();
+        let cache = LocalCache::with_cache_dir(cache_dir.path().to_path_buf()).unwrap();
         let file = NamedTempFile::new().unwrap();
-        fs::write(&file, "test content").unwrap();
+        let mtime = SystemTime::now();
+        let mtime_secs = mtime
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
 
-        let metadata = fs::metadata(file.path()).unwrap();
-        let mtime = metadata.modified().unwrap();
-        let size = metadata.len();
+        let value = CacheValue {
+            mtime_secs,
+            file_size: 1,
+            last_accessed: mtime_secs,
+            entries: Vec::new(),
+        };
+        let mut other_version = value.encode().unwrap();
+        other_version[4..8].copy_from_slice(&(CACHE_FORMAT_VERSION + 1).to_le_bytes());
+        let key = LocalCache::make_key(file.path());
+
+        for bytes in [other_version, bincode::serialize(&value).unwrap()] {
+            cache.db.insert(&key, bytes).unwrap();
+            assert!(cache.get(file.path(), mtime, 1).is_none());
+            // Dropped rather than read again
+            assert!(cache.db.get(&key).unwrap().is_none());
+        }
 
-        let results = vec![TranslationEntry {
-            key: "test.key".to_string(),
-            value: "test value".to_string(),
-            file: file.path().to_path_buf(),
-            line: 1,
-        }];
+        cache.db.insert(&key, value.encode().unwrap()).unwrap();
+        assert!(cache.get(file.path(), mtime, 1).is_some());
+    }
 
+    #[test]
+    fn test_stats_count_hits_and_misses_local() {
+        let cache_dir = TempDir::new().unwrap();
+        let cache = SearchResultCache::with_cache_dir(cache_dir.path().to_path_buf()).unwrap();
+        let file = NamedTempFile::new().unwrap();
+        let mtime = SystemTime::now();
+
+        assert!(cache.get(file.path(), mtime, 1).is_none());
+        cache.set(file.path(), mtime, 1, &[]).unwrap();
+        assert!(cache.get(file.path(), mtime, 1).is_some());
+        assert!(cache.get(file.path(), mtime, 1).is_some());
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert!(stats.size_on_disk > 0);
+        assert!(stats.oldest_access.is_some());
+
+        cache.clear().unwrap();
+        let stats = cache.stats().unwrap();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (0, 0, 0));
+        assert_eq!(stats.oldest_access, None);
+    }
+
+    #[test]
+    fn test_prune_removes_entries_not_used_recently_local() {
+        let cache_dir = TempDir::new().unwrap();
+        let cache = LocalCache::with_cache_dir(cache_dir.path().to_path_buf()).unwrap();
+        let recent = NamedTempFile::new().unwrap();
+        let old_file = NamedTempFile::new().unwrap();
+        let mtime = SystemTime::now();
+
+        cache.set(recent.path(), mtime, 1, &[]).unwrap();
+        // Last used ten days ago
+        let old = CacheValue {
+            mtime_secs: 0,
+            file_size: 1,
+            last_accessed: (SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60))
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            entries: Vec::new(),
+        };
         cache
-            .set(file.path(), "query", false, mtime, size, &results)
+            .db
+            .insert(LocalCache::make_key(old_file.path()), old.encode().unwrap())
             .unwrap();
 
-        let cached = cache.get(file.path(), "QUERY", false, mtime, size);
-        assert!(cached.is_some());
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+        assert_eq!(cache.prune(week).unwrap(), 1);
+        assert_eq!(cache.prune(week).unwrap(), 0);
+        assert_eq!(cache.stats().unwrap().entries, 1);
+        assert!(cache.get(recent.path(), mtime, 1).is_some());
+    }
+
+    #[test]
+    fn test_query_errors_keep_their_variant_over_the_wire() {
+        let candidates = vec![FunctionDef {
+            name: "total".to_string(),
+            scope: vec!["Report".to_string()],
+            file: PathBuf::from("app/report.rb"),
+            line: 2,
+            body: String::new(),
+        }];
+        let response =
+            CacheResponse::Trace(Err(QueryError::from(SearchError::AmbiguousFunction {
+                name: "total".to_string(),
+                candidates,
+            })));
+        let bytes = bincode::serialize(&response).unwrap();
+
+        let CacheResponse::Trace(Err(error)) = bincode::deserialize(&bytes).unwrap() else {
+            panic!("expected a trace error");
+        };
+        match SearchError::from(error) {
+            SearchError::AmbiguousFunction { name, candidates } => {
+                assert_eq!(name, "total");
+                assert_eq!(candidates[0].qualified_name(), "Report#total");
+            }
+            other => panic!("expected an ambiguity error, got {:?}", other),
+        }
+
+        let other = QueryError::from(SearchError::InvalidPath("x".to_string()));
+        assert!(matches!(
+            SearchError::from(other),
+            SearchError::Generic(message) if message == "Failed to parse file path: x"
+        ));
     }
 }
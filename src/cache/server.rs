@@ -0,0 +1,410 @@
+//! The background cache server (`cs --cache-server`).
+//!
+//! There is one server per user. It owns the result cache database and the
+//! query daemon's warm projects, answers requests on a small pool of worker
+//! threads, and exits when asked to (`cs cache stop`) or after it has been
+//! idle for a while.
+
+use super::daemon::WarmProjects;
+use super::transport::{self, Listener, Stream, MAX_REQUEST_SIZE, PROTOCOL_VERSION};
+use super::{lock, CacheRequest, CacheResponse, LocalCache, QueryError, ServerStatus};
+use crate::error::{Result, SearchError};
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Threads answering cache server connections
+const SERVER_WORKERS: usize = 4;
+/// Connections waiting for a worker before new ones are turned away
+const SERVER_QUEUE: usize = 32;
+const SERVER_IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the server waits for a request before exiting
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Overrides the idle timeout, in seconds
+const IDLE_TIMEOUT_VAR: &str = "CS_CACHE_SERVER_IDLE_TIMEOUT";
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+/// First and longest wait before accepting again after a failed accept
+const ACCEPT_RETRY_MIN: Duration = Duration::from_millis(10);
+const ACCEPT_RETRY_MAX: Duration = Duration::from_secs(1);
+
+/// Run the server for the default cache directory until it stops
+pub(super) fn run() -> Result<()> {
+    let cache_dir = LocalCache::cache_dir();
+    transport::create_private_dir(&cache_dir)?;
+
+    let Some(listener) = Listener::bind(&cache_dir)? else {
+        // Another server is already running
+        return Ok(());
+    };
+    let local = LocalCache::with_cache_dir(cache_dir)?;
+    Server::new(listener, local, idle_timeout()).serve();
+    Ok(())
+}
+
+fn idle_timeout() -> Duration {
+    std::env::var(IDLE_TIMEOUT_VAR)
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map_or(DEFAULT_IDLE_TIMEOUT, Duration::from_secs)
+}
+
+pub(super) struct Server {
+    listener: Listener,
+    local: LocalCache,
+    projects: WarmProjects,
+    started: SystemTime,
+    idle_timeout: Duration,
+    stopping: AtomicBool,
+    /// Connections being answered right now
+    busy: AtomicUsize,
+    /// Connections closed unanswered because the queue was full
+    turned_away: AtomicUsize,
+    last_active: Mutex<Instant>,
+}
+
+impl Server {
+    pub(super) fn new(listener: Listener, local: LocalCache, idle_timeout: Duration) -> Self {
+        Self {
+            listener,
+            local,
+            projects: WarmProjects::new(),
+            started: SystemTime::now(),
+            idle_timeout,
+            stopping: AtomicBool::new(false),
+            busy: AtomicUsize::new(0),
+            turned_away: AtomicUsize::new(0),
+            last_active: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Answer connections on a pool of [`SERVER_WORKERS`] threads until the
+    /// server is stopped.
+    ///
+    /// Connections wait in a bounded queue; when it is full new ones are
+    /// closed right away and their clients fall back to working in-process,
+    /// instead of piling up behind a slow request. They are counted in the
+    /// server status. A failing accept (e.g. out of file descriptors) is
+    /// retried with a growing pause rather than in a busy loop.
+    pub(super) fn serve(&self) {
+        let (sender, receiver) = mpsc::sync_channel::<Stream>(SERVER_QUEUE);
+        let receiver = Mutex::new(receiver);
+
+        std::thread::scope(|scope| {
+            for _ in 0..SERVER_WORKERS {
+                scope.spawn(|| loop {
+                    let next = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    // Queued connections are still answered after a stop
+                    let Ok(mut stream) = next else {
+                        break;
+                    };
+                    self.busy.fetch_add(1, Ordering::SeqCst);
+                    let _ = self.handle_connection(&mut stream);
+                    *lock(&self.last_active) = Instant::now();
+                    self.busy.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+            scope.spawn(|| self.stop_when_idle());
+
+            let mut retry = ACCEPT_RETRY_MIN;
+            while !self.stopping.load(Ordering::SeqCst) {
+                let stream = match self.listener.accept() {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        // The client gave up or a signal arrived: nothing to wait for
+                        if !matches!(
+                            e.kind(),
+                            ErrorKind::Interrupted | ErrorKind::ConnectionAborted
+                        ) {
+                            eprintln!("cs cache server: accept failed: {}", e);
+                            std::thread::sleep(retry);
+                            retry = (retry * 2).min(ACCEPT_RETRY_MAX);
+                        }
+                        continue;
+                    }
+                };
+                retry = ACCEPT_RETRY_MIN;
+                if self.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                *lock(&self.last_active) = Instant::now();
+                if sender.try_send(stream).is_err() {
+                    let turned_away = self.turned_away.fetch_add(1, Ordering::SeqCst) + 1;
+                    eprintln!(
+                        "cs cache server: all workers busy, closed a connection ({} so far)",
+                        turned_away
+                    );
+                }
+            }
+            drop(sender);
+        });
+    }
+
+    /// Stop accepting connections; `serve` returns once the queue is drained
+    pub(super) fn stop(&self) {
+        if !self.stopping.swap(true, Ordering::SeqCst) {
+            // Unblock the accept loop
+            self.listener.wake();
+        }
+    }
+
+    fn stop_when_idle(&self) {
+        while !self.stopping.load(Ordering::SeqCst) {
+            std::thread::sleep(IDLE_CHECK_INTERVAL);
+            let idle = lock(&self.last_active).elapsed();
+            if self.busy.load(Ordering::SeqCst) == 0 && idle >= self.idle_timeout {
+                self.stop();
+            }
+        }
+    }
+
+    fn status(&self) -> ServerStatus {
+        ServerStatus {
+            pid: std::process::id(),
+            address: self.listener.address(),
+            started: self.started,
+            idle_timeout: self.idle_timeout,
+            warm_projects: self.projects.len(),
+            turned_away: self.turned_away.load(Ordering::SeqCst),
+        }
+    }
+
+    fn handle_connection(&self, stream: &mut Stream) -> Result<()> {
+        // A client that stops talking only ties up its own worker, and not forever
+        stream.set_read_timeout(Some(SERVER_IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(SERVER_IO_TIMEOUT))?;
+        if !self.listener.authenticate(stream) {
+            return Ok(());
+        }
+
+        let buf = match transport::read_frame(stream, MAX_REQUEST_SIZE) {
+            Ok(buf) => buf,
+            Err(SearchError::ProtocolMismatch { .. }) => {
+                return transport::write_frame(stream, PROTOCOL_VERSION, &[]);
+            }
+            Err(e) => return Err(e),
+        };
+        let req: CacheRequest = bincode::deserialize(&buf)
+            .map_err(|e| SearchError::Generic(format!("Failed to decode cache request: {}", e)))?;
+
+        let resp = self.answer(req);
+        let resp_bytes = bincode::serialize(&resp)
+            .map_err(|e| SearchError::Generic(format!("Failed to encode cache response: {}", e)))?;
+        transport::write_frame(stream, PROTOCOL_VERSION, &resp_bytes)
+    }
+
+    fn answer(&self, req: CacheRequest) -> CacheResponse {
+        let local = &self.local;
+        match req {
+            CacheRequest::Get {
+                file,
+                mtime_secs,
+                file_size,
+            } => {
+                let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(mtime_secs);
+                let hit = local.get(&file, ts, file_size);
+                CacheResponse::Get(hit)
+            }
+            CacheRequest::Set {
+                file,
+                mtime_secs,
+                file_size,
+                entries,
+            } => {
+                let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(mtime_secs);
+                let res = local.set(&file, ts, file_size, &entries);
+                CacheResponse::Ack(res.is_ok())
+            }
+            CacheRequest::Clear => {
+                // Also closes the symbol index so the client can remove it
+                self.projects.clear();
+                let res = local.clear();
+                CacheResponse::Ack(res.is_ok())
+            }
+            CacheRequest::Ping => CacheResponse::Ack(true),
+            CacheRequest::Search {
+                query,
+                config_files,
+            } => CacheResponse::Search(
+                self.projects
+                    .search(query, &config_files)
+                    .map_err(QueryError::from),
+            ),
+            CacheRequest::Trace {
+                query,
+                config_files,
+            } => CacheResponse::Trace(
+                self.projects
+                    .trace(query, &config_files)
+                    .map(|tree| tree.map(|tree| (tree.root, tree.callers)))
+                    .map_err(QueryError::from),
+            ),
+            CacheRequest::Stats => CacheResponse::Stats(local.stats().map_err(|e| e.to_string())),
+            CacheRequest::Prune { older_than } => {
+                CacheResponse::Pruned(local.prune(older_than).map_err(|e| e.to_string()))
+            }
+            CacheRequest::Status => CacheResponse::Status(self.status()),
+            CacheRequest::Stop => {
+                self.stop();
+                CacheResponse::Ack(true)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::super::{RemoteCache, MAX_RESPONSE_SIZE};
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn frame(version: u32, payload: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::new();
+        transport::write_frame(&mut frame, version, payload).unwrap();
+        frame
+    }
+
+    fn server(dir: &Path, idle_timeout: Duration) -> Server {
+        let listener = Listener::bind(dir).unwrap().unwrap();
+        let local = LocalCache::with_cache_dir(dir.join("cache")).unwrap();
+        Server::new(listener, local, idle_timeout)
+    }
+
+    /// Run `handle_connection` on one end of a socket pair
+    fn exchange(request: &[u8]) -> (Result<()>, Vec<u8>) {
+        let dir = TempDir::new().unwrap();
+        let server = server(dir.path(), DEFAULT_IDLE_TIMEOUT);
+        let (mut client, mut stream) = UnixStream::pair().unwrap();
+
+        client.write_all(request).unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+        let handled = server.handle_connection(&mut stream);
+        drop(stream);
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+        (handled, response)
+    }
+
+    #[test]
+    fn test_server_answers_ping() {
+        let ping = bincode::serialize(&CacheRequest::Ping).unwrap();
+        let (handled, response) = exchange(&frame(PROTOCOL_VERSION, &ping));
+        handled.unwrap();
+
+        let payload = transport::read_frame(&mut response.as_slice(), MAX_RESPONSE_SIZE).unwrap();
+        let response: CacheResponse = bincode::deserialize(&payload).unwrap();
+        assert!(matches!(response, CacheResponse::Ack(true)));
+    }
+
+    #[test]
+    fn test_server_rejects_other_protocol_versions() {
+        let ping = bincode::serialize(&CacheRequest::Ping).unwrap();
+        for version in [PROTOCOL_VERSION - 1, PROTOCOL_VERSION + 1] {
+            let (handled, response) = exchange(&frame(version, &ping));
+            handled.unwrap();
+            // An empty frame carrying the server's own version
+            assert_eq!(response, frame(PROTOCOL_VERSION, &[]));
+        }
+    }
+
+    #[test]
+    fn test_server_rejects_oversized_requests() {
+        let mut request = PROTOCOL_VERSION.to_le_bytes().to_vec();
+        request.extend_from_slice(&(MAX_REQUEST_SIZE + 1).to_le_bytes());
+        let (handled, response) = exchange(&request);
+        assert!(handled.is_err());
+        assert!(response.is_empty());
+    }
+
+    #[test]
+    fn test_client_rejects_other_protocol_versions() {
+        let dir = TempDir::new().unwrap();
+        let listener = Listener::bind(dir.path()).unwrap().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut stream = listener.accept().unwrap();
+            let mut header = [0u8; 8];
+            stream.read_exact(&mut header).unwrap();
+            stream.write_all(&frame(PROTOCOL_VERSION + 1, &[])).unwrap();
+        });
+
+        let remote = RemoteCache {
+            cache_dir: dir.path().to_path_buf(),
+        };
+        match remote.ping() {
+            Err(SearchError::ProtocolMismatch { found, expected }) => {
+                assert_eq!(found, PROTOCOL_VERSION + 1);
+                assert_eq!(expected, PROTOCOL_VERSION);
+            }
+            other => panic!("expected a protocol mismatch, got {:?}", other),
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_server_handles_clients_concurrently() {
+        let dir = TempDir::new().unwrap();
+        let cache_dir = dir.path().to_path_buf();
+        let server = Arc::new(server(&cache_dir, DEFAULT_IDLE_TIMEOUT));
+        let running = Arc::clone(&server);
+        let serving = std::thread::spawn(move || running.serve());
+
+        // A second server sees the live one and steps back
+        assert!(Listener::bind(&cache_dir).unwrap().is_none());
+
+        // A client that never finishes its request holds one worker only
+        let mut stalled = transport::connect(&cache_dir).unwrap();
+        stalled.write_all(&PROTOCOL_VERSION.to_le_bytes()).unwrap();
+
+        let clients: Vec<_> = (0..8)
+            .map(|_| {
+                let cache_dir = cache_dir.clone();
+                std::thread::spawn(move || RemoteCache { cache_dir }.ping())
+            })
+            .collect();
+        for client in clients {
+            client.join().unwrap().unwrap();
+        }
+
+        server.stop();
+        serving.join().unwrap();
+    }
+
+    #[test]
+    fn test_stop_and_status_requests() {
+        let dir = TempDir::new().unwrap();
+        let cache_dir = dir.path().to_path_buf();
+        let server = Arc::new(server(&cache_dir, DEFAULT_IDLE_TIMEOUT));
+        let running = Arc::clone(&server);
+        let serving = std::thread::spawn(move || running.serve());
+
+        let remote = RemoteCache {
+            cache_dir: cache_dir.clone(),
+        };
+        let status = remote.status().unwrap();
+        assert_eq!(status.pid, std::process::id());
+        assert!(status.address.ends_with("cache.sock"));
+
+        remote.stop().unwrap();
+        serving.join().unwrap();
+        drop(server);
+        // The socket is gone with the server
+        assert!(transport::connect(&cache_dir).is_err());
+    }
+
+    #[test]
+    fn test_server_exits_when_idle() {
+        let dir = TempDir::new().unwrap();
+        let server = server(dir.path(), Duration::from_millis(300));
+        let started = Instant::now();
+        server.serve();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,501 @@
+//! Persistent, incrementally updated index of function definitions and call sites.
+//!
+//! Call tracing asks the same two questions over and over: "where is `foo`
+//! defined?" and "who calls `foo`?". Answering them by grepping the project
+//! and re-parsing every candidate file at every level of the call graph is
+//! what makes deep traces slow. The index answers both from memory after a
+//! single walk of the project.
+//!
+//! Entries are stored per file in a sled database under the cache directory,
+//! keyed by path and validated with the file's mtime and size (just like the
+//! search result cache), so only files that changed since the last run are
+//! parsed again.
+
+use crate::error::{Result, SearchError};
+use crate::parse::Sitter;
+use crate::trace::call_extractor::TOP_LEVEL;
+use crate::trace::{CallExtractor, CallerInfo, FunctionFinder};
+use hashbrown::{HashMap, HashSet};
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const SYMBOLS_DIR_NAME: &str = "symbols";
+/// Bump when the layout of `IndexedFile` or the extraction rules change
+const INDEX_FORMAT_VERSION: u32 = 6;
+const VERSION_KEY: &[u8] = b"\0format_version";
+
+/// Source file extensions worth indexing for definitions and call sites
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "vue", "svelte", "rb", "py", "rs", "cs", "go", "java",
+    "kt", "php", "swift", "c", "cc", "cpp", "h", "hpp", "scala",
+];
+
+/// Where a function is defined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolLocation {
+    pub file: PathBuf,
+    pub line: usize,
+    /// Enclosing classes, modules and namespaces, outermost first
+    pub scope: Vec<String>,
+}
+
+/// Counters describing what a refresh did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Source files currently in the index
+    pub files: usize,
+    /// Files that were (re)parsed because they were new or changed
+    pub reindexed: usize,
+    /// Files dropped because they no longer exist
+    pub removed: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct IndexedDefinition {
+    name: String,
+    line: usize,
+    scope: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct IndexedCall {
+    callee: String,
+    caller: String,
+    line: usize,
+}
+
+/// Index value stored for each source file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct IndexedFile {
+    mtime_secs: u64,
+    file_size: u64,
+    definitions: Vec<IndexedDefinition>,
+    calls: Vec<IndexedCall>,
+}
+
+/// Symbol index for one project root.
+///
+/// Open it with [`SymbolIndex::open`], bring it up to date with
+/// [`SymbolIndex::refresh`] and hand it to `FunctionFinder` / `CallExtractor`
+/// via their `with_symbol_index` builders.
+pub struct SymbolIndex {
+    root: PathBuf,
+    tree: sled::Tree,
+    definitions: HashMap<String, Vec<SymbolLocation>>,
+    call_sites: HashMap<String, Vec<CallerInfo>>,
+}
+
+impl SymbolIndex {
+    /// Open the index for `root` in the default cache directory.
+    ///
+    /// Fails if another `cs` process currently holds the index open.
+    pub fn open(root: &Path) -> Result<Self> {
+        Self::open_in(super::default_cache_dir(), root)
+    }
+
+    /// Open the index for `root` inside a specific cache directory
+    pub fn open_in(cache_dir: PathBuf, root: &Path) -> Result<Self> {
+        let db = Self::open_db(&cache_dir)?;
+        // One tree per project, named after the canonical root so `.` and the
+        // absolute spelling of the same directory share an index
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let tree = db
+            .open_tree(canonical_root.to_string_lossy().as_bytes())
+            .map_err(|e| SearchError::Generic(format!("Failed to open symbol index: {}", e)))?;
+
+        let version_matches = tree
+            .get(VERSION_KEY)
+            .ok()
+            .flatten()
+            .is_some_and(|v| v.as_ref() == INDEX_FORMAT_VERSION.to_le_bytes());
+        if !version_matches {
+            // Stale layout from an older release: start from scratch
+            tree.clear().map_err(|e| {
+                SearchError::Generic(format!("Failed to reset symbol index: {}", e))
+            })?;
+            tree.insert(VERSION_KEY, &INDEX_FORMAT_VERSION.to_le_bytes())
+                .map_err(|e| {
+                    SearchError::Generic(format!("Failed to write symbol index: {}", e))
+                })?;
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            tree,
+            definitions: HashMap::new(),
+            call_sites: HashMap::new(),
+        })
+    }
+
+    /// Remove every project's index from the default cache directory
+    pub fn clear_all() -> Result<()> {
+        let dir = super::default_cache_dir().join(SYMBOLS_DIR_NAME);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        Ok(())
+    }
+
+    fn open_db(cache_dir: &Path) -> Result<sled::Db> {
+        let dir = cache_dir.join(SYMBOLS_DIR_NAME);
+        fs::create_dir_all(&dir)?;
+        sled::open(&dir)
+            .map_err(|e| SearchError::Generic(format!("Failed to open symbol index: {}", e)))
+    }
+
+    /// Walk the project, re-parse new or changed files, drop deleted ones and
+    /// load the result into memory for lookups.
+    pub fn refresh(&mut self) -> Result<IndexStats> {
+        let mut stats = IndexStats::default();
+        let mut seen: HashSet<Vec<u8>> = HashSet::new();
+        let mut extractor = FileSymbolExtractor::new();
+
+        self.definitions.clear();
+        self.call_sites.clear();
+
+        let walker = WalkBuilder::new(&self.root)
+            .git_ignore(true)
+            .git_global(true)
+            .git_exclude(true)
+            .hidden(false)
+            .build();
+
+        for entry in walker.flatten() {
+            if entry.file_type().is_none_or(|ft| ft.is_dir()) {
+                continue;
+            }
+            let path = entry.path();
+            if !Self::is_source_file(path) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let mtime_secs = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+            let file_size = metadata.len();
+
+            // Keys are relative to the root; lookups report paths under the
+            // root exactly as the caller spelled it
+            let relative = path.strip_prefix(&self.root).unwrap_or(path);
+            let key = relative.to_string_lossy().as_bytes().to_vec();
+            let cached = self
+                .tree
+                .get(&key)
+                .ok()
+                .flatten()
+                .and_then(|bytes| bincode::deserialize::<IndexedFile>(&bytes).ok())
+                .filter(|f| f.mtime_secs == mtime_secs && f.file_size == file_size);
+
+            let indexed = match cached {
+                Some(indexed) => indexed,
+                None => {
+                    // Unreadable (e.g. non UTF-8) files are indexed as empty so
+                    // they are not retried until they change
+                    let content = fs::read_to_string(path).unwrap_or_default();
+                    let (definitions, calls) = extractor.extract(path, &content);
+                    let indexed = IndexedFile {
+                        mtime_secs,
+                        file_size,
+                        definitions,
+                        calls,
+                    };
+                    let bytes = bincode::serialize(&indexed).map_err(|e| {
+                        SearchError::Generic(format!("Failed to serialize symbol index: {}", e))
+                    })?;
+                    self.tree.insert(key.as_slice(), bytes).map_err(|e| {
+                        SearchError::Generic(format!("Failed to write symbol index: {}", e))
+                    })?;
+                    stats.reindexed += 1;
+                    indexed
+                }
+            };
+
+            self.load(path, indexed);
+            seen.insert(key);
+            stats.files += 1;
+        }
+
+        // Drop files that disappeared since the last refresh
+        for key in self.tree.iter().keys().flatten() {
+            if key.as_ref() != VERSION_KEY && !seen.contains(key.as_ref()) {
+                let _ = self.tree.remove(key);
+                stats.removed += 1;
+            }
+        }
+
+        let _ = self.tree.flush();
+        Ok(stats)
+    }
+
+    /// All known definitions of a function with exactly this name
+    pub fn definitions(&self, name: &str) -> &[SymbolLocation] {
+        self.definitions.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// All call sites of a function with exactly this name
+    pub fn call_sites(&self, name: &str) -> &[CallerInfo] {
+        self.call_sites.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    fn load(&mut self, path: &Path, indexed: IndexedFile) {
+        for def in indexed.definitions {
+            self.definitions
+                .entry(def.name)
+                .or_default()
+                .push(SymbolLocation {
+                    file: path.to_path_buf(),
+                    line: def.line,
+                    scope: def.scope,
+                });
+        }
+        for call in indexed.calls {
+            self.call_sites
+                .entry(call.callee)
+                .or_default()
+                .push(CallerInfo {
+                    caller_name: call.caller,
+                    file: path.to_path_buf(),
+                    line: call.line,
+                });
+        }
+    }
+
+    fn is_source_file(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+    }
+}
+
+/// Extracts definitions and call sites from a single file using the same
+/// rules as `FunctionFinder::find_definition` and `CallExtractor::find_callers`,
+/// so indexed and non-indexed traces agree.
+struct FileSymbolExtractor {
+    sitter: Sitter,
+    definition_patterns: Vec<Regex>,
+    containing_patterns: Vec<Regex>,
+    call_pattern: Regex,
+}
+
+impl FileSymbolExtractor {
+    fn new() -> Self {
+        Self {
+            sitter: Sitter::new(),
+            definition_patterns: FunctionFinder::default_patterns(),
+            containing_patterns: CallExtractor::containing_function_patterns(),
+            call_pattern: Regex::new(r"\b(\w+)\s*\(").unwrap(),
+        }
+    }
+
+    fn extract(
+        &mut self,
+        path: &Path,
+        content: &str,
+    ) -> (Vec<IndexedDefinition>, Vec<IndexedCall>) {
+        (self.definitions(path, content), self.calls(path, content))
+    }
+
+    fn definitions(&mut self, path: &Path, content: &str) -> Vec<IndexedDefinition> {
+        let mut definitions = Vec::new();
+
+        if self.sitter.is_supported(path) {
+            // Tree-sitter results are trusted even when empty, like FunctionFinder
+            if let Ok(functions) = self.sitter.find_functions(path, content) {
+                definitions.extend(functions.into_iter().map(|f| IndexedDefinition {
+                    name: f.name,
+                    line: f.start_line,
+                    scope: f.scope,
+                }));
+            }
+            return definitions;
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        for (idx, line) in lines.iter().enumerate() {
+            let mut names_on_line = HashSet::new();
+            for pattern in &self.definition_patterns {
+                if let Some(name) = pattern.captures(line).and_then(|c| c.get(1)) {
+                    if names_on_line.insert(name.as_str()) {
+                        definitions.push(IndexedDefinition {
+                            name: name.as_str().to_string(),
+                            line: idx + 1,
+                            scope: FunctionFinder::enclosing_scope(&lines, idx),
+                        });
+                    }
+                }
+            }
+        }
+
+        definitions
+    }
+
+    fn calls(&mut self, path: &Path, content: &str) -> Vec<IndexedCall> {
+        if self.sitter.is_supported(path) {
+            // Call expressions from the syntax tree, like CallExtractor
+            return self
+                .sitter
+                .find_calls(path, content)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|call| IndexedCall {
+                    callee: call.callee,
+                    caller: call.caller.unwrap_or_else(|| TOP_LEVEL.to_string()),
+                    line: call.line,
+                })
+                .collect();
+        }
+
+        // Line patterns for languages without a Tree-sitter grammar
+        let lines: Vec<&str> = content.lines().collect();
+        let mut calls = Vec::new();
+
+        for (idx, line) in lines.iter().enumerate() {
+            // Skip comment lines (JavaScript //, Ruby/Python #)
+            let trimmed = line.trim();
+            if trimmed.starts_with("//") || trimmed.starts_with('#') {
+                continue;
+            }
+            // Skip function definition lines
+            if CallExtractor::is_definition_line(trimmed) {
+                continue;
+            }
+
+            let mut callees_on_line = HashSet::new();
+            for cap in self.call_pattern.captures_iter(line) {
+                let callee = cap.get(1).map_or("", |m| m.as_str());
+                if !callees_on_line.insert(callee) {
+                    continue;
+                }
+                calls.push(IndexedCall {
+                    callee: callee.to_string(),
+                    caller: CallExtractor::containing_function_name(
+                        &self.containing_patterns,
+                        &lines,
+                        idx + 1,
+                    ),
+                    line: idx + 1,
+                });
+            }
+        }
+
+        calls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_index_definitions_and_call_sites() {
+        let cache_dir = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let file = write(
+            project.path(),
+            "app.js",
+            "function main() {\n  helper();\n}\n\nfunction helper() {\n  return 1;\n}\n",
+        );
+        write(project.path(), "notes.txt", "main() is not code\n");
+
+        let mut index =
+            SymbolIndex::open_in(cache_dir.path().to_path_buf(), project.path()).unwrap();
+        let stats = index.refresh().unwrap();
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.reindexed, 1);
+
+        assert_eq!(
+            index.definitions("helper"),
+            &[SymbolLocation {
+                file: file.clone(),
+                line: 5,
+                scope: vec![],
+            }]
+        );
+
+        let callers = index.call_sites("helper");
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].caller_name, "main");
+        assert_eq!(callers[0].line, 2);
+        // Definition lines are not call sites
+        assert!(index.call_sites("main").is_empty());
+    }
+
+    #[test]
+    fn test_call_sites_come_from_the_syntax_tree() {
+        let cache_dir = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        write(
+            project.path(),
+            "app.js",
+            "function main() {\n  helper(); // helper() again\n  const s = \"helper()\";\n  /* helper() */\n}\n\nfunction helper() {}\n",
+        );
+
+        let mut index =
+            SymbolIndex::open_in(cache_dir.path().to_path_buf(), project.path()).unwrap();
+        index.refresh().unwrap();
+        let indexed: Vec<_> = index
+            .call_sites("helper")
+            .iter()
+            .map(|c| (c.caller_name.clone(), c.line))
+            .collect();
+        assert_eq!(indexed, vec![("main".to_string(), 2)]);
+
+        // Scanning without the index finds the same call sites
+        let scanned: Vec<_> = CallExtractor::new(project.path().to_path_buf())
+            .find_callers("helper")
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.caller_name, c.line))
+            .collect();
+        assert_eq!(scanned, indexed);
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let cache_dir = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        write(project.path(), "a.py", "def alpha():\n    beta()\n");
+        let b = write(project.path(), "b.py", "def beta():\n    pass\n");
+
+        {
+            let mut index =
+                SymbolIndex::open_in(cache_dir.path().to_path_buf(), project.path()).unwrap();
+            assert_eq!(index.refresh().unwrap().reindexed, 2);
+        }
+
+        // Reopening reuses the stored entries
+        let mut index =
+            SymbolIndex::open_in(cache_dir.path().to_path_buf(), project.path()).unwrap();
+        let stats = index.refresh().unwrap();
+        assert_eq!(stats.reindexed, 0);
+        assert_eq!(index.definitions("beta").len(), 1);
+
+        // Changed files are re-parsed, deleted files are dropped
+        write(
+            project.path(),
+            "a.py",
+            "def alpha():\n    gamma()\n\ndef gamma():\n    pass\n",
+        );
+        fs::remove_file(b).unwrap();
+        let stats = index.refresh().unwrap();
+        assert_eq!(stats.reindexed, 1);
+        assert_eq!(stats.removed, 1);
+        assert!(index.definitions("beta").is_empty());
+        assert_eq!(index.call_sites("gamma")[0].caller_name, "alpha");
+    }
+}
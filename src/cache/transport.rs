@@ -0,0 +1,225 @@
+//! How cache server messages travel between processes.
+//!
+//! On Unix the server listens on a socket inside the per-user cache
+//! directory, which is kept `0700`, so only its owner can connect. Other
+//! platforms listen on loopback TCP and write the address together with a
+//! random token to the port file in that directory; clients must send the
+//! token before anything else.
+//!
+//! Every message is a frame: the protocol version and the payload length
+//! (both `u32`, little endian) followed by the bincode payload. A server
+//! answers a frame of another version with an empty frame carrying its own
+//! version, so mismatched releases detect each other without decoding
+//! payloads they don't understand.
+
+use crate::error::{Result, SearchError};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Bump whenever `CacheRequest` or `CacheResponse` change shape
+pub(super) const PROTOCOL_VERSION: u32 = 6;
+/// Largest request a server accepts; `Set` of a huge locale file stays well below
+pub(super) const MAX_REQUEST_SIZE: u32 = 16 * 1024 * 1024;
+/// Responses (whole search results) are only bounded by the frame format
+pub(super) const MAX_RESPONSE_SIZE: u32 = u32::MAX;
+
+pub(super) use imp::{connect, Listener, Stream};
+
+/// Create `dir` readable by its owner only
+pub(super) fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // Fails unless we own the directory, which is what we want
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+pub(super) fn write_frame(stream: &mut impl Write, version: u32, payload: &[u8]) -> Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| SearchError::Generic("Cache message too large".to_string()))?;
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&version.to_le_bytes());
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Read one frame of the current protocol version, at most `max_len` bytes
+pub(super) fn read_frame(stream: &mut impl Read, max_len: u32) -> Result<Vec<u8>> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    let version = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+    if len > max_len {
+        return Err(SearchError::Generic(format!(
+            "Cache message of {} bytes exceeds the {} byte limit",
+            len, max_len
+        )));
+    }
+
+    // Read the whole frame even if it can't be used: closing a socket with
+    // unread data resets the connection before the peer sees our answer
+    let mut payload = vec![0; len as usize];
+    stream.read_exact(&mut payload)?;
+    if version != PROTOCOL_VERSION {
+        return Err(SearchError::protocol_mismatch(version, PROTOCOL_VERSION));
+    }
+    Ok(payload)
+}
+
+#[cfg(unix)]
+mod imp {
+    use crate::error::{Result, SearchError};
+    use std::fs;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+
+    const SOCKET_FILE: &str = "cache.sock";
+
+    pub type Stream = UnixStream;
+
+    /// The server's socket, removed again when the server stops
+    pub struct Listener {
+        inner: UnixListener,
+        path: PathBuf,
+    }
+
+    impl Listener {
+        /// Listen in `dir`, or `None` if a live server already does
+        pub fn bind(dir: &Path) -> Result<Option<Self>> {
+            let path = dir.join(SOCKET_FILE);
+            if UnixStream::connect(&path).is_ok() {
+                return Ok(None);
+            }
+            // Left behind by a server that didn't shut down cleanly
+            let _ = fs::remove_file(&path);
+            let inner = UnixListener::bind(&path)
+                .map_err(|e| SearchError::Generic(format!("Failed to bind cache server: {}", e)))?;
+            Ok(Some(Self { inner, path }))
+        }
+
+        pub fn accept(&self) -> std::io::Result<Stream> {
+            self.inner.accept().map(|(stream, _)| stream)
+        }
+
+        /// Only our user can reach the socket
+        pub fn authenticate(&self, _stream: &mut Stream) -> bool {
+            true
+        }
+
+        /// Unblock a pending [`accept`](Self::accept)
+        pub fn wake(&self) {
+            let _ = UnixStream::connect(&self.path);
+        }
+
+        pub fn address(&self) -> String {
+            self.path.display().to_string()
+        }
+    }
+
+    impl Drop for Listener {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    pub fn connect(dir: &Path) -> Result<Stream> {
+        UnixStream::connect(dir.join(SOCKET_FILE))
+            .map_err(|e| SearchError::Generic(format!("Failed to connect cache server: {}", e)))
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use crate::error::{Result, SearchError};
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::path::Path;
+
+    const PORT_FILE: &str = "cache.port";
+    const TOKEN_LEN: usize = 32;
+
+    pub type Stream = TcpStream;
+
+    pub struct Listener {
+        inner: TcpListener,
+        token: [u8; TOKEN_LEN],
+    }
+
+    impl Listener {
+        /// Listen on loopback, or `None` if a live server already does
+        pub fn bind(dir: &Path) -> Result<Option<Self>> {
+            if connect(dir).is_ok() {
+                return Ok(None);
+            }
+
+            let mut token = [0u8; TOKEN_LEN];
+            getrandom::getrandom(&mut token).map_err(|e| {
+                SearchError::Generic(format!("Failed to generate cache server token: {}", e))
+            })?;
+            let inner = TcpListener::bind("127.0.0.1:0")
+                .map_err(|e| SearchError::Generic(format!("Failed to bind cache server: {}", e)))?;
+            let addr = inner.local_addr()?;
+            fs::write(dir.join(PORT_FILE), format!("{} {}", addr, hex(&token)))?;
+            Ok(Some(Self { inner, token }))
+        }
+
+        pub fn accept(&self) -> std::io::Result<Stream> {
+            self.inner.accept().map(|(stream, _)| stream)
+        }
+
+        /// Unblock a pending [`accept`](Self::accept)
+        pub fn wake(&self) {
+            if let Ok(addr) = self.inner.local_addr() {
+                let _ = TcpStream::connect(addr);
+            }
+        }
+
+        pub fn address(&self) -> String {
+            self.inner
+                .local_addr()
+                .map_or_else(|_| String::new(), |addr| addr.to_string())
+        }
+
+        /// Whether the client sent the token (read with the worker's timeout)
+        pub fn authenticate(&self, stream: &mut Stream) -> bool {
+            let mut token = [0u8; TOKEN_LEN];
+            stream.read_exact(&mut token).is_ok() && token == self.token
+        }
+    }
+
+    pub fn connect(dir: &Path) -> Result<Stream> {
+        let content = fs::read_to_string(dir.join(PORT_FILE))?;
+        let (addr, token) = content
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| SearchError::Generic("Invalid cache port file".to_string()))?;
+        let mut stream = TcpStream::connect(addr)
+            .map_err(|e| SearchError::Generic(format!("Failed to connect cache server: {}", e)))?;
+        stream.write_all(token_bytes(token)?.as_slice())?;
+        Ok(stream)
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn token_bytes(hex: &str) -> Result<Vec<u8>> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                    .ok_or_else(|| SearchError::Generic("Invalid cache port file".to_string()))
+            })
+            .collect()
+    }
+}
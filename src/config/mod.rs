@@ -1,5 +1,7 @@
 pub mod exclusions;
 pub mod patterns;
+pub mod project;
 
 pub use exclusions::{detect_project_type, get_default_exclusions, ProjectType};
 pub use patterns::default_patterns;
+pub use project::{ProjectConfig, CONFIG_FILE_NAME};
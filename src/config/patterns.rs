@@ -13,6 +13,21 @@ pub fn default_patterns() -> Vec<Regex> {
         // React Intl patterns
         Regex::new(r#"id:\s*['"]([^'"]+)['"]"#).unwrap(), // defineMessages
         Regex::new(r#"id=\s*['"]([^'"]+)['"]"#).unwrap(), // FormattedMessage props
+        // gettext patterns (Python, PHP, C): the msgid is the key. Msgids are
+        // sentences, so each quote type gets its own pattern to let the other
+        // one through (`_("Don't save")`)
+        Regex::new(r#"\b_\(\s*"([^"]+)""#).unwrap(), // _("...")
+        Regex::new(r#"\b_\(\s*'([^']+)'"#).unwrap(),
+        Regex::new(r#"\bn?gettext\(\s*"([^"]+)""#).unwrap(), // gettext, ngettext
+        Regex::new(r#"\bn?gettext\(\s*'([^']+)'"#).unwrap(),
+        Regex::new(r#"\bn?pgettext\(\s*(?:"[^"]*"|'[^']*')\s*,\s*"([^"]+)""#).unwrap(), // pgettext(ctx, msgid)
+        Regex::new(r#"\bn?pgettext\(\s*(?:"[^"]*"|'[^']*')\s*,\s*'([^']+)'"#).unwrap(),
+        // Android patterns: the resource name is the key
+        Regex::new(r#"\bR\.(?:string|plurals|array)\.(\w+)"#).unwrap(), // getString(R.string.key)
+        Regex::new(r#"@string/(\w+)"#).unwrap(),                        // XML layouts
+        // iOS patterns
+        Regex::new(r#"NSLocalizedString\(\s*@?"([^"]+)""#).unwrap(),
+        Regex::new(r#"String\(\s*localized:\s*"([^"]+)""#).unwrap(),
     ]
 }
 
@@ -24,8 +39,8 @@ mod tests {
     fn test_patterns_compile() {
         let patterns = default_patterns();
         assert!(!patterns.is_empty());
-        // Updated to reflect added React Intl patterns
-        assert_eq!(patterns.len(), 6);
+        // Updated to reflect added React Intl, gettext and mobile patterns
+        assert_eq!(patterns.len(), 16);
     }
 
     #[test]
@@ -58,6 +73,70 @@ mod tests {
         assert!(js_pattern.is_match(r#"i18n.t("invoice.labels.add_new")"#));
     }
 
+    #[test]
+    fn test_gettext_patterns() {
+        let patterns = default_patterns();
+        let key = |code: &str| {
+            patterns
+                .iter()
+                .find_map(|p| p.captures(code))
+                .map(|c| c[1].to_string())
+        };
+
+        assert_eq!(key(r#"_("Save changes")"#).as_deref(), Some("Save changes"));
+        assert_eq!(key(r#"gettext('Welcome')"#).as_deref(), Some("Welcome"));
+        assert_eq!(
+            key(r#"ngettext("%d file", "%d files", n)"#).as_deref(),
+            Some("%d file")
+        );
+        assert_eq!(key(r#"pgettext("menu", "Open")"#).as_deref(), Some("Open"));
+        assert_eq!(key(r#"pgettext('menu', "Open")"#).as_deref(), Some("Open"));
+        assert_eq!(key(r#"my_gettext_helper("x")"#), None);
+
+        // An apostrophe inside double quotes, or a quote inside single ones
+        assert_eq!(key(r#"_("Don't save")"#).as_deref(), Some("Don't save"));
+        assert_eq!(
+            key(r#"gettext('Say "hi"')"#).as_deref(),
+            Some(r#"Say "hi""#)
+        );
+    }
+
+    #[test]
+    fn test_mobile_patterns() {
+        let patterns = default_patterns();
+        let key = |code: &str| {
+            patterns
+                .iter()
+                .find_map(|p| p.captures(code))
+                .map(|c| c[1].to_string())
+        };
+
+        assert_eq!(
+            key("binding.title.text = getString(R.string.add_new)").as_deref(),
+            Some("add_new")
+        );
+        assert_eq!(
+            key("resources.getQuantityString(R.plurals.files, n, n)").as_deref(),
+            Some("files")
+        );
+        assert_eq!(
+            key(r#"android:text="@string/add_new""#).as_deref(),
+            Some("add_new")
+        );
+        assert_eq!(
+            key(r#"NSLocalizedString("add_new", comment: "")"#).as_deref(),
+            Some("add_new")
+        );
+        assert_eq!(
+            key(r#"[button setTitle:NSLocalizedString(@"add_new", nil)]"#).as_deref(),
+            Some("add_new")
+        );
+        assert_eq!(
+            key(r#"Text(String(localized: "add_new"))"#).as_deref(),
+            Some("add_new")
+        );
+    }
+
     #[test]
     fn test_vue_pattern() {
         let patterns = default_patterns();
@@ -0,0 +1,381 @@
+//! User-configurable settings loaded from `.cs.toml` files.
+//!
+//! Two layers are merged on top of the built-in defaults:
+//!
+//! 1. the user-level file `<config dir>/cs/config.toml`
+//! 2. the nearest `.cs.toml` found by walking up from the search path
+//!
+//! ```toml
+//! [patterns]
+//! # i18n call patterns; the first capture group must be the translation key
+//! add = ['tr\("([^"]+)"\)']
+//! replace = false          # true: drop the built-in patterns
+//!
+//! [exclusions]
+//! add = ["generated", "storybook-static"]
+//!
+//! [locales]
+//! directories = ["strings"] # extra directory names holding locale files
+//!
+//! [languages]
+//! mjs = "javascript"        # parse *.mjs files with the JavaScript grammar
+//!
+//! [search]
+//! include_extensions = ["html.ui"]
+//! ```
+
+use crate::config::exclusions::{get_default_exclusions, ProjectType};
+use crate::config::patterns::default_patterns;
+use crate::error::{Result, SearchError};
+use crate::parse::sitter::SupportedLanguage;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+/// Name of the per-project configuration file
+pub const CONFIG_FILE_NAME: &str = ".cs.toml";
+
+/// Overrides the directory holding the user-level `config.toml`
+pub const CONFIG_DIR_ENV: &str = "CS_CONFIG_DIR";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    #[serde(default)]
+    patterns: RawList,
+    #[serde(default)]
+    exclusions: RawList,
+    #[serde(default)]
+    locales: RawLocales,
+    #[serde(default)]
+    languages: HashMap<String, Spanned<String>>,
+    #[serde(default)]
+    search: RawSearch,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawList {
+    #[serde(default)]
+    add: Vec<Spanned<String>>,
+    #[serde(default)]
+    replace: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawLocales {
+    #[serde(default)]
+    directories: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSearch {
+    #[serde(default)]
+    include_extensions: Vec<String>,
+}
+
+/// One parsed and validated configuration file
+#[derive(Debug, Clone)]
+struct ConfigLayer {
+    patterns: Vec<Regex>,
+    replace_patterns: bool,
+    exclusions: Vec<String>,
+    replace_exclusions: bool,
+    locale_dirs: Vec<String>,
+    languages: HashMap<String, SupportedLanguage>,
+    include_extensions: Vec<String>,
+}
+
+/// Effective configuration: built-in defaults plus every loaded layer.
+///
+/// `ProjectConfig::default()` has no layers and behaves exactly like the
+/// hard-coded defaults, so callers that don't load a config file are
+/// unaffected.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectConfig {
+    layers: Vec<ConfigLayer>,
+    files: Vec<PathBuf>,
+}
+
+impl ProjectConfig {
+    /// Load the user-level config and the nearest `.cs.toml` above `start`
+    pub fn discover(start: &Path) -> Result<Self> {
+        let mut files = Vec::new();
+        if let Some(user) = user_config_path().filter(|p| p.is_file()) {
+            files.push(user);
+        }
+        if let Some(project) = find_project_config(start) {
+            files.push(project);
+        }
+
+        let mut config = Self::default();
+        for file in files {
+            config.push_file(&file)?;
+        }
+        Ok(config)
+    }
+
+    /// Load a single configuration file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Self::from_files(&[path.to_path_buf()])
+    }
+
+    /// Load several configuration files, lowest precedence first (the order
+    /// returned by [`files`](Self::files))
+    pub fn from_files(paths: &[PathBuf]) -> Result<Self> {
+        let mut config = Self::default();
+        for path in paths {
+            config.push_file(path)?;
+        }
+        Ok(config)
+    }
+
+    /// Parse configuration from a string; `path` is only used in errors
+    pub fn parse(content: &str, path: &Path) -> Result<Self> {
+        let mut config = Self::default();
+        config.layers.push(parse_layer(content, path)?);
+        config.files.push(path.to_path_buf());
+        Ok(config)
+    }
+
+    fn push_file(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)?;
+        self.layers.push(parse_layer(&content, path)?);
+        self.files.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Configuration files that were loaded, lowest precedence first
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// i18n patterns used to find translation keys in code
+    pub fn patterns(&self) -> Vec<Regex> {
+        let mut patterns = default_patterns();
+        for layer in &self.layers {
+            if layer.replace_patterns {
+                patterns.clear();
+            }
+            patterns.extend(layer.patterns.iter().cloned());
+        }
+        patterns
+    }
+
+    /// Exclusions for a project of the given type
+    pub fn exclusions(&self, project_type: ProjectType) -> Vec<String> {
+        let mut exclusions: Vec<String> = get_default_exclusions(project_type)
+            .iter()
+            .map(|&s| s.to_string())
+            .collect();
+        for layer in &self.layers {
+            if layer.replace_exclusions {
+                exclusions.clear();
+            }
+            exclusions.extend(layer.exclusions.iter().cloned());
+        }
+        exclusions
+    }
+
+    /// Extra directory names holding locale files
+    pub fn locale_dirs(&self) -> Vec<String> {
+        self.layers
+            .iter()
+            .flat_map(|l| l.locale_dirs.iter().cloned())
+            .collect()
+    }
+
+    /// File extension to Tree-sitter language mappings; later layers win
+    pub fn languages(&self) -> HashMap<String, SupportedLanguage> {
+        self.layers
+            .iter()
+            .flat_map(|l| l.languages.iter().map(|(ext, lang)| (ext.clone(), *lang)))
+            .collect()
+    }
+
+    /// Additional file extensions to include in code reference search
+    pub fn include_extensions(&self) -> Vec<String> {
+        self.layers
+            .iter()
+            .flat_map(|l| l.include_extensions.iter().cloned())
+            .collect()
+    }
+}
+
+fn parse_layer(content: &str, path: &Path) -> Result<ConfigLayer> {
+    let raw: RawConfig = toml::from_str(content).map_err(|e| {
+        let line = e.span().map_or(0, |span| line_of(content, span.start));
+        SearchError::config_error(path, line, e.message())
+    })?;
+
+    let invalid = |span: std::ops::Range<usize>, reason: String| {
+        SearchError::config_error(path, line_of(content, span.start), reason)
+    };
+
+    let mut patterns = Vec::new();
+    for pattern in &raw.patterns.add {
+        let regex = Regex::new(pattern.get_ref())
+            .map_err(|e| invalid(pattern.span(), format!("invalid pattern: {}", e)))?;
+        if regex.captures_len() < 2 {
+            return Err(invalid(
+                pattern.span(),
+                format!(
+                    "pattern '{}' needs a capture group for the translation key",
+                    pattern.get_ref()
+                ),
+            ));
+        }
+        patterns.push(regex);
+    }
+
+    let mut languages = HashMap::new();
+    for (ext, name) in &raw.languages {
+        let lang = SupportedLanguage::from_name(name.get_ref()).ok_or_else(|| {
+            invalid(
+                name.span(),
+                format!(
+                    "unknown language '{}' for extension '{}' (expected one of: {})",
+                    name.get_ref(),
+                    ext,
+                    SupportedLanguage::NAMES.join(", ")
+                ),
+            )
+        })?;
+        languages.insert(ext.trim_start_matches('.').to_string(), lang);
+    }
+
+    Ok(ConfigLayer {
+        patterns,
+        replace_patterns: raw.patterns.replace,
+        exclusions: raw
+            .exclusions
+            .add
+            .into_iter()
+            .map(Spanned::into_inner)
+            .collect(),
+        replace_exclusions: raw.exclusions.replace,
+        locale_dirs: raw.locales.directories,
+        languages,
+        include_extensions: raw.search.include_extensions,
+    })
+}
+
+/// 1-indexed line containing byte `offset`
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os(CONFIG_DIR_ENV) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::config_dir()?.join("cs"),
+    };
+    Some(dir.join("config.toml"))
+}
+
+/// Nearest `.cs.toml` in `start` or one of its ancestors
+fn find_project_config(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    let dir = if start.is_file() {
+        start.parent()?.to_path_buf()
+    } else {
+        start
+    };
+    dir.ancestors()
+        .map(|d| d.join(CONFIG_FILE_NAME))
+        .find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn parse(content: &str) -> Result<ProjectConfig> {
+        ProjectConfig::parse(content, Path::new(".cs.toml"))
+    }
+
+    #[test]
+    fn test_default_matches_builtins() {
+        let config = ProjectConfig::default();
+        assert_eq!(config.patterns().len(), default_patterns().len());
+        assert_eq!(
+            config.exclusions(ProjectType::Node),
+            get_default_exclusions(ProjectType::Node)
+        );
+        assert!(config.languages().is_empty());
+    }
+
+    #[test]
+    fn test_add_and_replace() {
+        let config = parse(
+            r#"
+[patterns]
+add = ['tr\("([^"]+)"\)']
+
+[exclusions]
+replace = true
+add = ["generated"]
+
+[languages]
+".mjs" = "javascript"
+"#,
+        )
+        .unwrap();
+
+        let patterns = config.patterns();
+        assert_eq!(patterns.len(), default_patterns().len() + 1);
+        assert!(patterns.last().unwrap().is_match(r#"tr("home.title")"#));
+        assert_eq!(config.exclusions(ProjectType::Rust), vec!["generated"]);
+        assert_eq!(
+            config.languages().get("mjs"),
+            Some(&SupportedLanguage::JavaScript)
+        );
+    }
+
+    #[test]
+    fn test_errors_point_at_offending_line() {
+        let err = parse("[patterns]\nadd = [\n  'ok(\"(.+)\")',\n  'broken(',\n]\n").unwrap_err();
+        match err {
+            SearchError::ConfigError { line, reason, .. } => {
+                assert_eq!(line, 4);
+                assert!(reason.contains("invalid pattern"));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        let err = parse("[languages]\nmjs = \"cobol\"\n").unwrap_err();
+        assert!(matches!(err, SearchError::ConfigError { line: 2, .. }));
+
+        let err = parse("[exclusions]\nadd = [\"a\"]\nunknown = 1\n").unwrap_err();
+        assert!(matches!(err, SearchError::ConfigError { line: 3, .. }));
+    }
+
+    #[test]
+    fn test_pattern_without_capture_group() {
+        let err = parse("[patterns]\nadd = ['tr\\(']\n").unwrap_err();
+        assert!(err.to_string().contains("capture group"));
+    }
+
+    #[test]
+    fn test_discovers_nearest_config() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("app/views");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            "[locales]\ndirectories = [\"strings\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_project_config(&nested),
+            Some(dir.path().canonicalize().unwrap().join(CONFIG_FILE_NAME))
+        );
+    }
+}
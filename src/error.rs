@@ -84,6 +84,30 @@ pub enum SearchError {
     )]
     JsonParseError { file: PathBuf, reason: String },
 
+    /// Failed to parse gettext catalog
+    #[error(
+        "Failed to parse gettext file {file}:\n{reason}\n\nTip: Verify the .po syntax is correct (e.g. with msgfmt --check)"
+    )]
+    PoParseError { file: PathBuf, reason: String },
+
+    /// Failed to parse Fluent translation file
+    #[error(
+        "Failed to parse Fluent file {file}:\n{reason}\n\nTip: Verify the .ftl syntax is correct"
+    )]
+    FluentParseError { file: PathBuf, reason: String },
+
+    /// Failed to parse Android string resources
+    #[error(
+        "Failed to parse Android string resources {file}:\n{reason}\n\nTip: Verify the resource file is well-formed XML"
+    )]
+    AndroidStringsParseError { file: PathBuf, reason: String },
+
+    /// Failed to parse Apple strings file or string catalog
+    #[error(
+        "Failed to parse Apple strings file {file}:\n{reason}\n\nTip: Verify the file syntax is correct (e.g. with plutil -lint)"
+    )]
+    AppleStringsParseError { file: PathBuf, reason: String },
+
     /// Translation key found but no code references detected
     #[error("Translation key '{key}' found in {file} but no code references detected.\n\nTip: Check if the key is actually used in the codebase")]
     NoCodeReferences { key: String, file: PathBuf },
@@ -129,10 +153,37 @@ pub enum SearchError {
     #[error("ripgrep output is not valid UTF-8: {0}")]
     InvalidUtf8(#[from] std::string::FromUtf8Error),
 
+    /// Invalid `.cs.toml` configuration
+    #[error("Invalid config file {file}:{line}: {reason}")]
+    ConfigError {
+        file: PathBuf,
+        line: usize,
+        reason: String,
+    },
+
     /// Failed to parse file path
     #[error("Failed to parse file path: {0}")]
     InvalidPath(String),
 
+    /// The language server could not talk to its client
+    #[error("Language server error: {reason}")]
+    LspError { reason: String },
+
+    /// The cache server runs another release of the protocol
+    #[error("Cache server speaks protocol version {found}, expected {expected}")]
+    ProtocolMismatch { found: u32, expected: u32 },
+
+    /// Several functions match a traced name and none was picked
+    #[error("'{name}' matches {} functions; qualify the name (e.g. {}) to pick one", candidates.len(), candidates.first().map(|c| c.qualified_name()).unwrap_or_default())]
+    AmbiguousFunction {
+        name: String,
+        candidates: Vec<crate::trace::FunctionDef>,
+    },
+
+    /// The search was stopped through its cancellation token
+    #[error("Search cancelled")]
+    Cancelled,
+
     /// Generic search error with context
     #[error("{0}")]
     Generic(String),
@@ -224,6 +275,62 @@ impl SearchError {
         }
     }
 
+    /// Create a PoParseError from a file path and error
+    pub fn po_parse_error(file: impl Into<PathBuf>, reason: impl Into<String>) -> Self {
+        Self::PoParseError {
+            file: file.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a FluentParseError from a file path and error
+    pub fn fluent_parse_error(file: impl Into<PathBuf>, reason: impl Into<String>) -> Self {
+        Self::FluentParseError {
+            file: file.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an AndroidStringsParseError from a file path and error
+    pub fn android_strings_parse_error(
+        file: impl Into<PathBuf>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self::AndroidStringsParseError {
+            file: file.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an AppleStringsParseError from a file path and error
+    pub fn apple_strings_parse_error(file: impl Into<PathBuf>, reason: impl Into<String>) -> Self {
+        Self::AppleStringsParseError {
+            file: file.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a ConfigError pointing at a line of a configuration file
+    pub fn config_error(file: impl Into<PathBuf>, line: usize, reason: impl Into<String>) -> Self {
+        Self::ConfigError {
+            file: file.into(),
+            line,
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an LspError from a protocol, transport or (de)serialization failure
+    pub fn lsp_error(reason: impl std::fmt::Display) -> Self {
+        Self::LspError {
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Create a ProtocolMismatch error for a message of version `found`
+    pub fn protocol_mismatch(found: u32, expected: u32) -> Self {
+        Self::ProtocolMismatch { found, expected }
+    }
+
     /// Create a NoCodeReferences error
     pub fn no_code_references(key: impl Into<String>, file: impl Into<PathBuf>) -> Self {
         Self::NoCodeReferences {
@@ -1,35 +1,55 @@
+pub mod audit;
 pub mod cache;
 pub mod config;
 pub mod error;
+pub mod lsp;
 pub mod output;
 pub mod parse;
 pub mod search;
 pub mod trace;
 pub mod tree;
 
+use cache::daemon::WarmProject;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 // Re-export commonly used types
-pub use cache::SearchResultCache;
-pub use config::default_patterns;
+pub use cache::{CacheStats, QueryDaemon, SearchResultCache, ServerStatus, SymbolIndex};
+pub use config::{default_patterns, ProjectConfig};
 pub use error::{Result, SearchError};
-pub use output::TreeFormatter;
+pub use output::{
+    CallGraph, Clustering, GraphFormat, GraphFormatter, JsonFormatter, TreeFormatter,
+};
 pub use parse::{KeyExtractor, TranslationEntry, YamlParser};
-pub use search::{CodeReference, FileMatch, FileSearcher, Match, PatternMatcher, TextSearcher};
+pub use search::{
+    CancellationToken, CodeReference, Confidence, DynamicKeyFinder, FileMatch, FileSearcher,
+    KeyMatcher, Match, PatternMatcher, ProjectScanner, SearchEvent, SearchEvents, SearchSession,
+    TextSearcher,
+};
 pub use trace::{
-    CallExtractor, CallGraphBuilder, CallNode, CallTree, FunctionDef, FunctionFinder,
-    TraceDirection,
+    CallExtractor, CallGraphBuilder, CallNode, CallPaths, CallTree, FunctionDef, FunctionFinder,
+    QualifiedName, TraceDirection,
 };
 pub use tree::{Location, NodeType, ReferenceTree, ReferenceTreeBuilder, TreeNode};
 
 /// Query parameters for tracing
-#[derive(Debug, Clone)]
+///
+/// Queries are sent to the query daemon as they are, except `config`, which
+/// the daemon loads again from [`ProjectConfig::files`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraceQuery {
+    /// Function to trace, optionally qualified (see [`QualifiedName`])
     pub function_name: String,
     pub direction: TraceDirection,
     pub max_depth: usize,
     pub base_dir: Option<PathBuf>,
     pub exclude_patterns: Vec<String>,
+    pub use_symbol_index: bool, // Consult the persistent symbol index (default: true)
+    /// Trace the definition at this file and line when the name is ambiguous
+    pub definition: Option<(PathBuf, usize)>,
+    #[serde(skip)]
+    pub config: ProjectConfig,
 }
 
 impl TraceQuery {
@@ -40,6 +60,9 @@ impl TraceQuery {
             max_depth,
             base_dir: None,
             exclude_patterns: Vec::new(),
+            use_symbol_index: true,
+            definition: None,
+            config: ProjectConfig::default(),
         }
     }
 
@@ -52,10 +75,95 @@ impl TraceQuery {
         self.exclude_patterns = exclusions;
         self
     }
+
+    pub fn with_symbol_index(mut self, use_symbol_index: bool) -> Self {
+        self.use_symbol_index = use_symbol_index;
+        self
+    }
+
+    pub fn with_definition(mut self, file: PathBuf, line: usize) -> Self {
+        self.definition = Some((file, line));
+        self
+    }
+
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = config;
+        self
+    }
 }
 
-/// Query parameters for searching
+/// Query parameters for finding call paths between two functions
 #[derive(Debug, Clone)]
+pub struct PathQuery {
+    /// Function the paths start from, optionally qualified
+    pub from: String,
+    /// Function the paths lead to, optionally qualified; every definition
+    /// matching it is a target
+    pub to: String,
+    /// Maximum number of calls in a path
+    pub max_depth: usize,
+    /// Maximum number of functions visited, counting each time a function is
+    /// reached through another chain
+    pub max_nodes: usize,
+    /// Maximum number of paths returned
+    pub max_paths: usize,
+    pub base_dir: Option<PathBuf>,
+    pub use_symbol_index: bool,
+    /// Start from the definition at this file and line when `from` is ambiguous
+    pub definition: Option<(PathBuf, usize)>,
+    pub config: ProjectConfig,
+}
+
+impl PathQuery {
+    pub fn new(from: String, to: String, max_depth: usize) -> Self {
+        Self {
+            from,
+            to,
+            max_depth,
+            max_nodes: 10_000,
+            max_paths: 100,
+            base_dir: None,
+            use_symbol_index: true,
+            definition: None,
+            config: ProjectConfig::default(),
+        }
+    }
+
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    pub fn with_max_paths(mut self, max_paths: usize) -> Self {
+        self.max_paths = max_paths;
+        self
+    }
+
+    pub fn with_base_dir(mut self, base_dir: PathBuf) -> Self {
+        self.base_dir = Some(base_dir);
+        self
+    }
+
+    pub fn with_symbol_index(mut self, use_symbol_index: bool) -> Self {
+        self.use_symbol_index = use_symbol_index;
+        self
+    }
+
+    pub fn with_definition(mut self, file: PathBuf, line: usize) -> Self {
+        self.definition = Some((file, line));
+        self
+    }
+
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = config;
+        self
+    }
+}
+
+/// Query parameters for searching
+///
+/// Like [`TraceQuery`], everything but `config` is sent to the query daemon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchQuery {
     pub text: String,
     pub case_sensitive: bool,
@@ -66,6 +174,12 @@ pub struct SearchQuery {
     pub include_patterns: Vec<String>,
     pub verbose: bool,
     pub quiet: bool, // Suppress progress indicators (for --simple mode)
+    #[serde(skip)]
+    pub config: ProjectConfig,
+    /// Lines of context captured before each code reference (default: 2)
+    pub before_context: usize,
+    /// Lines of context captured after each code reference (default: 2)
+    pub after_context: usize,
 }
 
 impl SearchQuery {
@@ -80,6 +194,9 @@ impl SearchQuery {
             include_patterns: Vec::new(),
             verbose: false,
             quiet: false,
+            config: ProjectConfig::default(),
+            before_context: 2,
+            after_context: 2,
         }
     }
 
@@ -122,10 +239,25 @@ impl SearchQuery {
         self.quiet = quiet;
         self
     }
+
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_before_context(mut self, lines: usize) -> Self {
+        self.before_context = lines;
+        self
+    }
+
+    pub fn with_after_context(mut self, lines: usize) -> Self {
+        self.after_context = lines;
+        self
+    }
 }
 
 /// Result of a search operation
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     pub query: String,
     pub translation_entries: Vec<TranslationEntry>,
@@ -140,6 +272,9 @@ pub struct SearchResult {
 /// 3. Finds code references for each translation key
 /// 4. Returns a SearchResult with all findings
 ///
+/// [`SearchSession`] runs the same search but hands out each result as soon
+/// as it is found, and can be cancelled.
+///
 /// # Rust Book Reference
 ///
 /// **Chapter 9.2: Recoverable Errors with Result**
@@ -164,133 +299,46 @@ pub struct SearchResult {
 /// - `#[must_use]` makes error handling explicit and intentional
 #[must_use = "this function returns a Result that should be handled"]
 pub fn run_search(query: SearchQuery) -> Result<SearchResult> {
-    // Determine the base directory to search
-    let raw_base_dir = query
-        .base_dir
-        .clone()
-        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    run_search_with_file_matches(query).map(|(result, _)| result)
+}
 
-    // Handle case where base_dir is a file vs directory
-    let (search_dir, specific_file) = if raw_base_dir.is_file() {
-        // If it's a file, search in its parent directory but only that specific file
-        let parent_dir = raw_base_dir
-            .parent()
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| PathBuf::from("."));
-        (parent_dir, Some(raw_base_dir.clone()))
-    } else {
-        // If it's a directory, search the whole directory
-        (raw_base_dir.clone(), None)
-    };
+/// Like [`run_search`], but also returns the files whose names contain the
+/// query text.
+///
+/// Translation parsing, key usage, direct text and file name matching all
+/// come from a single walk of the project (see [`ProjectScanner`]), so this
+/// is cheaper than following `run_search` with a separate [`FileSearcher`].
+#[must_use = "this function returns a Result that should be handled"]
+pub fn run_search_with_file_matches(query: SearchQuery) -> Result<(SearchResult, Vec<FileMatch>)> {
+    search_project(query, None)
+}
 
-    // Use the search directory for project type detection
-    let project_type = config::detect_project_type(&search_dir);
-    let mut exclusions: Vec<String> = config::get_default_exclusions(project_type)
-        .iter()
-        .map(|&s| s.to_string())
-        .collect();
-    exclusions.extend(query.exclude_patterns.clone());
-
-    // Step 1: Extract translation entries matching the search text
-    // Only search for translation entries if we're not searching a specific file
-    let translation_entries = if specific_file.is_none() {
-        let mut extractor = KeyExtractor::new();
-        extractor.set_exclusions(exclusions.clone());
-        extractor.set_verbose(query.verbose);
-        extractor.set_quiet(query.quiet);
-        extractor.set_case_sensitive(query.case_sensitive);
-        extractor.extract(&search_dir, &query.text)?
-    } else {
-        Vec::new() // Skip translation search for specific files
+/// The search behind [`run_search_with_file_matches`]. The query daemon
+/// passes the `warm` state it keeps for the project, so the file list and
+/// parsed translation files come from memory instead of the disk.
+pub(crate) fn search_project(
+    query: SearchQuery,
+    warm: Option<&WarmProject>,
+) -> Result<(SearchResult, Vec<FileMatch>)> {
+    let mut result = SearchResult {
+        query: query.text.clone(),
+        translation_entries: Vec::new(),
+        code_references: Vec::new(),
     };
-
-    // Step 2: Find code references for each translation entry
-    // Search for full key AND partial keys (for namespace caching patterns)
-    let mut all_code_refs = Vec::new();
-
-    if specific_file.is_none() {
-        let mut matcher = PatternMatcher::new(search_dir.clone());
-        matcher.set_exclusions(exclusions.clone());
-
-        for entry in &translation_entries {
-            // Generate all key variations (full key + partial keys)
-            let key_variations = generate_partial_keys(&entry.key);
-
-            // Search for each key variation
-            for key in &key_variations {
-                let code_refs = matcher.find_usages(key)?;
-                all_code_refs.extend(code_refs);
-            }
-        }
-    }
-
-    // Step 3: Perform direct text search for the query text
-    // This ensures we find hardcoded text even if no translation keys are found
-    let text_searcher = TextSearcher::new(search_dir.clone())
-        .case_sensitive(query.case_sensitive)
-        .word_match(query.word_match)
-        .is_regex(query.is_regex)
-        .add_globs(query.include_patterns.clone())
-        .add_exclusions(exclusions.clone())
-        .respect_gitignore(true); // Always respect gitignore for now
-
-    if let Ok(direct_matches) = text_searcher.search(&query.text) {
-        for m in direct_matches {
-            // If searching a specific file, only include matches from that file
-            if let Some(ref target_file) = specific_file {
-                if m.file != *target_file {
-                    continue;
-                }
-            }
-
-            // Filter out matches that are in translation files (already handled)
-            // But only if we're not searching a specific file
-            let path_str = m.file.to_string_lossy();
-            if specific_file.is_none()
-                && (path_str.ends_with(".yml")
-                    || path_str.ends_with(".yaml")
-                    || path_str.ends_with(".json")
-                    || path_str.ends_with(".js"))
-            {
-                continue;
-            }
-
-            // Apply exclusions
-            if exclusions.iter().any(|ex| path_str.contains(ex)) {
-                continue;
-            }
-
-            // Convert Match to CodeReference
-            all_code_refs.push(CodeReference {
-                file: m.file.clone(),
-                line: m.line,
-                pattern: "Direct Match".to_string(),
-                context: m.content.clone(),
-                key_path: query.text.clone(), // Use the search text as the "key"
-                context_before: m.context_before.clone(),
-                context_after: m.context_after.clone(),
-            });
-        }
-    }
-
-    // Deduplicate code references (in case same reference matches multiple key variations)
-    // We prioritize "traced" matches (where key_path != query) over "direct" matches (where key_path == query)
-    // This ensures that if we have both for the same line, we keep the one that links to a translation key.
-    all_code_refs.sort_by(|a, b| {
-        a.file.cmp(&b.file).then(a.line.cmp(&b.line)).then_with(|| {
-            let a_is_direct = a.key_path == query.text;
-            let b_is_direct = b.key_path == query.text;
-            // We want traced (false) to come before direct (true) so it is kept by dedup
-            a_is_direct.cmp(&b_is_direct)
-        })
-    });
-    all_code_refs.dedup_by(|a, b| a.file == b.file && a.line == b.line);
-
-    Ok(SearchResult {
-        query: query.text,
-        translation_entries,
-        code_references: all_code_refs,
-    })
+    let mut file_matches = Vec::new();
+    SearchSession::new(query).run_with(warm, |event| match event {
+        SearchEvent::Translation(entry) => result.translation_entries.push(entry),
+        SearchEvent::CodeReference(reference) => result.code_references.push(reference),
+        SearchEvent::File(file_match) => file_matches.push(file_match),
+    })?;
+
+    // The session hands out references and files as it finds them; results
+    // list them by path and line
+    result
+        .code_references
+        .sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    file_matches.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((result, file_matches))
 }
 
 /// Orchestrates the call graph tracing process
@@ -305,22 +353,150 @@ pub fn run_search(query: SearchQuery) -> Result<SearchResult> {
 ///
 /// # Returns
 /// A `CallTree` representing the call graph, or `None` if the start function is not found.
+/// When several functions match and the query does not pin one with
+/// [`TraceQuery::with_definition`], fails with [`SearchError::AmbiguousFunction`].
 #[must_use = "this function returns a Result that should be handled"]
 pub fn run_trace(query: TraceQuery) -> Result<Option<CallTree>> {
-    let base_dir = query
-        .base_dir
+    trace_project(query, open_symbol_index)
+}
+
+/// The trace behind [`run_trace`], taking the symbol index for the project
+/// from `symbol_index`. The query daemon hands out the index it keeps open.
+pub(crate) fn trace_project(
+    query: TraceQuery,
+    symbol_index: impl FnOnce(&std::path::Path) -> Option<Arc<SymbolIndex>>,
+) -> Result<Option<CallTree>> {
+    let (mut finder, extractor) = call_graph_services(
+        &query.base_dir,
+        &query.config,
+        query.use_symbol_index,
+        symbol_index,
+    );
+
+    match start_function(&mut finder, &query.function_name, &query.definition)? {
+        Some(start_fn) => {
+            let mut builder =
+                CallGraphBuilder::new(query.direction, query.max_depth, &mut finder, &extractor);
+            builder.build_trace(&start_fn)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Find every call chain from `query.from` to `query.to`, shortest first
+///
+/// # Returns
+/// The paths found, or `None` if either function is not found. Like
+/// [`run_trace`], fails with [`SearchError::AmbiguousFunction`] when several
+/// functions match `query.from`.
+#[must_use = "this function returns a Result that should be handled"]
+pub fn run_path_search(query: PathQuery) -> Result<Option<CallPaths>> {
+    let (mut finder, extractor) = call_graph_services(
+        &query.base_dir,
+        &query.config,
+        query.use_symbol_index,
+        open_symbol_index,
+    );
+
+    let Some(start_fn) = start_function(&mut finder, &query.from, &query.definition)? else {
+        return Ok(None);
+    };
+    let targets = finder.find_functions(&query.to);
+    if targets.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = CallGraphBuilder::new(
+        TraceDirection::Forward,
+        query.max_depth,
+        &mut finder,
+        &extractor,
+    );
+    Ok(Some(builder.find_paths(
+        &start_fn,
+        &targets,
+        query.max_nodes,
+        query.max_paths,
+    )))
+}
+
+/// The finder and extractor for tracing calls under `base_dir`
+fn call_graph_services(
+    base_dir: &Option<PathBuf>,
+    config: &ProjectConfig,
+    use_symbol_index: bool,
+    symbol_index: impl FnOnce(&std::path::Path) -> Option<Arc<SymbolIndex>>,
+) -> (FunctionFinder, CallExtractor) {
+    let base_dir = base_dir
         .clone()
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
-    let mut finder = FunctionFinder::new(base_dir.clone());
-    if let Some(start_fn) = finder.find_function(&query.function_name) {
-        let extractor = CallExtractor::new(base_dir);
-        let mut builder =
-            CallGraphBuilder::new(query.direction, query.max_depth, &mut finder, &extractor);
-        builder.build_trace(&start_fn)
-    } else {
-        Ok(None)
+    let languages = config.languages();
+    let mut finder = FunctionFinder::new(base_dir.clone()).with_language_map(languages.clone());
+    let mut extractor = CallExtractor::new(base_dir.clone()).with_language_map(languages.clone());
+
+    // The index is an accelerator only: if it cannot be opened (e.g. another
+    // `cs` process holds it) we fall back to scanning the project. It is built
+    // with the default language mapping, so custom mappings bypass it.
+    if use_symbol_index && languages.is_empty() {
+        if let Some(index) = symbol_index(&base_dir) {
+            finder = finder.with_symbol_index(Arc::clone(&index));
+            extractor = extractor.with_symbol_index(index);
+        }
     }
+
+    (finder, extractor)
+}
+
+/// The single definition `name` refers to, narrowed down to the one at
+/// `definition` if given
+fn start_function(
+    finder: &mut FunctionFinder,
+    name: &str,
+    definition: &Option<(PathBuf, usize)>,
+) -> Result<Option<FunctionDef>> {
+    let mut candidates = finder.find_functions(name);
+    if let Some((file, line)) = definition {
+        let wanted = file.canonicalize().unwrap_or_else(|_| file.clone());
+        candidates.retain(|def| {
+            def.line == *line
+                && def.file.canonicalize().unwrap_or_else(|_| def.file.clone()) == wanted
+        });
+    }
+
+    match candidates.len() {
+        0 | 1 => Ok(candidates.pop()),
+        _ => Err(SearchError::AmbiguousFunction {
+            name: name.to_string(),
+            candidates,
+        }),
+    }
+}
+
+/// The symbol index of the project traced last by this process. All
+/// projects share one database, which only a single handle may hold open.
+static PROCESS_SYMBOL_INDEX: Mutex<Option<(PathBuf, Arc<SymbolIndex>)>> = Mutex::new(None);
+
+/// The symbol index for `base_dir`, refreshed only the first time this process
+/// asks for it: later traces, like one per candidate with `--all-candidates`,
+/// reuse the handle instead of walking the project again.
+fn open_symbol_index(base_dir: &std::path::Path) -> Option<Arc<SymbolIndex>> {
+    let mut opened = PROCESS_SYMBOL_INDEX
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((root, index)) = opened.as_ref() {
+        if root == base_dir {
+            return Some(Arc::clone(index));
+        }
+    }
+
+    // Close the other project's index before opening this one
+    *opened = None;
+    let mut index = SymbolIndex::open(base_dir).ok()?;
+    index.refresh().ok()?;
+    let index = Arc::new(index);
+    *opened = Some((base_dir.to_path_buf(), Arc::clone(&index)));
+    Some(index)
 }
 
 /// Helper function to filter translation files from search results
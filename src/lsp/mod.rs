@@ -0,0 +1,228 @@
+//! Language Server Protocol mode (`cs lsp`).
+//!
+//! Speaks LSP over stdio so editors can navigate translations the same way
+//! the CLI traces them:
+//!
+//! - **hover** over `t('invoice.labels.add_new')` shows the value from every
+//!   locale file
+//! - **go to definition** jumps to the locale file line defining the key
+//! - **find references** on a locale file line (or on a key in code) lists
+//!   its [`CodeReference`](crate::CodeReference)s
+//! - **call hierarchy** on a function is backed by
+//!   [`CallGraphBuilder`](crate::CallGraphBuilder)
+//!
+//! Translations are parsed once when the client initializes and again when
+//! a translation file is saved. Hover and go to definition read the editor's
+//! open buffers; references and call hierarchy search the files on disk.
+
+mod server;
+
+pub use server::LanguageServer;
+
+use crate::error::{Result, SearchError};
+use crate::ProjectConfig;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _,
+};
+use lsp_types::request::{
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare, GotoDefinition,
+    HoverRequest, References, Request as _,
+};
+use lsp_types::{
+    CallHierarchyServerCapability, HoverProviderCapability, InitializeParams, OneOf,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Url,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Run the language server on stdin/stdout until the client exits
+pub fn run_stdio() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(connection)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Run the language server on `connection`: initialize, then answer
+/// requests until the client shuts it down
+pub fn serve(connection: Connection) -> Result<()> {
+    let capabilities = serde_json::to_value(capabilities()).map_err(SearchError::lsp_error)?;
+    let params = connection
+        .initialize(capabilities)
+        .map_err(SearchError::lsp_error)?;
+    let params: InitializeParams =
+        serde_json::from_value(params).map_err(SearchError::lsp_error)?;
+
+    let root = workspace_root(&params)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    let config = ProjectConfig::discover(&root)?;
+    let mut server = LanguageServer::new(root, config)?;
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection
+                    .handle_shutdown(&request)
+                    .map_err(SearchError::lsp_error)?
+                {
+                    return Ok(());
+                }
+                let response = handle_request(&server, request);
+                connection
+                    .sender
+                    .send(Message::Response(response))
+                    .map_err(SearchError::lsp_error)?;
+            }
+            Message::Notification(notification) => handle_notification(&mut server, notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..Default::default()
+            },
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+        ..Default::default()
+    }
+}
+
+/// The first workspace folder, or the (deprecated) root URI
+fn workspace_root(params: &InitializeParams) -> Option<PathBuf> {
+    #[allow(deprecated)]
+    let root_uri = params.root_uri.as_ref();
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(root_uri)
+        .and_then(|uri| uri.to_file_path().ok())
+}
+
+fn handle_request(server: &LanguageServer, request: Request) -> Response {
+    let id = request.id.clone();
+    let result = match request.method.as_str() {
+        HoverRequest::METHOD => respond::<HoverRequest, _>(request, |params| {
+            let position = params.text_document_position_params;
+            Ok(server.hover(&file_path(&position.text_document.uri)?, position.position))
+        }),
+        GotoDefinition::METHOD => respond::<GotoDefinition, _>(request, |params| {
+            let position = params.text_document_position_params;
+            let locations =
+                server.definition(&file_path(&position.text_document.uri)?, position.position);
+            Ok((!locations.is_empty()).then(|| locations.into()))
+        }),
+        References::METHOD => respond::<References, _>(request, |params| {
+            let position = params.text_document_position;
+            let locations = server.references(
+                &file_path(&position.text_document.uri)?,
+                position.position,
+                params.context.include_declaration,
+            )?;
+            Ok(Some(locations))
+        }),
+        CallHierarchyPrepare::METHOD => respond::<CallHierarchyPrepare, _>(request, |params| {
+            let position = params.text_document_position_params;
+            let items = server.prepare_call_hierarchy(
+                &file_path(&position.text_document.uri)?,
+                position.position,
+            )?;
+            Ok((!items.is_empty()).then_some(items))
+        }),
+        CallHierarchyIncomingCalls::METHOD => {
+            respond::<CallHierarchyIncomingCalls, _>(request, |params| {
+                Ok(Some(server.incoming_calls(&params.item)?))
+            })
+        }
+        CallHierarchyOutgoingCalls::METHOD => {
+            respond::<CallHierarchyOutgoingCalls, _>(request, |params| {
+                Ok(Some(server.outgoing_calls(&params.item)?))
+            })
+        }
+        _ => Err((
+            ErrorCode::MethodNotFound,
+            format!("Unhandled method: {}", request.method),
+        )),
+    };
+
+    match result {
+        Ok(value) => Response::new_ok(id, value),
+        Err((code, message)) => Response::new_err(id, code as i32, message),
+    }
+}
+
+/// Decode a request's parameters, run `handler` and encode its result
+fn respond<R, F>(
+    request: Request,
+    handler: F,
+) -> std::result::Result<serde_json::Value, (ErrorCode, String)>
+where
+    R: lsp_types::request::Request,
+    R::Params: DeserializeOwned,
+    R::Result: Serialize,
+    F: FnOnce(R::Params) -> Result<R::Result>,
+{
+    let (_, params) = request
+        .extract::<R::Params>(R::METHOD)
+        .map_err(|e| (ErrorCode::InvalidParams, format!("{:?}", e)))?;
+    let result = handler(params).map_err(|e| (ErrorCode::InternalError, e.to_string()))?;
+    serde_json::to_value(result).map_err(|e| (ErrorCode::InternalError, e.to_string()))
+}
+
+fn handle_notification(server: &mut LanguageServer, notification: Notification) -> Result<()> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params = extract::<DidOpenTextDocument>(notification)?;
+            let document = params.text_document;
+            server.open_document(file_path(&document.uri)?, document.text);
+        }
+        DidChangeTextDocument::METHOD => {
+            let params = extract::<DidChangeTextDocument>(notification)?;
+            // Full sync: the last change holds the whole document
+            if let Some(change) = params.content_changes.into_iter().last() {
+                server.open_document(file_path(&params.text_document.uri)?, change.text);
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            let params = extract::<DidCloseTextDocument>(notification)?;
+            server.close_document(&file_path(&params.text_document.uri)?);
+        }
+        DidSaveTextDocument::METHOD => {
+            let params = extract::<DidSaveTextDocument>(notification)?;
+            server.document_saved(&file_path(&params.text_document.uri)?)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn extract<N>(notification: Notification) -> Result<N::Params>
+where
+    N: lsp_types::notification::Notification,
+    N::Params: DeserializeOwned,
+{
+    notification
+        .extract::<N::Params>(N::METHOD)
+        .map_err(|e| SearchError::lsp_error(format!("{:?}", e)))
+}
+
+fn file_path(uri: &Url) -> Result<PathBuf> {
+    uri.to_file_path()
+        .map_err(|_| SearchError::lsp_error(format!("Not a file URI: {}", uri)))
+}
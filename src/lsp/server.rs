@@ -0,0 +1,548 @@
+use crate::config::{self, ProjectConfig};
+use crate::error::Result;
+use crate::parse::key_extractor::is_translation_candidate;
+use crate::search::pattern_match::resolve_key;
+use crate::search::KeyMatcher;
+use crate::trace::{CallExtractor, CallGraphBuilder, FunctionDef, FunctionFinder, TraceDirection};
+use crate::{CodeReference, KeyExtractor, PatternMatcher, TranslationEntry};
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Hover, HoverContents,
+    Location, MarkupContent, MarkupKind, Position, Range, SymbolKind, Url,
+};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// State of a `cs lsp` session: the project's translations and the
+/// documents the editor has open
+pub struct LanguageServer {
+    root: PathBuf,
+    config: ProjectConfig,
+    exclusions: Vec<String>,
+    patterns: Vec<Regex>,
+    entries: Vec<TranslationEntry>,
+    /// Open documents, which may differ from the files on disk
+    documents: HashMap<PathBuf, String>,
+}
+
+impl LanguageServer {
+    /// Start a session for the project at `root`, parsing its translations
+    pub fn new(root: PathBuf, config: ProjectConfig) -> Result<Self> {
+        let mut exclusions = config.exclusions(config::detect_project_type(&root));
+        exclusions.sort();
+        exclusions.dedup();
+        let mut server = Self {
+            patterns: config.patterns(),
+            root,
+            config,
+            exclusions,
+            entries: Vec::new(),
+            documents: HashMap::new(),
+        };
+        server.reload_translations()?;
+        Ok(server)
+    }
+
+    /// The translation entries currently known
+    pub fn entries(&self) -> &[TranslationEntry] {
+        &self.entries
+    }
+
+    /// Parse every translation file in the project again
+    pub fn reload_translations(&mut self) -> Result<()> {
+        let mut extractor = KeyExtractor::new();
+        extractor.set_exclusions(self.exclusions.clone());
+        extractor.set_locale_dirs(self.config.locale_dirs());
+        extractor.set_quiet(true);
+        self.entries = extractor.extract_all(&self.root)?;
+        Ok(())
+    }
+
+    /// Track the editor's content for `path`
+    pub fn open_document(&mut self, path: PathBuf, text: String) {
+        self.documents.insert(path, text);
+    }
+
+    /// Go back to reading `path` from disk
+    pub fn close_document(&mut self, path: &Path) {
+        self.documents.remove(path);
+    }
+
+    /// Pick up changes to translations when a translation file is saved
+    pub fn document_saved(&mut self, path: &Path) -> Result<()> {
+        if is_translation_candidate(&self.root, path, &self.exclusions) {
+            self.reload_translations()?;
+        }
+        Ok(())
+    }
+
+    /// The values of the key under the cursor in every locale
+    pub fn hover(&self, path: &Path, position: Position) -> Option<Hover> {
+        let (key, range) = self.key_at(path, position)?;
+        let entries = self.entries_for(&key);
+
+        let mut markdown = format!("**{}**\n\n", key);
+        if entries.is_empty() {
+            markdown.push_str("Not defined in any translation file");
+        } else {
+            markdown.push_str("| Locale | Value |\n|---|---|\n");
+            for entry in entries {
+                markdown.push_str(&format!(
+                    "| {} | {} |\n",
+                    table_cell(&self.locale_label(entry)),
+                    table_cell(&entry.value)
+                ));
+            }
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: markdown,
+            }),
+            range: Some(range),
+        })
+    }
+
+    /// The translation file lines defining the key under the cursor
+    pub fn definition(&self, path: &Path, position: Position) -> Vec<Location> {
+        match self.key_at(path, position) {
+            Some((key, _)) => self
+                .entries_for(&key)
+                .into_iter()
+                .filter_map(|entry| self.entry_location(entry))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The code using the key defined on the cursor's line of a translation
+    /// file, or the key under the cursor in code
+    pub fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Result<Vec<Location>> {
+        let defined_here = self
+            .entries
+            .iter()
+            .find(|entry| entry.line == position.line as usize + 1 && same_file(&entry.file, path));
+        let key = match defined_here {
+            Some(entry) => entry.key.clone(),
+            None => match self.key_at(path, position) {
+                Some((key, _)) => key,
+                None => return Ok(Vec::new()),
+            },
+        };
+
+        let mut matcher = PatternMatcher::with_patterns(self.patterns.clone(), self.root.clone());
+        matcher.set_exclusions(self.exclusions.clone());
+        matcher.set_locale_dirs(self.config.locale_dirs());
+        let references = matcher.find_usages_of(&KeyMatcher::from_keys(vec![key.clone()]))?;
+
+        let mut locations: Vec<Location> =
+            references.iter().filter_map(reference_location).collect();
+        if include_declaration {
+            locations.extend(
+                self.entries_for(&key)
+                    .into_iter()
+                    .filter_map(|entry| self.entry_location(entry)),
+            );
+        }
+        Ok(locations)
+    }
+
+    /// The definitions of the function named under the cursor
+    pub fn prepare_call_hierarchy(
+        &self,
+        path: &Path,
+        position: Position,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let Some(name) = self.identifier_at(path, position) else {
+            return Ok(Vec::new());
+        };
+        Ok(self
+            .finder()
+            .find_definition(&name)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|def| self.call_hierarchy_item(def))
+            .collect())
+    }
+
+    /// The functions calling `item`, with the lines of their calls
+    pub fn incoming_calls(
+        &self,
+        item: &CallHierarchyItem,
+    ) -> Result<Vec<CallHierarchyIncomingCall>> {
+        let mut finder = self.finder();
+        let extractor = self.extractor();
+        let Some(def) = definition_of(&mut finder, item) else {
+            return Ok(Vec::new());
+        };
+        let Some(tree) =
+            CallGraphBuilder::new(TraceDirection::Backward, 1, &mut finder, &extractor)
+                .build_trace(&def)?
+        else {
+            return Ok(Vec::new());
+        };
+
+        let sites = extractor.find_callers(&def.name)?;
+        Ok(tree
+            .root
+            .children
+            .iter()
+            .filter_map(|caller| {
+                let from_ranges = sites
+                    .iter()
+                    .filter(|site| {
+                        site.caller_name == caller.def.name
+                            && same_file(&site.file, &caller.def.file)
+                    })
+                    .filter_map(|site| self.word_range(&site.file, site.line, &def.name))
+                    .collect();
+                Some(CallHierarchyIncomingCall {
+                    from: self.call_hierarchy_item(&caller.def)?,
+                    from_ranges,
+                })
+            })
+            .collect())
+    }
+
+    /// The functions `item` calls, with the lines of the calls
+    pub fn outgoing_calls(
+        &self,
+        item: &CallHierarchyItem,
+    ) -> Result<Vec<CallHierarchyOutgoingCall>> {
+        let mut finder = self.finder();
+        let extractor = self.extractor();
+        let Some(def) = definition_of(&mut finder, item) else {
+            return Ok(Vec::new());
+        };
+        let Some(tree) = CallGraphBuilder::new(TraceDirection::Forward, 1, &mut finder, &extractor)
+            .build_trace(&def)?
+        else {
+            return Ok(Vec::new());
+        };
+
+        Ok(tree
+            .root
+            .children
+            .iter()
+            .filter_map(|callee| {
+                // The first call to the callee in the body
+                let call =
+                    Regex::new(&format!(r"\b{}\s*\(", regex::escape(&callee.def.name))).ok()?;
+                let from_ranges = def
+                    .body
+                    .lines()
+                    .position(|line| call.is_match(line))
+                    .and_then(|offset| {
+                        self.word_range(&def.file, def.line + offset, &callee.def.name)
+                    })
+                    .into_iter()
+                    .collect();
+                Some(CallHierarchyOutgoingCall {
+                    to: self.call_hierarchy_item(&callee.def)?,
+                    from_ranges,
+                })
+            })
+            .collect())
+    }
+
+    /// The translation key in the i18n call under the cursor, and its range
+    fn key_at(&self, path: &Path, position: Position) -> Option<(String, Range)> {
+        let text = self.text(path)?;
+        let line = text.lines().nth(position.line as usize)?;
+        let offset = byte_offset(line, position.character);
+
+        self.patterns.iter().find_map(|pattern| {
+            pattern
+                .captures_iter(line)
+                .filter_map(|captures| captures.get(1))
+                .find(|key| key.start() <= offset && offset <= key.end())
+                .map(|key| {
+                    (
+                        resolve_key(path, key.as_str()),
+                        line_range(position.line, line, key.start(), key.end()),
+                    )
+                })
+        })
+    }
+
+    /// The identifier under the cursor
+    fn identifier_at(&self, path: &Path, position: Position) -> Option<String> {
+        let text = self.text(path)?;
+        let line = text.lines().nth(position.line as usize)?;
+        let offset = byte_offset(line, position.character);
+
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+        let start = line[..offset]
+            .rfind(|c: char| !is_ident(c))
+            .map_or(0, |i| i + 1);
+        let end = line[offset..]
+            .find(|c: char| !is_ident(c))
+            .map_or(line.len(), |i| offset + i);
+        (start < end).then(|| line[start..end].to_string())
+    }
+
+    /// Entries defining `key`, in every locale
+    fn entries_for(&self, key: &str) -> Vec<&TranslationEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.key == key)
+            .collect()
+    }
+
+    /// How an entry's locale is shown: its locale code (or file name when
+    /// unknown), with the branch it belongs to
+    fn locale_label(&self, entry: &TranslationEntry) -> String {
+        let locale = entry.locale.clone().unwrap_or_else(|| {
+            entry
+                .file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        match &entry.branch {
+            Some(branch) => format!("{} ({})", locale, branch),
+            None => locale,
+        }
+    }
+
+    /// Where an entry is defined, narrowed to its last key segment
+    fn entry_location(&self, entry: &TranslationEntry) -> Option<Location> {
+        let last = entry.key.rsplit('.').next().unwrap_or(&entry.key);
+        let range = self
+            .word_range(&entry.file, entry.line, last)
+            .unwrap_or_else(|| whole_line(entry.line));
+        Some(Location::new(self.uri(&entry.file)?, range))
+    }
+
+    fn call_hierarchy_item(&self, def: &FunctionDef) -> Option<CallHierarchyItem> {
+        let selection_range = self
+            .word_range(&def.file, def.line, &def.name)
+            .unwrap_or_else(|| whole_line(def.line));
+        Some(CallHierarchyItem {
+            name: def.name.clone(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: Some(format!(
+                "{}:{}",
+                self.relative(&def.file).display(),
+                def.line
+            )),
+            uri: self.uri(&def.file)?,
+            range: whole_line(def.line),
+            selection_range,
+            data: None,
+        })
+    }
+
+    /// The range of the first occurrence of `word` on a line (1-indexed)
+    fn word_range(&self, file: &Path, line: usize, word: &str) -> Option<Range> {
+        let text = self.text(file)?;
+        let content = text.lines().nth(line.checked_sub(1)?)?;
+        let start = content.find(word)?;
+        Some(line_range(
+            line as u32 - 1,
+            content,
+            start,
+            start + word.len(),
+        ))
+    }
+
+    /// The editor's content for `path`, or the file on disk
+    fn text(&self, path: &Path) -> Option<String> {
+        match self.documents.get(path) {
+            Some(text) => Some(text.clone()),
+            None => fs::read_to_string(self.absolute(path)).ok(),
+        }
+    }
+
+    fn uri(&self, path: &Path) -> Option<Url> {
+        Url::from_file_path(self.absolute(path)).ok()
+    }
+
+    fn absolute(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        }
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    fn finder(&self) -> FunctionFinder {
+        FunctionFinder::new(self.root.clone()).with_language_map(self.config.languages())
+    }
+
+    fn extractor(&self) -> CallExtractor {
+        CallExtractor::new(self.root.clone()).with_language_map(self.config.languages())
+    }
+}
+
+/// The definition a call hierarchy item was made from
+fn definition_of(finder: &mut FunctionFinder, item: &CallHierarchyItem) -> Option<FunctionDef> {
+    let path = item.uri.to_file_path().ok()?;
+    let mut defs = finder.find_definition(&item.name).ok()?;
+    let index = defs
+        .iter()
+        .position(|def| {
+            def.line == item.range.start.line as usize + 1 && same_file(&def.file, &path)
+        })
+        .unwrap_or(0);
+    (index < defs.len()).then(|| defs.swap_remove(index))
+}
+
+fn reference_location(reference: &CodeReference) -> Option<Location> {
+    let uri = Url::from_file_path(&reference.file).ok()?;
+    let range = match reference.context.find(&reference.key_path) {
+        Some(start) if !reference.confidence.is_possible() => line_range(
+            reference.line as u32 - 1,
+            &reference.context,
+            start,
+            start + reference.key_path.len(),
+        ),
+        _ => whole_line(reference.line),
+    };
+    Some(Location::new(uri, range))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+}
+
+/// The byte offset in `line` of a UTF-16 column, as LSP counts them
+fn byte_offset(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= character as usize {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+/// The UTF-16 column of a byte offset in `line`
+fn utf16_column(line: &str, offset: usize) -> u32 {
+    line[..offset].encode_utf16().count() as u32
+}
+
+/// The range of bytes `start..end` of `line`, which is line `line_number`
+/// (0-indexed) of its document
+fn line_range(line_number: u32, line: &str, start: usize, end: usize) -> Range {
+    Range::new(
+        Position::new(line_number, utf16_column(line, start)),
+        Position::new(line_number, utf16_column(line, end)),
+    )
+}
+
+/// The start of line `line` (1-indexed)
+fn whole_line(line: usize) -> Range {
+    let line = line.saturating_sub(1) as u32;
+    Range::new(Position::new(line, 0), Position::new(line + 1, 0))
+}
+
+/// A value made safe to put in a Markdown table cell
+fn table_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let project = TempDir::new().unwrap();
+        let locales = project.path().join("config/locales");
+        fs::create_dir_all(&locales).unwrap();
+        fs::write(
+            locales.join("en.yml"),
+            "en:\n  invoice:\n    add_new: \"Add | New\"\n",
+        )
+        .unwrap();
+        fs::write(
+            locales.join("fr.yml"),
+            "fr:\n  invoice:\n    add_new: \"Ajouter\"\n",
+        )
+        .unwrap();
+        fs::write(
+            project.path().join("invoices.rb"),
+            "label = t('invoice.add_new')\n",
+        )
+        .unwrap();
+        project
+    }
+
+    fn server(project: &TempDir) -> LanguageServer {
+        LanguageServer::new(project.path().to_path_buf(), ProjectConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_utf16_columns() {
+        let line = "é = t('😀.a')";
+        // `é` is one UTF-16 unit but two bytes, `😀` two units and four bytes
+        assert_eq!(byte_offset(line, 1), 2);
+        assert_eq!(byte_offset(line, 9), 12);
+        assert_eq!(utf16_column(line, 12), 9);
+        assert_eq!(byte_offset(line, 100), line.len());
+    }
+
+    #[test]
+    fn test_hover_uses_open_document() {
+        let project = project();
+        let mut server = server(&project);
+        let file = project.path().join("invoices.rb");
+
+        // Outside the key
+        assert!(server.hover(&file, Position::new(0, 2)).is_none());
+
+        let hover = server.hover(&file, Position::new(0, 14)).unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup");
+        };
+        assert!(content.value.contains("| en | Add \\| New |"));
+        assert!(content.value.contains("| fr | Ajouter |"));
+        assert_eq!(
+            hover.range,
+            Some(Range::new(Position::new(0, 11), Position::new(0, 26)))
+        );
+
+        // Unsaved edits are used
+        server.open_document(file.clone(), "x = t('invoice.missing')\n".to_string());
+        let hover = server.hover(&file, Position::new(0, 10)).unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup");
+        };
+        assert!(content.value.contains("Not defined"));
+    }
+
+    #[test]
+    fn test_saving_a_translation_file_reloads() {
+        let project = project();
+        let mut server = server(&project);
+        let en = project.path().join("config/locales/en.yml");
+        fs::write(&en, "en:\n  invoice:\n    add_new: \"Create\"\n").unwrap();
+
+        server
+            .document_saved(&project.path().join("invoices.rb"))
+            .unwrap();
+        assert!(server.entries().iter().any(|e| e.value == "Add | New"));
+
+        server.document_saved(&en).unwrap();
+        assert!(server.entries().iter().any(|e| e.value == "Create"));
+        assert!(!server.entries().iter().any(|e| e.value == "Add | New"));
+    }
+}
@@ -1,7 +1,8 @@
-use clap::Parser;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::*;
 use regex::RegexBuilder;
 use std::env;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
@@ -10,7 +11,11 @@ use std::process;
 #[command(name = "cs")]
 #[command(author, version, about = "Code Search - Intelligent code search tool for tracing text (UI text, function names, variables) to implementation code", long_about = None)]
 #[command(help_template = "{name} {version}\n{about}\n\nUSAGE:\n    {usage}\n\n{all-args}")]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Text to search for (UI text, function names, variables, error messages, etc.)
     #[arg(value_name = "SEARCH_TEXT")]
     search_text: Option<String>,
@@ -31,6 +36,10 @@ struct Cli {
     #[arg(long)]
     simple: bool,
 
+    /// Output results as a versioned JSON document (for scripts and editor integrations)
+    #[arg(long, conflicts_with = "simple")]
+    json: bool,
+
     /// Clear the search result cache
     #[arg(long)]
     clear_cache: bool,
@@ -59,6 +68,18 @@ struct Cli {
     #[arg(long = "regex")]
     regex: bool,
 
+    /// Show NUM lines after each code match (default: 2)
+    #[arg(short = 'A', long = "after-context", value_name = "NUM")]
+    after_context: Option<usize>,
+
+    /// Show NUM lines before each code match (default: 2)
+    #[arg(short = 'B', long = "before-context", value_name = "NUM")]
+    before_context: Option<usize>,
+
+    /// Show NUM lines before and after each code match; -A and -B take precedence
+    #[arg(short = 'C', long = "context", value_name = "NUM")]
+    context: Option<usize>,
+
     /// Search for files by name only (skip content search)
     #[arg(
         short = 'f',
@@ -81,6 +102,49 @@ struct Cli {
     #[arg(long, conflicts_with = "trace", conflicts_with = "traceback")]
     trace_all: bool,
 
+    /// Export the call trace as a graph: dot, mermaid, graphml or json
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = parse_graph_format,
+        conflicts_with = "json",
+        conflicts_with = "simple"
+    )]
+    format: Option<cs::GraphFormat>,
+
+    /// Group graph nodes by file or dir (with --format)
+    #[arg(long, value_name = "BY", value_parser = parse_clustering, requires = "format")]
+    cluster: Option<cs::Clustering>,
+
+    /// Trace every function matching an ambiguous name instead of listing them
+    #[arg(long)]
+    all_candidates: bool,
+
+    /// Trace the definition at FILE:LINE when several functions match the name
+    #[arg(long, value_name = "FILE:LINE", value_parser = parse_location, conflicts_with = "all_candidates")]
+    defined_at: Option<(PathBuf, usize)>,
+
+    /// Print every call chain from the first function to the second, shortest first
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["FROM", "TO"],
+        conflicts_with_all = ["trace", "traceback", "trace_all", "file_only", "format"]
+    )]
+    path_between: Option<Vec<String>>,
+
+    /// Maximum number of functions visited by --path-between
+    #[arg(long, default_value = "10000", value_name = "NUM")]
+    max_nodes: usize,
+
+    /// Maximum number of call paths printed by --path-between
+    #[arg(long, default_value = "100", value_name = "NUM")]
+    max_paths: usize,
+
+    /// Don't use the persistent symbol index when tracing (scan files instead)
+    #[arg(long)]
+    no_index: bool,
+
     /// Maximum depth for call tracing (default: 3, max: 10)
     #[arg(long, default_value = "3", value_parser = validate_depth)]
     depth: usize,
@@ -94,6 +158,78 @@ struct Cli {
     search_all: bool,
 }
 
+/// Project-wide audit commands
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Report translation keys that are never referenced from code
+    UnusedKeys(AuditArgs),
+
+    /// Report keys missing between sibling locale files, and keys used in code
+    /// that no locale file defines
+    MissingKeys(MissingKeysArgs),
+
+    /// Run a language server over stdio, for hover, go to definition, find
+    /// references and call hierarchy in editors
+    Lsp,
+
+    /// Inspect and manage the cache of parsed files and its server
+    #[command(subcommand)]
+    Cache(CacheCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Show the number of cached files, their size and hit rate
+    Stats,
+
+    /// Remove cached files not used for a while
+    Prune {
+        /// Age such as 30d, 12h, 45m or 90s
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: std::time::Duration,
+    },
+
+    /// Show whether the cache server is running
+    Status,
+
+    /// Stop the cache server
+    Stop,
+}
+
+/// Options shared by the audit commands
+#[derive(Args, Debug)]
+struct AuditArgs {
+    /// Project to audit (defaults to current directory)
+    #[arg(value_name = "PATH")]
+    path: Option<String>,
+
+    /// Additional patterns to exclude (e.g., "test,spec,mock")
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// Output in simple, machine-readable format (file:line:key)
+    #[arg(long)]
+    simple: bool,
+
+    /// Output the report as a versioned JSON document
+    #[arg(long, conflicts_with = "simple")]
+    json: bool,
+
+    /// Show verbose output including detailed parse error messages
+    #[arg(long)]
+    verbose: bool,
+}
+
+#[derive(Args, Debug)]
+struct MissingKeysArgs {
+    #[command(flatten)]
+    audit: AuditArgs,
+
+    /// Locale the other locales are compared against
+    #[arg(long, default_value = "en", value_name = "LOCALE")]
+    base_locale: String,
+}
+
 /// Validate that depth is between 1 and 10
 fn validate_depth(s: &str) -> Result<usize, String> {
     let depth: usize = s
@@ -107,13 +243,96 @@ fn validate_depth(s: &str) -> Result<usize, String> {
     Ok(depth)
 }
 
+/// Parse an age like `30d`, `12h`, `45m` or `90s` (plain numbers are seconds)
+fn parse_age(s: &str) -> Result<std::time::Duration, String> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid age (e.g. 30d, 12h, 45m)", s))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit '{}', expected s, m, h, d or w", unit)),
+    };
+    Ok(std::time::Duration::from_secs(
+        number.saturating_mul(seconds),
+    ))
+}
+
+/// Parse a `--format` graph format name
+fn parse_graph_format(s: &str) -> Result<cs::GraphFormat, String> {
+    cs::GraphFormat::from_name(s).ok_or_else(|| {
+        format!(
+            "unknown format '{}', expected one of: {}",
+            s,
+            cs::GraphFormat::NAMES.join(", ")
+        )
+    })
+}
+
+/// Parse a `FILE:LINE` location
+fn parse_location(s: &str) -> Result<(PathBuf, usize), String> {
+    let (file, line) = s
+        .rsplit_once(':')
+        .ok_or_else(|| format!("expected FILE:LINE, got '{}'", s))?;
+    let line = line
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid line number", line))?;
+    Ok((PathBuf::from(file), line))
+}
+
+/// Parse a `--cluster` grouping
+fn parse_clustering(s: &str) -> Result<cs::Clustering, String> {
+    cs::Clustering::from_name(s).ok_or_else(|| {
+        format!(
+            "unknown clustering '{}', expected one of: {}",
+            s,
+            cs::Clustering::NAMES.join(", ")
+        )
+    })
+}
+
+/// Parse the command line. Subcommand names are also words people search
+/// for: one that doesn't parse as its subcommand (`cs cache`,
+/// `cs unused-keys --trace`) is taken as the search text instead.
+fn parse_cli() -> Cli {
+    let args: Vec<std::ffi::OsString> = env::args_os().collect();
+    let error = match Cli::try_parse_from(&args) {
+        Ok(cli) => return cli,
+        Err(error) => error,
+    };
+
+    let word = args.get(1).and_then(|arg| arg.to_str()).unwrap_or_default();
+    let asked_for_help = matches!(
+        error.kind(),
+        clap::error::ErrorKind::DisplayHelp | clap::error::ErrorKind::DisplayVersion
+    );
+    if asked_for_help || Cli::command().find_subcommand(word).is_none() {
+        error.exit();
+    }
+
+    // Without a subcommand of that name, the word is a positional argument
+    let command = Cli::command().mut_subcommand(word, |sub| sub.name("\0"));
+    command
+        .try_get_matches_from(&args)
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+        .unwrap_or_else(|_| error.exit())
+}
+
 fn main() {
     // Enable colored output (override TTY detection), unless NO_COLOR is set
     if std::env::var("NO_COLOR").is_err() {
         colored::control::set_override(true);
     }
 
-    let cli = Cli::parse();
+    let cli = parse_cli();
 
     // Hidden entrypoint: run cache server and exit
     if cli.cache_server {
@@ -128,7 +347,7 @@ fn main() {
     if cli.clear_cache {
         match cs::SearchResultCache::new() {
             Ok(cache) => {
-                if let Err(e) = cache.clear() {
+                if let Err(e) = cache.clear().and_then(|_| cs::SymbolIndex::clear_all()) {
                     eprintln!("Error clearing cache: {}", e);
                     process::exit(1);
                 }
@@ -142,6 +361,18 @@ fn main() {
         }
     }
 
+    match cli.command {
+        Some(Command::UnusedKeys(args)) => return run_unused_keys(args),
+        Some(Command::MissingKeys(args)) => return run_missing_keys(args),
+        Some(Command::Lsp) => return run_lsp(),
+        Some(Command::Cache(command)) => return run_cache(command),
+        None => {}
+    }
+
+    if let Some(functions) = &cli.path_between {
+        return run_path_between(&cli, &functions[0], &functions[1]);
+    }
+
     // Validate search text is non-empty (unless clearing cache)
     if !cli.clear_cache
         && (cli.search_text.is_none() || cli.search_text.as_ref().unwrap().trim().is_empty())
@@ -170,19 +401,26 @@ fn main() {
         (raw_path.clone(), raw_path)
     };
 
+    let config = load_config(&project_root);
+
     // Determine operation mode
     let is_trace_mode = cli.trace || cli.traceback || cli.trace_all;
 
+    if cli.format.is_some() && !is_trace_mode {
+        eprintln!(
+            "{} --format exports call traces; use it with --trace, --traceback or --trace-all",
+            "Error:".red().bold()
+        );
+        process::exit(1);
+    }
+
     if is_trace_mode {
         let direction = if cli.trace {
             cs::TraceDirection::Forward
         } else if cli.traceback {
             cs::TraceDirection::Backward
         } else {
-            // For trace_all, we can default to Forward for now,
-            // or implement logic to run both and merge results.
-            // For simplicity, let's just use Forward.
-            cs::TraceDirection::Forward
+            cs::TraceDirection::Both
         };
 
         let base_dir = if let Some(path) = &cli.path {
@@ -190,11 +428,31 @@ fn main() {
         } else {
             env::current_dir().unwrap_or_else(|_| Path::new(".").to_path_buf())
         };
-        let query = cs::TraceQuery::new(search_text.clone(), direction.clone(), cli.depth)
-            .with_base_dir(base_dir)
-            .with_exclusions(cli.exclude);
+        let mut query = cs::TraceQuery::new(search_text.clone(), direction.clone(), cli.depth)
+            .with_base_dir(base_dir.clone())
+            .with_exclusions(cli.exclude)
+            .with_symbol_index(!cli.no_index)
+            .with_config(config.clone());
+        if let Some((file, line)) = &cli.defined_at {
+            query = query.with_definition(base_dir.join(file), *line);
+        }
 
-        match cs::run_trace(query) {
+        match trace(query.clone()) {
+            Ok(Some(tree)) if cli.format.is_some() => {
+                let formatter =
+                    cs::GraphFormatter::new(cli.format.unwrap()).with_clustering(cli.cluster);
+                print!(
+                    "{}",
+                    formatter.format_trace(&search_text, &tree, &direction)
+                );
+            }
+            Ok(Some(tree)) if cli.json => {
+                let formatter = cs::JsonFormatter::new();
+                println!(
+                    "{}",
+                    formatter.format_trace(&search_text, Some(&tree), &direction)
+                );
+            }
             Ok(Some(tree)) => {
                 let formatter = cs::TreeFormatter::new()
                     .with_search_query(search_text.clone())
@@ -203,6 +461,10 @@ fn main() {
                 print!("{}", output);
             }
             Ok(None) => {
+                if cli.json {
+                    let formatter = cs::JsonFormatter::new();
+                    println!("{}", formatter.format_trace(&search_text, None, &direction));
+                }
                 eprintln!(
                     "{} Function '{}' not found in codebase",
                     "Error:".red().bold(),
@@ -229,11 +491,71 @@ fn main() {
                 );
                 process::exit(1);
             }
+            Err(cs::SearchError::AmbiguousFunction { candidates, .. }) if cli.all_candidates => {
+                let mut trees = Vec::new();
+                for def in &candidates {
+                    let pinned = query.clone().with_definition(def.file.clone(), def.line);
+                    match trace(pinned) {
+                        Ok(Some(tree)) => trees.push(tree),
+                        Ok(None) => {}
+                        Err(e) => {
+                            eprintln!("{} {}", "Error during call trace:".red().bold(), e);
+                            process::exit(1);
+                        }
+                    }
+                }
+
+                if let Some(format) = cli.format {
+                    let formatter = cs::GraphFormatter::new(format).with_clustering(cli.cluster);
+                    print!(
+                        "{}",
+                        formatter.format_traces(&search_text, &trees, &direction)
+                    );
+                } else if cli.json {
+                    let formatter = cs::JsonFormatter::new();
+                    println!(
+                        "{}",
+                        formatter.format_traces(&search_text, &trees, &direction)
+                    );
+                } else {
+                    let formatter = cs::TreeFormatter::new()
+                        .with_search_query(search_text.clone())
+                        .with_simple_format(cli.simple);
+                    let output = trees
+                        .iter()
+                        .map(|tree| formatter.format_trace_tree(tree, direction.clone()))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    print!("{}", output);
+                }
+            }
+            Err(cs::SearchError::AmbiguousFunction { candidates, .. }) => {
+                if cli.json {
+                    let formatter = cs::JsonFormatter::new();
+                    println!(
+                        "{}",
+                        formatter.format_trace_candidates(&search_text, &candidates)
+                    );
+                }
+                let flag = if cli.trace {
+                    "--trace"
+                } else if cli.traceback {
+                    "--traceback"
+                } else {
+                    "--trace-all"
+                };
+                exit_ambiguous(&search_text, &candidates, |name| {
+                    format!("cs {} '{}'", flag, name)
+                });
+            }
             Err(e) => {
                 // Handle errors with user-friendly messages and helpful guidance
                 use colored::Colorize;
                 use cs::SearchError;
 
+                if cli.json {
+                    println!("{}", cs::JsonFormatter::new().format_error(&e));
+                }
                 match e {
                     SearchError::Io(io_err) => {
                         eprintln!("{} {}", "IO Error:".red().bold(), io_err);
@@ -266,15 +588,16 @@ fn main() {
         // Use the new orchestrator and formatter for i18n search
         // Compute exclusions: Default (based on project type) + Manual (from CLI)
         let project_type = cs::config::detect_project_type(&project_root);
-        let mut exclusions: Vec<String> = cs::config::get_default_exclusions(project_type)
-            .iter()
-            .map(|&s| s.to_string())
-            .collect();
+        let mut exclusions = config.exclusions(project_type);
         exclusions.extend(cli.exclude.clone());
 
-        // Convert include_extensions to globs
+        // Convert include_extensions (from the CLI and .cs.toml) to globs
         let mut includes = cli.glob.clone();
-        for ext in cli.include_extensions {
+        for ext in cli
+            .include_extensions
+            .into_iter()
+            .chain(config.include_extensions())
+        {
             let pattern = if ext.starts_with('.') {
                 format!("*{}", ext)
             } else {
@@ -291,21 +614,40 @@ fn main() {
             .with_exclusions(cli.exclude)
             .with_includes(includes.clone())
             .with_verbose(cli.verbose)
-            .with_quiet(cli.simple);
+            .with_quiet(cli.simple || cli.json)
+            .with_config(config)
+            .with_before_context(cli.before_context.or(cli.context).unwrap_or(2))
+            .with_after_context(cli.after_context.or(cli.context).unwrap_or(2));
+        // Context is only shown by the flat result format, so asking for it
+        // selects that format over the reference tree
+        let context_requested =
+            cli.after_context.is_some() || cli.before_context.is_some() || cli.context.is_some();
 
         // If --all flag is set, also search for exact text matches in code
         if cli.search_all {
             query = query.with_includes(includes);
         }
 
-        // Perform file search
-        let file_searcher = cs::FileSearcher::new(project_root.clone())
-            .case_sensitive(!cli.ignore_case)
-            .add_exclusions(exclusions.clone());
-        let file_matches = file_searcher.search(&search_text).unwrap_or_default();
+        // If --file-only, skip content search and only match file names
+        let file_matches = if cli.file_only {
+            cs::FileSearcher::new(project_root.clone())
+                .case_sensitive(!cli.ignore_case)
+                .add_exclusions(exclusions.clone())
+                .search(&search_text)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
 
-        // If --file-only, skip content search
-        if cli.file_only {
+        if cli.file_only && cli.json {
+            let result = cs::SearchResult {
+                query: search_text.clone(),
+                translation_entries: Vec::new(),
+                code_references: Vec::new(),
+            };
+            let formatter = cs::JsonFormatter::new();
+            println!("{}", formatter.format_search(&result, &file_matches));
+        } else if cli.file_only {
             if file_matches.is_empty() {
                 println!("No files found matching '{}'", search_text);
             } else {
@@ -317,28 +659,41 @@ fn main() {
                 }
             }
         } else {
-            // Perform content search
-            match cs::run_search(query) {
-                Ok(result) => {
+            // Perform content search; file names are matched in the same walk.
+            // Simple output has one line per result, so like rg it prints
+            // results as they are found
+            let simple_formatter = cs::TreeFormatter::new()
+                .with_search_query(search_text.clone())
+                .with_simple_format(true);
+            let searched = if cli.simple && !cli.json {
+                search_each(query, |event| {
+                    match simple_formatter.format_event_simple(event) {
+                        Some(line) => write!(io::stdout(), "{}", line),
+                        None => Ok(()),
+                    }
+                })
+            } else {
+                search(query)
+            };
+            match searched {
+                Ok((result, file_matches)) => {
                     let has_translation_results = !result.translation_entries.is_empty();
                     let has_code_results = !result.code_references.is_empty();
                     let has_file_results = !file_matches.is_empty();
                     let has_any_results =
                         has_translation_results || has_code_results || has_file_results;
 
-                    if !has_any_results {
+                    if cli.json {
+                        // JSON documents are always emitted, even when empty,
+                        // so consumers never have to parse free-form text
+                        let formatter = cs::JsonFormatter::new();
+                        println!("{}", formatter.format_search(&result, &file_matches));
+                    } else if !has_any_results {
                         println!("No matches found for '{}'", search_text);
                     } else if cli.search_all {
                         if cli.simple {
-                            // Simple format: just output results without headers
-                            let formatter = cs::TreeFormatter::new()
-                                .with_search_query(search_text.clone())
-                                .with_simple_format(true);
-
-                            if has_translation_results || has_code_results {
-                                let output = formatter.format_result(&result);
-                                print!("{}", output);
-                            }
+                            // Simple format: results were printed as they were
+                            // found, without headers
                         } else {
                             // --all flag: Show structured sections with headers
                             println!("=== Search Results for '{}' ===\n", search_text.bold());
@@ -398,12 +753,15 @@ fn main() {
                         }
                     } else {
                         // Default behavior: Show only non-empty results
-                        if has_translation_results || has_code_results {
+                        if (has_translation_results || has_code_results) && cli.simple {
+                            // Simple results were printed as they were found
+                            println!();
+                        } else if has_translation_results || has_code_results {
                             let formatter = cs::TreeFormatter::new()
                                 .with_search_query(search_text.clone())
-                                .with_simple_format(cli.simple);
+                                .with_simple_format(false);
 
-                            let output = if cli.simple {
+                            let output = if context_requested {
                                 formatter.format_result(&result)
                             } else if has_translation_results {
                                 // Use tree format for translation searches to show hierarchy
@@ -436,7 +794,14 @@ fn main() {
                     use colored::Colorize;
                     use cs::SearchError;
 
+                    // Scripts still get a document on stdout; the guidance
+                    // below goes to stderr as usual
+                    if cli.json && !matches!(e, SearchError::Cancelled) {
+                        println!("{}", cs::JsonFormatter::new().format_error(&e));
+                    }
                     match e {
+                        // Whoever reads the output stopped reading
+                        SearchError::Cancelled => process::exit(0),
                         SearchError::NoTranslationFiles {
                             text,
                             searched_paths,
@@ -505,7 +870,7 @@ fn main() {
                             eprintln!("{}", "Possible reasons:".yellow().bold());
                             eprintln!("  • The key exists but is not yet used in code");
                             eprintln!(
-                                "  • The key is used dynamically (not detectable by static search)"
+                                "  • The key is built at runtime in a way static search can't follow"
                             );
                             eprintln!("  • The code files are outside the search scope");
                             eprintln!();
@@ -670,6 +1035,527 @@ fn is_code_file(file_path: &std::path::Path, custom_extensions: &[String]) -> bo
     false
 }
 
+/// Search in the query daemon when one is running, in-process otherwise.
+/// `--verbose` always searches in-process, where parse errors are reported.
+fn search(query: cs::SearchQuery) -> cs::Result<(cs::SearchResult, Vec<cs::FileMatch>)> {
+    if !query.verbose {
+        if let Some(found) = cs::QueryDaemon::connect().and_then(|d| d.search(query.clone())) {
+            return found;
+        }
+    }
+    cs::run_search_with_file_matches(query)
+}
+
+/// Like [`search`], but hands every result to `on_event` as soon as it is
+/// found. Results from the query daemon arrive all at once. A failing
+/// `on_event` cancels the search, which then fails with
+/// [`cs::SearchError::Cancelled`].
+fn search_each(
+    query: cs::SearchQuery,
+    mut on_event: impl FnMut(&cs::SearchEvent) -> io::Result<()>,
+) -> cs::Result<(cs::SearchResult, Vec<cs::FileMatch>)> {
+    use cs::SearchEvent;
+
+    if !query.verbose {
+        if let Some(found) = cs::QueryDaemon::connect().and_then(|d| d.search(query.clone())) {
+            let (result, file_matches) = found?;
+            let events = result
+                .translation_entries
+                .iter()
+                .cloned()
+                .map(SearchEvent::Translation)
+                .chain(
+                    result
+                        .code_references
+                        .iter()
+                        .cloned()
+                        .map(SearchEvent::CodeReference),
+                );
+            for event in events {
+                on_event(&event).map_err(|_| cs::SearchError::Cancelled)?;
+            }
+            return Ok((result, file_matches));
+        }
+    }
+
+    let mut result = cs::SearchResult {
+        query: query.text.clone(),
+        translation_entries: Vec::new(),
+        code_references: Vec::new(),
+    };
+    let mut file_matches = Vec::new();
+    let session = cs::SearchSession::new(query);
+    let cancel = session.cancellation();
+    session.run(|event| {
+        if on_event(&event).is_err() {
+            cancel.cancel();
+        }
+        match event {
+            SearchEvent::Translation(entry) => result.translation_entries.push(entry),
+            SearchEvent::CodeReference(reference) => result.code_references.push(reference),
+            SearchEvent::File(file_match) => file_matches.push(file_match),
+        }
+    })?;
+    file_matches.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((result, file_matches))
+}
+
+/// Report the functions an ambiguous name matches and exit. `command` spells
+/// the command line for a given (qualified) name.
+fn exit_ambiguous(
+    name: &str,
+    candidates: &[cs::FunctionDef],
+    command: impl Fn(&str) -> String,
+) -> ! {
+    eprintln!(
+        "{} '{}' matches {} functions:",
+        "Error:".red().bold(),
+        name.bold(),
+        candidates.len()
+    );
+    for def in candidates {
+        eprintln!(
+            "  • {} ({}:{})",
+            def.qualified_name().bold(),
+            def.file.display(),
+            def.line
+        );
+    }
+    eprintln!();
+    eprintln!("{}", "Next steps:".green().bold());
+    // A qualified name only helps if it matches a single candidate: two
+    // `Invoice#total` in different files, or `Invoice#total` next to
+    // `Billing::Invoice#total`, need the definition pinned instead
+    let unique = candidates
+        .iter()
+        .map(|def| def.qualified_name())
+        .find(|qualified| {
+            let query = cs::QualifiedName::parse(qualified);
+            candidates
+                .iter()
+                .filter(|def| def.name == query.name && query.matches_scope(def))
+                .count()
+                == 1
+        });
+    match unique {
+        Some(qualified) => eprintln!("  1. Qualify the name: {}", command(&qualified).cyan()),
+        None => eprintln!(
+            "  1. Pick a definition: {}",
+            format!(
+                "{} --defined-at {}:{}",
+                command(name),
+                candidates[0].file.display(),
+                candidates[0].line
+            )
+            .cyan()
+        ),
+    }
+    eprintln!(
+        "  2. Use every candidate: {}",
+        format!("{} --all-candidates", command(name)).cyan()
+    );
+    process::exit(1);
+}
+
+/// Print every call chain from one function to another
+fn run_path_between(cli: &Cli, from: &str, to: &str) {
+    // There is no search text, so a lone positional argument is the path
+    let base_dir = cli
+        .path
+        .as_ref()
+        .or(cli.search_text.as_ref())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    let config = load_config(&base_dir);
+    let mut query = cs::PathQuery::new(from.to_string(), to.to_string(), cli.depth)
+        .with_max_nodes(cli.max_nodes)
+        .with_max_paths(cli.max_paths)
+        .with_base_dir(base_dir.clone())
+        .with_symbol_index(!cli.no_index)
+        .with_config(config);
+    if let Some((file, line)) = &cli.defined_at {
+        query = query.with_definition(base_dir.join(file), *line);
+    }
+
+    let found = match cs::run_path_search(query.clone()) {
+        Err(cs::SearchError::AmbiguousFunction { candidates, .. }) if cli.all_candidates => {
+            let mut merged = cs::CallPaths::default();
+            for def in &candidates {
+                let pinned = query.clone().with_definition(def.file.clone(), def.line);
+                match cs::run_path_search(pinned) {
+                    Ok(Some(paths)) => merged.merge(paths),
+                    Ok(None) => {}
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red().bold(), e);
+                        process::exit(1);
+                    }
+                }
+            }
+            Ok(Some(merged))
+        }
+        other => other,
+    };
+
+    match found {
+        Ok(paths) if cli.json => {
+            let formatter = cs::JsonFormatter::new();
+            println!("{}", formatter.format_call_paths(from, to, paths.as_ref()));
+            if paths.is_none_or(|p| p.paths.is_empty()) {
+                process::exit(1);
+            }
+        }
+        Ok(Some(paths)) if !paths.paths.is_empty() => {
+            if !cli.simple {
+                println!(
+                    "{} call path{} from {} to {}:\n",
+                    paths.paths.len(),
+                    if paths.paths.len() == 1 { "" } else { "s" },
+                    from.bold(),
+                    to.bold()
+                );
+            }
+            let formatter = cs::TreeFormatter::new().with_simple_format(cli.simple);
+            print!("{}", formatter.format_call_paths(&paths));
+        }
+        Ok(Some(paths)) => {
+            eprintln!(
+                "{} No call path from '{}' to '{}' within depth {}",
+                "Error:".red().bold(),
+                from,
+                to,
+                cli.depth
+            );
+            if paths.truncated {
+                eprintln!(
+                    "  • The search stopped after visiting {} functions; raise it with {}",
+                    cli.max_nodes,
+                    "--max-nodes".cyan()
+                );
+            }
+            eprintln!("  • Allow longer chains with {}", "--depth".cyan());
+            process::exit(1);
+        }
+        Ok(None) => {
+            eprintln!(
+                "{} Function '{}' or '{}' not found in codebase",
+                "Error:".red().bold(),
+                from.bold(),
+                to.bold()
+            );
+            process::exit(1);
+        }
+        Err(cs::SearchError::AmbiguousFunction { candidates, .. }) => {
+            exit_ambiguous(from, &candidates, |name| {
+                format!("cs --path-between '{}' '{}'", name, to)
+            });
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", cs::JsonFormatter::new().format_error(&e));
+            }
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Trace in the query daemon when one is running, in-process otherwise
+fn trace(query: cs::TraceQuery) -> cs::Result<Option<cs::CallTree>> {
+    if let Some(tree) = cs::QueryDaemon::connect().and_then(|d| d.trace(query.clone())) {
+        return tree;
+    }
+    cs::run_trace(query)
+}
+
+/// Load the user-level config and the nearest `.cs.toml` for `path`,
+/// exiting with the offending file and line if it is invalid
+fn load_config(path: &Path) -> cs::ProjectConfig {
+    match cs::ProjectConfig::discover(path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Serve `cs lsp` on stdio until the editor disconnects
+fn run_lsp() {
+    // stdout carries the protocol, so errors can only go to stderr
+    if let Err(e) = cs::lsp::run_stdio() {
+        eprintln!("{} {}", "Error:".red().bold(), e);
+        process::exit(1);
+    }
+}
+
+/// Run a `cs cache` subcommand
+fn run_cache(command: CacheCommand) {
+    let result = match command {
+        CacheCommand::Stats => cs::SearchResultCache::open_existing()
+            .and_then(|cache| cache.stats())
+            .map(print_cache_stats),
+        CacheCommand::Prune { older_than } => cs::SearchResultCache::open_existing()
+            .and_then(|cache| cache.prune(older_than))
+            .map(|removed| {
+                println!(
+                    "Removed {} cached file{} not used in {}",
+                    removed,
+                    if removed == 1 { "" } else { "s" },
+                    format_duration(older_than)
+                )
+            }),
+        CacheCommand::Status => cs::SearchResultCache::server_status().map(|status| match status {
+            Some(status) => {
+                println!("Cache server running");
+                println!("  pid:           {}", status.pid);
+                println!("  address:       {}", status.address);
+                println!("  uptime:        {}", format_duration(status.uptime()));
+                println!("  idle timeout:  {}", format_duration(status.idle_timeout));
+                println!("  warm projects: {}", status.warm_projects);
+                println!("  turned away:   {}", status.turned_away);
+            }
+            None => println!("Cache server not running"),
+        }),
+        CacheCommand::Stop => cs::SearchResultCache::stop_server().map(|stopped| {
+            if stopped {
+                println!("Cache server stopped");
+            } else {
+                println!("Cache server not running");
+            }
+        }),
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(e @ cs::SearchError::ProtocolMismatch { .. }) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            eprintln!(
+                "The running server belongs to another release of cs; it exits on its own once idle"
+            );
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_cache_stats(stats: cs::CacheStats) {
+    let lookups = stats.hits + stats.misses;
+    println!("Cached files:   {}", stats.entries);
+    println!("Size on disk:   {}", format_size(stats.size_on_disk));
+    if lookups == 0 {
+        println!("Hits / misses:  0 / 0");
+    } else {
+        println!(
+            "Hits / misses:  {} / {} ({:.0}% hit rate)",
+            stats.hits,
+            stats.misses,
+            stats.hits as f64 * 100.0 / lookups as f64
+        );
+    }
+    match stats.oldest_access.and_then(|at| at.elapsed().ok()) {
+        Some(age) => println!("Oldest entry:   last used {} ago", format_duration(age)),
+        None => println!("Oldest entry:   -"),
+    }
+}
+
+/// `1536` → `1.5 KiB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Largest two units of a duration, e.g. `3d 4h` or `12m 5s`
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let parts = [
+        (secs / 86_400, "d"),
+        (secs / 3_600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let parts: Vec<String> = parts
+        .iter()
+        .skip_while(|(value, _)| *value == 0)
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Run `cs unused-keys` and print the report
+fn run_unused_keys(args: AuditArgs) {
+    let base_dir = args
+        .path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+    let config = load_config(&base_dir);
+    let report = match cs::audit::UnusedKeyFinder::new(base_dir)
+        .with_config(config)
+        .with_exclusions(args.exclude)
+        .with_verbose(args.verbose)
+        .run()
+    {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    };
+
+    if args.json {
+        println!("{}", cs::JsonFormatter::new().format_unused_keys(&report));
+    } else if args.simple {
+        for unused in &report.unused_keys {
+            for loc in &unused.locations {
+                println!("{}:{}:{}", loc.file.display(), loc.line, unused.key);
+            }
+        }
+    } else if report.unused_keys.is_empty() {
+        println!(
+            "{} All {} translation keys are referenced in code",
+            "✓".green(),
+            report.keys_checked
+        );
+    } else {
+        println!(
+            "{}",
+            format!(
+                "Unused translation keys ({} of {}):",
+                report.unused_keys.len(),
+                report.keys_checked
+            )
+            .bold()
+        );
+        for unused in &report.unused_keys {
+            println!("  {}", unused.key.yellow());
+            for loc in &unused.locations {
+                println!("    {}:{}", loc.file.display(), loc.line);
+            }
+        }
+    }
+}
+
+/// Run `cs missing-keys` and print the report
+fn run_missing_keys(args: MissingKeysArgs) {
+    let audit = args.audit;
+    let base_dir = audit
+        .path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+    let config = load_config(&base_dir);
+    let report = match cs::audit::MissingKeyFinder::new(base_dir)
+        .with_config(config)
+        .with_base_locale(args.base_locale)
+        .with_exclusions(audit.exclude)
+        .with_verbose(audit.verbose)
+        .run()
+    {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            process::exit(1);
+        }
+    };
+
+    if audit.json {
+        println!("{}", cs::JsonFormatter::new().format_missing_keys(&report));
+        return;
+    }
+
+    if audit.simple {
+        for diff in report.groups.iter().flat_map(|g| &g.locales) {
+            for key in &diff.missing {
+                println!("{}:missing:{}", diff.file.display(), key);
+            }
+            for key in &diff.extra {
+                println!("{}:extra:{}", diff.file.display(), key);
+            }
+        }
+        for undefined in &report.undefined_keys {
+            for loc in &undefined.locations {
+                println!(
+                    "{}:{}:undefined:{}",
+                    loc.file.display(),
+                    loc.line,
+                    undefined.key
+                );
+            }
+        }
+        return;
+    }
+
+    if report.is_clean() {
+        println!(
+            "{} All locales are complete and every referenced key is defined",
+            "✓".green()
+        );
+        return;
+    }
+
+    for group in &report.groups {
+        let incomplete: Vec<_> = group
+            .locales
+            .iter()
+            .filter(|l| !l.missing.is_empty() || !l.extra.is_empty())
+            .collect();
+        if incomplete.is_empty() {
+            continue;
+        }
+
+        let compared_to = match &group.base_locale {
+            Some(base) => format!("compared to {}", base),
+            None => "compared to all locales".to_string(),
+        };
+        println!("{} ({})", group.pattern.bold(), compared_to);
+        for diff in incomplete {
+            println!("  {} {}", diff.locale.cyan(), diff.file.display());
+            for key in &diff.missing {
+                println!("    {} {}", "missing".red(), key);
+            }
+            for key in &diff.extra {
+                println!("    {} {}", "extra".yellow(), key);
+            }
+        }
+        println!();
+    }
+
+    if !report.undefined_keys.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "Keys used in code but defined in no locale file ({}):",
+                report.undefined_keys.len()
+            )
+            .bold()
+        );
+        for undefined in &report.undefined_keys {
+            println!("  {}", undefined.key.yellow());
+            for loc in &undefined.locations {
+                println!("    {}:{}", loc.file.display(), loc.line);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,6 @@
-use crate::trace::{CallNode, CallTree, TraceDirection};
+use crate::trace::{CallNode, CallPaths, CallTree, TraceDirection};
 use crate::tree::{NodeType, ReferenceTree, TreeNode};
-use crate::{CodeReference, SearchResult};
+use crate::{CodeReference, Confidence, SearchEvent, SearchResult, TranslationEntry};
 use colored::*;
 use regex::RegexBuilder;
 
@@ -55,12 +55,16 @@ impl TreeFormatter {
             output.push_str(&format!("{}\n", "=== Translation Files ===".bold()));
             for entry in &result.translation_entries {
                 output.push_str(&format!(
-                    "{}:{}:{}: {}\n",
+                    "{}:{}:{}: {}",
                     entry.file.display(),
                     entry.line,
                     entry.key.yellow().bold(),
                     format!("\"{}\"", entry.value).green().bold()
                 ));
+                if let Some(branch) = &entry.branch {
+                    output.push_str(&format!(" {}", format!("[{}]", branch).dimmed()));
+                }
+                output.push('\n');
             }
             output.push('\n');
         }
@@ -93,31 +97,49 @@ impl TreeFormatter {
 
         // Translation entries in simple format
         for entry in &result.translation_entries {
-            let escaped_key = self.escape_simple_content(&entry.key);
-            let escaped_value = self.escape_simple_content(&entry.value);
-            let content = format!("{}: {}", escaped_key, escaped_value);
-            output.push_str(&format!(
-                "{}:{}:{}\n",
-                self.escape_simple_path(&entry.file.display().to_string()),
-                entry.line,
-                content
-            ));
+            output.push_str(&self.format_entry_simple(entry));
         }
 
         // Code references in simple format
         for code_ref in &result.code_references {
-            let escaped_content = self.escape_simple_content(code_ref.context.trim());
-            output.push_str(&format!(
-                "{}:{}:{}\n",
-                self.escape_simple_path(&code_ref.file.display().to_string()),
-                code_ref.line,
-                escaped_content
-            ));
+            output.push_str(&self.format_reference_simple(code_ref));
         }
 
         output
     }
 
+    /// Format one result of a [`SearchSession`](crate::SearchSession) the way
+    /// the simple format prints it, so results can be printed as they arrive.
+    /// File name matches are not part of the simple format and yield `None`.
+    pub fn format_event_simple(&self, event: &SearchEvent) -> Option<String> {
+        match event {
+            SearchEvent::Translation(entry) => Some(self.format_entry_simple(entry)),
+            SearchEvent::CodeReference(code_ref) => Some(self.format_reference_simple(code_ref)),
+            SearchEvent::File(_) => None,
+        }
+    }
+
+    fn format_entry_simple(&self, entry: &TranslationEntry) -> String {
+        let escaped_key = self.escape_simple_content(&entry.key);
+        let escaped_value = self.escape_simple_content(&entry.value);
+        format!(
+            "{}:{}:{}: {}\n",
+            self.escape_simple_path(&entry.file.display().to_string()),
+            entry.line,
+            escaped_key,
+            escaped_value
+        )
+    }
+
+    fn format_reference_simple(&self, code_ref: &CodeReference) -> String {
+        format!(
+            "{}:{}:{}\n",
+            self.escape_simple_path(&code_ref.file.display().to_string()),
+            code_ref.line,
+            self.escape_simple_content(code_ref.context.trim())
+        )
+    }
+
     /// Escape special characters in file paths for simple format
     fn escape_simple_path(&self, path: &str) -> String {
         // For file paths, we need to handle colons since they're our delimiter
@@ -200,8 +222,11 @@ impl TreeFormatter {
             }
 
             // Add the match line
-            let highlighted_context =
+            let mut highlighted_context =
                 self.highlight_key_in_context(&code_ref.context, &code_ref.key_path);
+            if code_ref.confidence.is_possible() {
+                highlighted_context.push_str(&format!(" {}", possible_marker(code_ref.confidence)));
+            }
             all_lines.push((code_ref.line, highlighted_context, true));
 
             // Add context after
@@ -211,12 +236,25 @@ impl TreeFormatter {
             }
         }
 
-        // Sort by line number and deduplicate
-        all_lines.sort_by_key(|(line_num, _, _)| *line_num);
+        // Sort by line number and deduplicate, so the context of adjacent
+        // hits merges. A line that is both a hit and another hit's context
+        // is shown as a hit
+        all_lines.sort_by_key(|(line_num, _, is_match)| (*line_num, !*is_match));
         all_lines.dedup_by_key(|(line_num, _, _)| *line_num);
 
+        // Like rg, separate groups of lines that aren't contiguous with `--`,
+        // but only when context is shown
+        let has_context = refs
+            .iter()
+            .any(|r| !r.context_before.is_empty() || !r.context_after.is_empty());
+
         // Format output like rg: context lines use '-', match lines use ':'
+        let mut previous_line = None;
         for (line_num, content, is_match) in all_lines {
+            if has_context && previous_line.is_some_and(|prev| line_num > prev + 1) {
+                output.push_str("--\n");
+            }
+            previous_line = Some(line_num);
             let separator = if is_match { ":" } else { "-" };
             output.push_str(&format!(
                 "{}{}{}:{}\n",
@@ -302,7 +340,70 @@ impl TreeFormatter {
         match direction {
             TraceDirection::Forward => self.format_forward_tree(tree),
             TraceDirection::Backward => self.format_backward_tree(tree),
+            TraceDirection::Both => self.format_bidirectional_tree(tree),
+        }
+    }
+
+    /// Render call paths as numbered `a -> b -> c` chains, shortest first.
+    ///
+    /// In simple format only the chains are printed, one per line.
+    pub fn format_call_paths(&self, paths: &CallPaths) -> String {
+        let mut output = String::new();
+        for (i, path) in paths.chains().enumerate() {
+            let chain = path
+                .iter()
+                .map(|def| {
+                    let name = if self.simple_format {
+                        def.qualified_name()
+                    } else {
+                        def.qualified_name().bold().to_string()
+                    };
+                    format!("{} ({}:{})", name, def.file.display(), def.line)
+                })
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            if self.simple_format {
+                output.push_str(&format!("{}\n", chain));
+            } else {
+                output.push_str(&format!("{}. {}\n", i + 1, chain));
+            }
+        }
+
+        if paths.truncated && !self.simple_format {
+            output.push_str(&format!(
+                "{}\n",
+                "[search limit reached: some paths may be missing]".red()
+            ));
+        }
+
+        output
+    }
+
+    /// Render callers above the focus function and callees below it
+    fn format_bidirectional_tree(&self, tree: &CallTree) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("{}\n", "=== Callers ===".bold()));
+        if tree.callers.is_empty() {
+            output.push_str(&format!("{}\n", "(No incoming calls found)".dimmed()));
+        } else {
+            // Re-attach the callers to the focus so every chain ends at it
+            let focus = CallNode {
+                def: tree.root.def.clone(),
+                children: tree.callers.clone(),
+                truncated: false,
+            };
+            output.push_str(&Self::format_backward_chains(&focus));
+        }
+
+        output.push('\n');
+        output.push_str(&format!("{}\n", "=== Callees ===".bold()));
+        Self::format_call_node(&tree.root, &mut output, "", true, true);
+        if tree.root.children.is_empty() {
+            output.push_str(&format!("{}\n", "(No outgoing calls found)".dimmed()));
         }
+
+        output
     }
 
     fn format_forward_tree(&self, tree: &CallTree) -> String {
@@ -312,6 +413,21 @@ impl TreeFormatter {
     }
 
     fn format_backward_tree(&self, tree: &CallTree) -> String {
+        let mut output = Self::format_backward_chains(&tree.root);
+
+        if output.is_empty() {
+            // If no callers found, just print the root
+            output.push_str(&format!(
+                "{} (No incoming calls found)\n",
+                tree.root.def.qualified_name()
+            ));
+        }
+
+        output
+    }
+
+    /// Render every caller path ending at `root` as a `a -> b -> root` chain
+    fn format_backward_chains(root: &CallNode) -> String {
         let mut output = String::new();
         // For backward trace, we want to show chains like: caller -> callee -> target
         // But the tree structure is target <- callee <- caller
@@ -324,7 +440,7 @@ impl TreeFormatter {
         // a path from a leaf to root represents a call chain: leaf calls ... calls root.
 
         let mut paths = Vec::new();
-        Self::collect_backward_paths(&tree.root, vec![], &mut paths);
+        Self::collect_backward_paths(root, vec![], &mut paths);
 
         for path in paths {
             // path is [leaf, ..., root]
@@ -346,7 +462,7 @@ impl TreeFormatter {
                 .map(|node| {
                     format!(
                         "{} ({}:{})",
-                        node.def.name.bold(),
+                        node.def.qualified_name().bold(),
                         node.def.file.display(),
                         node.def.line
                     )
@@ -365,14 +481,6 @@ impl TreeFormatter {
             output.push('\n');
         }
 
-        if output.is_empty() {
-            // If no callers found, just print the root
-            output.push_str(&format!(
-                "{} (No incoming calls found)\n",
-                tree.root.def.name
-            ));
-        }
-
         output
     }
 
@@ -414,7 +522,7 @@ impl TreeFormatter {
 
         let content = format!(
             "{} ({}:{})",
-            node.def.name.bold(),
+            node.def.qualified_name().bold(),
             node.def.file.display(),
             node.def.line
         );
@@ -505,7 +613,15 @@ impl TreeFormatter {
                     truncated_value
                 };
 
-                format!("{}: '{}'", key.yellow().bold(), highlighted_value)
+                match &node.branch {
+                    Some(branch) => format!(
+                        "{}: '{}' {}",
+                        key.yellow().bold(),
+                        highlighted_value,
+                        format!("[{}]", branch).dimmed()
+                    ),
+                    None => format!("{}: '{}'", key.yellow().bold(), highlighted_value),
+                }
             }
             NodeType::KeyPath => {
                 format!("Key: {}", node.content)
@@ -522,10 +638,14 @@ impl TreeFormatter {
                 let truncated = self.truncate(node.content.trim(), width);
 
                 // Highlight if metadata is present
-                if let Some(key) = &node.metadata {
+                let highlighted = if let Some(key) = &node.metadata {
                     self.highlight_key_in_context(&truncated, key)
                 } else {
                     truncated
+                };
+                match node.confidence {
+                    Some(confidence) => format!("{} {}", highlighted, possible_marker(confidence)),
+                    None => highlighted,
                 }
             }
         }
@@ -542,6 +662,17 @@ impl TreeFormatter {
     }
 }
 
+/// Dimmed note after a possible usage, e.g. `[possible, low confidence]`
+fn possible_marker(confidence: Confidence) -> String {
+    let level = match confidence {
+        Confidence::Exact | Confidence::High => "high",
+        Confidence::Low => "low",
+    };
+    format!("[possible, {} confidence]", level)
+        .dimmed()
+        .to_string()
+}
+
 impl Default for TreeFormatter {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,582 @@
+//! Graph exports of call traces (Graphviz DOT, Mermaid, GraphML and JSON).
+//!
+//! A [`CallTree`] repeats a function every time it is reached through another
+//! path and stops at cycles. The exports flatten it into a [`CallGraph`] with
+//! one node per function (by file, line and name) and one edge per distinct call, and draw the
+//! call that closes a cycle back to the node it returns to.
+
+use crate::output::JsonFormatter;
+use crate::trace::{CallNode, CallTree, FunctionDef, TraceDirection};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Output format of a graph export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    GraphMl,
+    Json,
+}
+
+impl GraphFormat {
+    /// Format names accepted by [`GraphFormat::from_name`]
+    pub const NAMES: &'static [&'static str] = &["dot", "mermaid", "graphml", "json"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dot" | "graphviz" => Some(Self::Dot),
+            "mermaid" => Some(Self::Mermaid),
+            "graphml" => Some(Self::GraphMl),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// How nodes are grouped in a graph export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clustering {
+    /// One cluster per source file
+    File,
+    /// One cluster per directory
+    Directory,
+}
+
+impl Clustering {
+    /// Clustering names accepted by [`Clustering::from_name`]
+    pub const NAMES: &'static [&'static str] = &["file", "dir"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "file" => Some(Self::File),
+            "dir" | "directory" => Some(Self::Directory),
+            _ => None,
+        }
+    }
+
+    fn key(&self, file: &Path) -> PathBuf {
+        match self {
+            Self::File => file.to_path_buf(),
+            Self::Directory => file
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map_or_else(|| PathBuf::from("."), Path::to_path_buf),
+        }
+    }
+}
+
+/// A function in a [`CallGraph`]
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    /// Stable identifier (`n0`, `n1`, ...) in order of first appearance
+    pub id: String,
+    pub def: FunctionDef,
+    /// Whether the trace stopped at this function because of the depth limit
+    pub truncated: bool,
+    /// File or directory the node is grouped under, when clustering
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+}
+
+/// A call from `source` to `target`, by node id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// Whether this call leads back to a function already on the call path
+    pub cycle: bool,
+}
+
+/// Deduplicated call graph of a trace
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CallGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl CallGraph {
+    /// Flatten `tree`. Edges always point from caller to callee, whichever
+    /// way the tree was traced.
+    pub fn from_tree(tree: &CallTree, direction: &TraceDirection) -> Self {
+        Self::from_trees(std::slice::from_ref(tree), direction)
+    }
+
+    /// Flatten several traces (e.g. of every candidate of an ambiguous name)
+    /// into one graph sharing the functions they have in common
+    pub fn from_trees(trees: &[CallTree], direction: &TraceDirection) -> Self {
+        let mut builder = GraphBuilder::default();
+
+        for tree in trees {
+            let mut path = Vec::new();
+            let callers_of_root = *direction == TraceDirection::Backward;
+            let root = builder.add_node(&tree.root, callers_of_root, &mut path);
+
+            // Bidirectional traces keep callers apart from the callee tree
+            path.push(node_key(&tree.root.def));
+            for caller in &tree.callers {
+                let caller = builder.add_node(caller, true, &mut path);
+                builder.add_edge(caller, root, false);
+            }
+        }
+
+        builder.graph
+    }
+
+    /// Group every node under its file or directory
+    pub fn assign_clusters(&mut self, clustering: Clustering) {
+        for node in &mut self.nodes {
+            node.cluster = Some(clustering.key(&node.def.file).display().to_string());
+        }
+    }
+
+    /// Nodes grouped by cluster label, in order of first appearance within
+    /// each cluster
+    fn clusters(&self) -> BTreeMap<&str, Vec<&GraphNode>> {
+        let mut clusters: BTreeMap<&str, Vec<&GraphNode>> = BTreeMap::new();
+        for node in &self.nodes {
+            if let Some(cluster) = &node.cluster {
+                clusters.entry(cluster).or_default().push(node);
+            }
+        }
+        clusters
+    }
+}
+
+/// Identifies a function in a graph without hashing its body
+type NodeKey<'a> = (&'a Path, usize, &'a str);
+
+fn node_key(def: &FunctionDef) -> NodeKey<'_> {
+    (&def.file, def.line, &def.name)
+}
+
+/// Deduplicates nodes and edges while a [`CallGraph`] is built
+#[derive(Default)]
+struct GraphBuilder<'a> {
+    graph: CallGraph,
+    /// Index into `graph.nodes` of every function seen so far
+    nodes: HashMap<NodeKey<'a>, usize>,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    /// Add `node` and its subtree, returning the node's index. With
+    /// `callers`, children call their parent instead of the other way round.
+    fn add_node(
+        &mut self,
+        node: &'a CallNode,
+        callers: bool,
+        path: &mut Vec<NodeKey<'a>>,
+    ) -> usize {
+        let index = self.node_index(&node.def);
+        if node.truncated {
+            self.graph.nodes[index].truncated = true;
+        }
+
+        path.push(node_key(&node.def));
+        for child in &node.children {
+            let cycle = path.contains(&node_key(&child.def));
+            let child_index = self.node_index(&child.def);
+            if callers {
+                self.add_edge(child_index, index, cycle);
+            } else {
+                self.add_edge(index, child_index, cycle);
+            }
+            self.add_node(child, callers, path);
+        }
+        path.pop();
+
+        index
+    }
+
+    /// The index of `def`, adding a node for it on first sight
+    fn node_index(&mut self, def: &'a FunctionDef) -> usize {
+        let nodes = &mut self.graph.nodes;
+        *self.nodes.entry(node_key(def)).or_insert_with(|| {
+            nodes.push(GraphNode {
+                id: format!("n{}", nodes.len()),
+                def: def.clone(),
+                truncated: false,
+                cluster: None,
+            });
+            nodes.len() - 1
+        })
+    }
+
+    fn add_edge(&mut self, source: usize, target: usize, cycle: bool) {
+        if self.edges.insert((source, target)) {
+            let nodes = &self.graph.nodes;
+            self.graph.edges.push(GraphEdge {
+                source: nodes[source].id.clone(),
+                target: nodes[target].id.clone(),
+                cycle,
+            });
+        }
+    }
+}
+
+/// Formatter for exporting call traces as graphs
+pub struct GraphFormatter {
+    format: GraphFormat,
+    clustering: Option<Clustering>,
+}
+
+impl GraphFormatter {
+    pub fn new(format: GraphFormat) -> Self {
+        Self {
+            format,
+            clustering: None,
+        }
+    }
+
+    /// Group nodes by file or directory
+    pub fn with_clustering(mut self, clustering: Option<Clustering>) -> Self {
+        self.clustering = clustering;
+        self
+    }
+
+    /// Export the trace of `function` in the configured format
+    pub fn format_trace(
+        &self,
+        function: &str,
+        tree: &CallTree,
+        direction: &TraceDirection,
+    ) -> String {
+        self.format_traces(function, std::slice::from_ref(tree), direction)
+    }
+
+    /// Export several traces of `function` as a single graph
+    pub fn format_traces(
+        &self,
+        function: &str,
+        trees: &[CallTree],
+        direction: &TraceDirection,
+    ) -> String {
+        let mut graph = CallGraph::from_trees(trees, direction);
+        if let Some(clustering) = self.clustering {
+            graph.assign_clusters(clustering);
+        }
+        match self.format {
+            GraphFormat::Dot => Self::format_dot(&graph),
+            GraphFormat::Mermaid => Self::format_mermaid(&graph),
+            GraphFormat::GraphMl => Self::format_graphml(&graph),
+            GraphFormat::Json => {
+                JsonFormatter::new().format_trace_graph(function, direction, &graph)
+            }
+        }
+    }
+
+    fn format_dot(graph: &CallGraph) -> String {
+        let mut output = String::from("digraph calls {\n");
+        output.push_str("  rankdir=LR;\n");
+        output.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+
+        let node_line = |node: &GraphNode, indent: &str| {
+            let style = if node.truncated { ", style=dashed" } else { "" };
+            format!(
+                "{}{} [label=\"{}\"{}];\n",
+                indent,
+                node.id,
+                escape_dot(&node_label(node, "\n")),
+                style
+            )
+        };
+
+        let clusters = graph.clusters();
+        if clusters.is_empty() {
+            for node in &graph.nodes {
+                output.push_str(&node_line(node, "  "));
+            }
+        }
+        for (i, (label, nodes)) in clusters.iter().enumerate() {
+            output.push_str(&format!("  subgraph cluster_{} {{\n", i));
+            output.push_str(&format!("    label=\"{}\";\n", escape_dot(label)));
+            for node in nodes {
+                output.push_str(&node_line(node, "    "));
+            }
+            output.push_str("  }\n");
+        }
+
+        for edge in &graph.edges {
+            let style = if edge.cycle {
+                " [style=dashed, label=\"cycle\"]"
+            } else {
+                ""
+            };
+            output.push_str(&format!("  {} -> {}{};\n", edge.source, edge.target, style));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_mermaid(graph: &CallGraph) -> String {
+        let mut output = String::from("flowchart LR\n");
+
+        let node_line = |node: &GraphNode, indent: &str| {
+            let label = escape_mermaid(&node_label(node, "<br/>"));
+            if node.truncated {
+                // Stadium shape marks functions whose calls were not followed
+                format!("{}{}([\"{}\"])\n", indent, node.id, label)
+            } else {
+                format!("{}{}[\"{}\"]\n", indent, node.id, label)
+            }
+        };
+
+        let clusters = graph.clusters();
+        if clusters.is_empty() {
+            for node in &graph.nodes {
+                output.push_str(&node_line(node, "  "));
+            }
+        }
+        for (i, (label, nodes)) in clusters.iter().enumerate() {
+            output.push_str(&format!(
+                "  subgraph c{}[\"{}\"]\n",
+                i,
+                escape_mermaid(label)
+            ));
+            for node in nodes {
+                output.push_str(&node_line(node, "    "));
+            }
+            output.push_str("  end\n");
+        }
+
+        for edge in &graph.edges {
+            let arrow = if edge.cycle { "-.->|cycle|" } else { "-->" };
+            output.push_str(&format!("  {} {} {}\n", edge.source, arrow, edge.target));
+        }
+
+        output
+    }
+
+    fn format_graphml(graph: &CallGraph) -> String {
+        let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        output.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, target, kind) in [
+            ("name", "node", "string"),
+            ("file", "node", "string"),
+            ("line", "node", "int"),
+            ("truncated", "node", "boolean"),
+            ("cluster", "node", "string"),
+            ("cycle", "edge", "boolean"),
+        ] {
+            output.push_str(&format!(
+                "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>\n",
+                id, target, kind
+            ));
+        }
+        output.push_str("  <graph id=\"calls\" edgedefault=\"directed\">\n");
+
+        for node in &graph.nodes {
+            output.push_str(&format!("    <node id=\"{}\">\n", node.id));
+            let mut data = vec![
+                ("name", node.def.qualified_name()),
+                ("file", node.def.file.display().to_string()),
+                ("line", node.def.line.to_string()),
+                ("truncated", node.truncated.to_string()),
+            ];
+            if let Some(cluster) = &node.cluster {
+                data.push(("cluster", cluster.clone()));
+            }
+            for (key, value) in data {
+                output.push_str(&format!(
+                    "      <data key=\"{}\">{}</data>\n",
+                    key,
+                    escape_xml(&value)
+                ));
+            }
+            output.push_str("    </node>\n");
+        }
+
+        for (i, edge) in graph.edges.iter().enumerate() {
+            output.push_str(&format!(
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n",
+                i, edge.source, edge.target
+            ));
+            output.push_str(&format!(
+                "      <data key=\"cycle\">{}</data>\n",
+                edge.cycle
+            ));
+            output.push_str("    </edge>\n");
+        }
+
+        output.push_str("  </graph>\n");
+        output.push_str("</graphml>\n");
+        output
+    }
+}
+
+/// `name` and `file:line` on separate lines, with a depth limit marker
+fn node_label(node: &GraphNode, line_break: &str) -> String {
+    let mut label = format!(
+        "{}{}{}:{}",
+        node.def.qualified_name(),
+        line_break,
+        node.def.file.display(),
+        node.def.line
+    );
+    if node.truncated {
+        label.push_str(line_break);
+        label.push_str("[depth limit reached]");
+    }
+    label
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(name: &str, file: &str, line: usize) -> FunctionDef {
+        FunctionDef {
+            name: name.to_string(),
+            scope: vec![],
+            file: PathBuf::from(file),
+            line,
+            body: String::new(),
+        }
+    }
+
+    fn node(def: FunctionDef, children: Vec<CallNode>) -> CallNode {
+        CallNode {
+            def,
+            children,
+            truncated: false,
+        }
+    }
+
+    /// main -> {helper -> log, log}, and helper -> main closing a cycle
+    fn sample_tree() -> CallTree {
+        let main = def("main", "src/app.js", 1);
+        let helper = def("helper", "src/util.js", 3);
+        let log = def("log", "lib/log.js", 7);
+        CallTree {
+            root: node(
+                main.clone(),
+                vec![
+                    node(helper, vec![node(log.clone(), vec![]), node(main, vec![])]),
+                    node(log, vec![]),
+                ],
+            ),
+            callers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_graph_deduplicates_nodes_and_keeps_cycles() {
+        let graph = CallGraph::from_tree(&sample_tree(), &TraceDirection::Forward);
+
+        let names: Vec<&str> = graph.nodes.iter().map(|n| n.def.name.as_str()).collect();
+        assert_eq!(names, vec!["main", "helper", "log"]);
+        assert_eq!(
+            graph.edges,
+            vec![
+                GraphEdge {
+                    source: "n0".into(),
+                    target: "n1".into(),
+                    cycle: false
+                },
+                GraphEdge {
+                    source: "n1".into(),
+                    target: "n2".into(),
+                    cycle: false
+                },
+                GraphEdge {
+                    source: "n1".into(),
+                    target: "n0".into(),
+                    cycle: true
+                },
+                GraphEdge {
+                    source: "n0".into(),
+                    target: "n2".into(),
+                    cycle: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nodes_are_keyed_on_location_not_body() {
+        // The same function read at two points of a trace may carry different
+        // bodies, e.g. when one side came from the symbol index
+        let mut edited = def("log", "lib/log.js", 7);
+        edited.body = "function log() {}".to_string();
+        let tree = CallTree {
+            root: node(
+                def("main", "src/app.js", 1),
+                vec![
+                    node(def("log", "lib/log.js", 7), vec![]),
+                    node(edited, vec![]),
+                ],
+            ),
+            callers: vec![],
+        };
+        let graph = CallGraph::from_tree(&tree, &TraceDirection::Forward);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_backward_edges_point_from_caller() {
+        let tree = CallTree {
+            root: node(
+                def("target", "a.js", 1),
+                vec![node(def("caller", "b.js", 2), vec![])],
+            ),
+            callers: vec![],
+        };
+        let graph = CallGraph::from_tree(&tree, &TraceDirection::Backward);
+        assert_eq!(graph.edges[0].source, "n1");
+        assert_eq!(graph.edges[0].target, "n0");
+    }
+
+    #[test]
+    fn test_dot_and_mermaid_output() {
+        let tree = sample_tree();
+        let dot = GraphFormatter::new(GraphFormat::Dot).format_trace(
+            "main",
+            &tree,
+            &TraceDirection::Forward,
+        );
+        assert!(dot.starts_with("digraph calls {"));
+        assert!(dot.contains("n0 [label=\"main\\nsrc/app.js:1\"];"));
+        assert!(dot.contains("n1 -> n0 [style=dashed, label=\"cycle\"];"));
+
+        let mermaid = GraphFormatter::new(GraphFormat::Mermaid)
+            .with_clustering(Some(Clustering::Directory))
+            .format_trace("main", &tree, &TraceDirection::Forward);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("subgraph c1[\"src\"]"));
+        assert!(mermaid.contains("n1 -.->|cycle| n0"));
+    }
+
+    #[test]
+    fn test_graphml_output_is_escaped() {
+        let tree = CallTree {
+            root: node(def("compare<T>", "a&b.rs", 1), vec![]),
+            callers: vec![],
+        };
+        let graphml = GraphFormatter::new(GraphFormat::GraphMl)
+            .with_clustering(Some(Clustering::File))
+            .format_trace("compare", &tree, &TraceDirection::Forward);
+        assert!(graphml.contains("<data key=\"name\">compare&lt;T&gt;</data>"));
+        assert!(graphml.contains("<data key=\"cluster\">a&amp;b.rs</data>"));
+    }
+}
@@ -0,0 +1,445 @@
+use crate::audit::{LocaleGroup, MissingKeysReport, UndefinedKey, UnusedKey, UnusedKeysReport};
+use crate::output::graph::{CallGraph, GraphEdge, GraphNode};
+use crate::parse::TranslationEntry;
+use crate::search::{CodeReference, FileMatch};
+use crate::trace::{CallNode, CallPaths, CallTree, FunctionDef, TraceDirection};
+use crate::{SearchError, SearchResult};
+use serde::Serialize;
+
+/// Version of the JSON output schema.
+///
+/// Bump this whenever a field is renamed or removed, or its meaning changes.
+/// Adding new fields is backwards compatible and does not require a bump.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Top-level document emitted by `cs --json` in search mode
+#[derive(Debug, Serialize)]
+struct SearchDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    query: &'a str,
+    translation_entries: &'a [TranslationEntry],
+    code_references: &'a [CodeReference],
+    file_matches: &'a [FileMatch],
+}
+
+/// Top-level document emitted by `cs --json` in trace mode
+#[derive(Debug, Serialize)]
+struct TraceDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    function: &'a str,
+    direction: &'a TraceDirection,
+    root: Option<&'a CallNode>,
+    /// Caller subtrees of the root; only present for `direction: "both"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callers: Option<&'a [CallNode]>,
+}
+
+/// One traced definition inside a [`TracesDocument`]
+#[derive(Debug, Serialize)]
+struct TracedDefinition<'a> {
+    root: &'a CallNode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callers: Option<&'a [CallNode]>,
+}
+
+/// Top-level document emitted by `cs --json --all-candidates` when several
+/// functions match the traced name
+#[derive(Debug, Serialize)]
+struct TracesDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    function: &'a str,
+    direction: &'a TraceDirection,
+    traces: Vec<TracedDefinition<'a>>,
+}
+
+/// Top-level document emitted by `cs --json` when the traced name is ambiguous
+#[derive(Debug, Serialize)]
+struct TraceCandidatesDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    function: &'a str,
+    candidates: &'a [FunctionDef],
+}
+
+/// Top-level document emitted by `cs --trace --format json`
+#[derive(Debug, Serialize)]
+struct TraceGraphDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    function: &'a str,
+    direction: &'a TraceDirection,
+    nodes: &'a [GraphNode],
+    edges: &'a [GraphEdge],
+}
+
+/// Top-level document emitted by `cs --path-between --json`
+#[derive(Debug, Serialize)]
+struct CallPathsDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    from: &'a str,
+    to: &'a str,
+    /// Each path starts at `from` and ends at a definition of `to`; `null`
+    /// when either function is not found
+    paths: Option<Vec<Vec<&'a FunctionDef>>>,
+    truncated: bool,
+}
+
+/// Top-level document emitted by `cs unused-keys --json`
+#[derive(Debug, Serialize)]
+struct UnusedKeysDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    keys_checked: usize,
+    unused_keys: &'a [UnusedKey],
+}
+
+/// Top-level document emitted by `cs missing-keys --json`
+#[derive(Debug, Serialize)]
+struct MissingKeysDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    groups: &'a [LocaleGroup],
+    undefined_keys: &'a [UndefinedKey],
+}
+
+/// Top-level document emitted by `cs --json` when a search or trace fails
+#[derive(Debug, Serialize)]
+struct ErrorDocument {
+    schema_version: u32,
+    kind: &'static str,
+    /// Stable name of the failure, see [`error_code`]
+    error: &'static str,
+    message: String,
+}
+
+/// Formatter for rendering search and trace results as JSON.
+///
+/// Every document carries a `schema_version` and a `kind` (`"search"`,
+/// `"trace"`, `"traces"`, `"trace-candidates"`, `"trace-graph"`, `"call-paths"`, `"unused-keys"`, `"missing-keys"` or `"error"`) so that scripts and editor integrations can detect the shape
+/// before reading the rest of the payload.
+pub struct JsonFormatter {
+    pretty: bool,
+}
+
+impl JsonFormatter {
+    /// Create a new JsonFormatter that emits pretty-printed JSON
+    pub fn new() -> Self {
+        Self { pretty: true }
+    }
+
+    /// Emit compact single-line JSON instead of pretty-printed output
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.pretty = !compact;
+        self
+    }
+
+    /// Format a search result (and any file name matches) as a JSON document
+    pub fn format_search(&self, result: &SearchResult, file_matches: &[FileMatch]) -> String {
+        self.to_string(&SearchDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "search",
+            query: &result.query,
+            translation_entries: &result.translation_entries,
+            code_references: &result.code_references,
+            file_matches,
+        })
+    }
+
+    /// Format a call trace as a JSON document.
+    ///
+    /// `tree` is `None` when the function could not be found; the document is
+    /// still emitted with a `null` root so consumers always get valid JSON.
+    pub fn format_trace(
+        &self,
+        function: &str,
+        tree: Option<&CallTree>,
+        direction: &TraceDirection,
+    ) -> String {
+        self.to_string(&TraceDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "trace",
+            function,
+            direction,
+            root: tree.map(|t| &t.root),
+            callers: match direction {
+                TraceDirection::Both => Some(tree.map_or(&[][..], |t| &t.callers)),
+                _ => None,
+            },
+        })
+    }
+
+    /// Format the traces of every definition matching an ambiguous name
+    pub fn format_traces(
+        &self,
+        function: &str,
+        trees: &[CallTree],
+        direction: &TraceDirection,
+    ) -> String {
+        self.to_string(&TracesDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "traces",
+            function,
+            direction,
+            traces: trees
+                .iter()
+                .map(|tree| TracedDefinition {
+                    root: &tree.root,
+                    callers: (*direction == TraceDirection::Both).then_some(&tree.callers[..]),
+                })
+                .collect(),
+        })
+    }
+
+    /// Format the definitions an ambiguous traced name could refer to
+    pub fn format_trace_candidates(&self, function: &str, candidates: &[FunctionDef]) -> String {
+        self.to_string(&TraceCandidatesDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "trace-candidates",
+            function,
+            candidates,
+        })
+    }
+
+    /// Format a call trace flattened into a graph (see [`CallGraph`])
+    pub fn format_trace_graph(
+        &self,
+        function: &str,
+        direction: &TraceDirection,
+        graph: &CallGraph,
+    ) -> String {
+        self.to_string(&TraceGraphDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "trace-graph",
+            function,
+            direction,
+            nodes: &graph.nodes,
+            edges: &graph.edges,
+        })
+    }
+
+    /// Format the call paths between two functions
+    pub fn format_call_paths(&self, from: &str, to: &str, paths: Option<&CallPaths>) -> String {
+        self.to_string(&CallPathsDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "call-paths",
+            from,
+            to,
+            paths: paths.map(|p| p.chains().collect()),
+            truncated: paths.is_some_and(|p| p.truncated),
+        })
+    }
+
+    /// Format an unused-keys audit as a JSON document
+    pub fn format_unused_keys(&self, report: &UnusedKeysReport) -> String {
+        self.to_string(&UnusedKeysDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "unused-keys",
+            keys_checked: report.keys_checked,
+            unused_keys: &report.unused_keys,
+        })
+    }
+
+    /// Format a missing-keys audit as a JSON document
+    pub fn format_missing_keys(&self, report: &MissingKeysReport) -> String {
+        self.to_string(&MissingKeysDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "missing-keys",
+            groups: &report.groups,
+            undefined_keys: &report.undefined_keys,
+        })
+    }
+
+    /// Format a failed search or trace, so that `--json` output is a
+    /// document even when there are no results to report
+    pub fn format_error(&self, error: &SearchError) -> String {
+        self.to_string(&ErrorDocument {
+            schema_version: JSON_SCHEMA_VERSION,
+            kind: "error",
+            error: error_code(error),
+            message: error.to_string(),
+        })
+    }
+
+    fn to_string<T: Serialize>(&self, value: &T) -> String {
+        let rendered = if self.pretty {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        };
+        // The documents only contain strings, numbers and paths, so
+        // serialization can only fail on non-UTF-8 paths.
+        rendered.unwrap_or_else(|e| {
+            format!(
+                "{{\"schema_version\":{},\"kind\":\"error\",\"error\":\"other\",\"message\":{:?}}}",
+                JSON_SCHEMA_VERSION,
+                e.to_string()
+            )
+        })
+    }
+}
+
+/// The `error` field of an error document
+fn error_code(error: &SearchError) -> &'static str {
+    match error {
+        SearchError::NoTranslationFiles { .. } => "no-translation-files",
+        SearchError::YamlParseError { .. }
+        | SearchError::JsonParseError { .. }
+        | SearchError::PoParseError { .. }
+        | SearchError::FluentParseError { .. }
+        | SearchError::AndroidStringsParseError { .. }
+        | SearchError::AppleStringsParseError { .. } => "parse-error",
+        SearchError::NoCodeReferences { .. } => "no-code-references",
+        SearchError::Io(_) => "io",
+        SearchError::ConfigError { .. } => "config",
+        SearchError::AmbiguousFunction { .. } => "ambiguous-function",
+        SearchError::ProtocolMismatch { .. } => "protocol-mismatch",
+        SearchError::Cancelled => "cancelled",
+        _ => "other",
+    }
+}
+
+impl Default for JsonFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::Confidence;
+    use crate::tree::Location;
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    fn sample_result() -> SearchResult {
+        SearchResult {
+            query: "Add New".to_string(),
+            translation_entries: vec![TranslationEntry {
+                key: "invoice.labels.add_new".to_string(),
+                value: "Add New".to_string(),
+                line: 4,
+                file: PathBuf::from("config/locales/en.yml"),
+                branch: None,
+                locale: None,
+            }],
+            code_references: vec![CodeReference {
+                file: PathBuf::from("app/views/invoices/index.html.erb"),
+                line: 12,
+                pattern: r#"I18n\.t\(['"]([^'"]+)['"]\)"#.to_string(),
+                context: "<%= I18n.t('invoice.labels.add_new') %>".to_string(),
+                key_path: "invoice.labels.add_new".to_string(),
+                context_before: vec![],
+                context_after: vec![],
+                confidence: Confidence::Exact,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_search_document_shape() {
+        let result = sample_result();
+        let files = vec![FileMatch {
+            path: PathBuf::from("add_new.rb"),
+        }];
+        let output = JsonFormatter::new().format_search(&result, &files);
+        let json: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(json["kind"], "search");
+        assert_eq!(json["query"], "Add New");
+        assert_eq!(
+            json["translation_entries"][0]["key"],
+            "invoice.labels.add_new"
+        );
+        assert_eq!(json["translation_entries"][0]["line"], 4);
+        assert_eq!(json["code_references"][0]["line"], 12);
+        assert_eq!(
+            json["code_references"][0]["file"],
+            "app/views/invoices/index.html.erb"
+        );
+        assert_eq!(json["file_matches"][0]["path"], "add_new.rb");
+    }
+
+    #[test]
+    fn test_error_document_shape() {
+        let error = SearchError::Io(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "permission denied",
+        ));
+        let output = JsonFormatter::new().format_error(&error);
+        let json: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(json["kind"], "error");
+        assert_eq!(json["error"], "io");
+        assert_eq!(json["message"], "IO error: permission denied");
+    }
+
+    #[test]
+    fn test_trace_document_shape() {
+        let tree = CallTree {
+            root: CallNode {
+                def: FunctionDef {
+                    name: "main".to_string(),
+                    scope: vec![],
+                    file: PathBuf::from("src/main.rs"),
+                    line: 1,
+                    body: "fn main() { helper(); }".to_string(),
+                },
+                children: vec![],
+                truncated: false,
+            },
+            callers: vec![],
+        };
+        let output =
+            JsonFormatter::new().format_trace("main", Some(&tree), &TraceDirection::Forward);
+        let json: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["kind"], "trace");
+        assert_eq!(json["direction"], "forward");
+        assert_eq!(json["root"]["def"]["name"], "main");
+        assert_eq!(json["root"]["truncated"], false);
+        // Function bodies are deliberately left out of the schema
+        assert!(json["root"]["def"].get("body").is_none());
+    }
+
+    #[test]
+    fn test_trace_document_not_found() {
+        let output = JsonFormatter::new().with_compact(true).format_trace(
+            "missing",
+            None,
+            &TraceDirection::Backward,
+        );
+        assert!(!output.contains('\n'));
+        let json: Value = serde_json::from_str(&output).unwrap();
+        assert!(json["root"].is_null());
+        assert_eq!(json["direction"], "backward");
+    }
+
+    #[test]
+    fn test_unused_keys_document_shape() {
+        let report = UnusedKeysReport {
+            keys_checked: 2,
+            unused_keys: vec![UnusedKey {
+                key: "legacy.banner".to_string(),
+                locations: vec![Location::new(PathBuf::from("locales/en.json"), 7)],
+            }],
+        };
+        let output = JsonFormatter::new().format_unused_keys(&report);
+        let json: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["kind"], "unused-keys");
+        assert_eq!(json["keys_checked"], 2);
+        assert_eq!(json["unused_keys"][0]["key"], "legacy.banner");
+        assert_eq!(
+            json["unused_keys"][0]["locations"][0]["file"],
+            "locales/en.json"
+        );
+        assert_eq!(json["unused_keys"][0]["locations"][0]["line"], 7);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod formatter;
+pub mod graph;
+pub mod json;
 
 pub use formatter::TreeFormatter;
+pub use graph::{CallGraph, Clustering, GraphFormat, GraphFormatter};
+pub use json::{JsonFormatter, JSON_SCHEMA_VERSION};
@@ -0,0 +1,206 @@
+use crate::error::{Result, SearchError};
+use roxmltree::{Document, Node};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::translation::TranslationEntry;
+
+/// Parser for Android string resources (`res/values*/strings.xml`).
+///
+/// The key of each entry is the resource name, as referenced from code with
+/// `R.string.add_new` or from layouts with `@string/add_new`:
+///
+/// - `<string name="add_new">Add New</string>` yields one entry
+/// - `<plurals name="files">` yields one entry per `<item quantity="...">`,
+///   with the quantity in `branch`
+/// - `<string-array name="days">` yields one entry per `<item>`
+pub struct AndroidStringsParser;
+
+impl AndroidStringsParser {
+    /// Whether `path` is an XML file in a `values` resource directory
+    /// (`values`, `values-fr`, `values-pt-rBR`, ...)
+    pub fn is_resource_file(path: &Path) -> bool {
+        let is_xml = path.extension().is_some_and(|ext| ext == "xml");
+        let in_values_dir = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| name == "values" || name.starts_with("values-"));
+        is_xml && in_values_dir
+    }
+
+    pub fn parse_file(path: &Path) -> Result<Vec<TranslationEntry>> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            SearchError::android_strings_parse_error(path, format!("Failed to read file: {}", e))
+        })?;
+        Self::parse_content(&content, path)
+    }
+
+    /// Parse resource XML; `path` is recorded in the entries and errors
+    pub fn parse_content(content: &str, path: &Path) -> Result<Vec<TranslationEntry>> {
+        let doc = Document::parse(content)
+            .map_err(|e| SearchError::android_strings_parse_error(path, e.to_string()))?;
+
+        let root = doc.root_element();
+        if !root.has_tag_name("resources") {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        let mut push = |name: &str, node: Node, branch: Option<String>| {
+            entries.push(TranslationEntry {
+                key: name.to_string(),
+                value: Self::text_of(node),
+                line: doc.text_pos_at(node.range().start).row as usize,
+                file: PathBuf::from(path),
+                branch,
+                locale: None,
+            });
+        };
+
+        for element in root.children().filter(Node::is_element) {
+            let Some(name) = element.attribute("name") else {
+                continue;
+            };
+            match element.tag_name().name() {
+                "string" => push(name, element, None),
+                "plurals" | "string-array" => {
+                    for item in element.children().filter(|n| n.has_tag_name("item")) {
+                        let quantity = item.attribute("quantity");
+                        push(name, item, quantity.map(|q| format!("quantity:{}", q)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// The displayed text of a resource value: markup such as `<b>` is
+    /// dropped, surrounding quotes and backslash escapes are resolved and,
+    /// outside quotes, whitespace is collapsed as Android does.
+    fn text_of(node: Node) -> String {
+        let raw: String = node
+            .descendants()
+            .filter(Node::is_text)
+            .filter_map(|n| n.text())
+            .collect();
+        let trimmed = raw.trim();
+
+        let text = match trimmed.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            Some(quoted) => quoted.to_string(),
+            None => trimmed.split_whitespace().collect::<Vec<_>>().join(" "),
+        };
+
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                result.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        Some(decoded) => result.push(decoded),
+                        None => {
+                            result.push_str("\\u");
+                            result.push_str(&hex);
+                        }
+                    }
+                }
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Vec<TranslationEntry> {
+        AndroidStringsParser::parse_content(content, Path::new("res/values/strings.xml")).unwrap()
+    }
+
+    #[test]
+    fn test_strings_plurals_and_arrays() {
+        let entries = parse(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<resources>
+    <string name="add_new">Add New</string>
+    <plurals name="files">
+        <item quantity="one">%d file</item>
+        <item quantity="other">%d files</item>
+    </plurals>
+    <string-array name="sizes">
+        <item>Small</item>
+    </string-array>
+    <color name="accent">#FF0000</color>
+</resources>
+"#,
+        );
+
+        let pairs: Vec<_> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.line))
+            .collect();
+        assert_eq!(entries[2].branch.as_deref(), Some("quantity:other"));
+        assert_eq!(
+            pairs,
+            vec![
+                ("add_new", "Add New", 3),
+                ("files", "%d file", 5),
+                ("files", "%d files", 6),
+                ("sizes", "Small", 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escapes_quotes_and_markup() {
+        let entries = parse(
+            r#"<resources>
+    <string name="apostrophe">Don\'t   stop</string>
+    <string name="quoted">"  spaced  "</string>
+    <string name="bold">Hello <b>World</b></string>
+    <string name="unicode">Caf\u00e9</string>
+</resources>"#,
+        );
+
+        let values: Vec<_> = entries.iter().map(|e| e.value.as_str()).collect();
+        assert_eq!(
+            values,
+            vec!["Don't stop", "  spaced  ", "Hello World", "Café"]
+        );
+    }
+
+    #[test]
+    fn test_is_resource_file() {
+        assert!(AndroidStringsParser::is_resource_file(Path::new(
+            "app/src/main/res/values/strings.xml"
+        )));
+        assert!(AndroidStringsParser::is_resource_file(Path::new(
+            "res/values-pt-rBR/strings.xml"
+        )));
+        assert!(!AndroidStringsParser::is_resource_file(Path::new(
+            "res/layout/main.xml"
+        )));
+        assert!(!AndroidStringsParser::is_resource_file(Path::new(
+            "res/values/strings.json"
+        )));
+    }
+
+    #[test]
+    fn test_invalid_xml() {
+        let err = AndroidStringsParser::parse_content("<resources>", Path::new("strings.xml"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Android string resources"));
+    }
+}
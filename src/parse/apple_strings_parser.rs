@@ -0,0 +1,396 @@
+use crate::error::{Result, SearchError};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::translation::TranslationEntry;
+
+/// Parser for Apple localization files.
+///
+/// - `Localizable.strings`: `"add_new" = "Add New";` pairs, in UTF-8 or UTF-16
+/// - `Localizable.xcstrings`: Xcode string catalogs (JSON holding every
+///   locale). Each localization, and each plural/device variation within it,
+///   yields one entry. Strings without a source-language localization use
+///   the key itself, which is what Xcode displays.
+///
+/// The key is what code passes to `NSLocalizedString("add_new", ...)` or
+/// `String(localized: "add_new")`.
+pub struct AppleStringsParser;
+
+impl AppleStringsParser {
+    pub fn parse_file(path: &Path) -> Result<Vec<TranslationEntry>> {
+        let bytes = fs::read(path).map_err(|e| {
+            SearchError::apple_strings_parse_error(path, format!("Failed to read file: {}", e))
+        })?;
+        let content = Self::decode(&bytes)
+            .ok_or_else(|| SearchError::apple_strings_parse_error(path, "Invalid text encoding"))?;
+
+        if path.extension().is_some_and(|ext| ext == "xcstrings") {
+            Self::parse_catalog(&content, path)
+        } else {
+            Self::parse_strings(&content, path)
+        }
+    }
+
+    /// `.strings` files are frequently saved as UTF-16 with a byte order mark
+    fn decode(bytes: &[u8]) -> Option<String> {
+        let utf16 = |be: bool| {
+            let units: Vec<u16> = bytes[2..]
+                .chunks_exact(2)
+                .map(|c| {
+                    if be {
+                        u16::from_be_bytes([c[0], c[1]])
+                    } else {
+                        u16::from_le_bytes([c[0], c[1]])
+                    }
+                })
+                .collect();
+            String::from_utf16(&units).ok()
+        };
+
+        match bytes {
+            [0xFF, 0xFE, ..] => utf16(false),
+            [0xFE, 0xFF, ..] => utf16(true),
+            [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8(rest.to_vec()).ok(),
+            _ => String::from_utf8(bytes.to_vec()).ok(),
+        }
+    }
+
+    /// Parse `.strings` text; `path` is recorded in the entries and errors
+    pub fn parse_strings(content: &str, path: &Path) -> Result<Vec<TranslationEntry>> {
+        let tokens = Self::tokenize(content, path)?;
+        let mut entries = Vec::new();
+
+        let mut i = 0;
+        while i < tokens.len() {
+            let Token::Text(key, line) = &tokens[i] else {
+                return Err(Self::unexpected(&tokens[i], path));
+            };
+
+            let value = match (tokens.get(i + 1), tokens.get(i + 2), tokens.get(i + 3)) {
+                (
+                    Some(Token::Punct('=', _)),
+                    Some(Token::Text(value, _)),
+                    Some(Token::Punct(';', _)),
+                ) => {
+                    i += 4;
+                    value.clone()
+                }
+                // `"key";` is shorthand for a string equal to its key
+                (Some(Token::Punct(';', _)), _, _) => {
+                    i += 2;
+                    key.clone()
+                }
+                _ => {
+                    return Err(SearchError::apple_strings_parse_error(
+                        path,
+                        format!("line {}: expected \"{}\" = \"value\";", line, key),
+                    ))
+                }
+            };
+
+            entries.push(TranslationEntry {
+                key: key.clone(),
+                value,
+                line: *line,
+                file: PathBuf::from(path),
+                branch: None,
+                locale: None,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn unexpected(token: &Token, path: &Path) -> SearchError {
+        let (text, line) = match token {
+            Token::Text(text, line) => (text.clone(), line),
+            Token::Punct(ch, line) => (ch.to_string(), line),
+        };
+        SearchError::apple_strings_parse_error(
+            path,
+            format!("line {}: unexpected '{}'", line, text),
+        )
+    }
+
+    /// Split `.strings` content into quoted/bare strings and `=`/`;`,
+    /// dropping `/* */` and `//` comments
+    fn tokenize(content: &str, path: &Path) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        let mut chars = content.chars().peekable();
+        let mut line = 1;
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\n' => line += 1,
+                c if c.is_whitespace() => {}
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut prev = ' ';
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        if prev == '*' && c == '/' {
+                            break;
+                        }
+                        prev = c;
+                    }
+                }
+                '/' if chars.peek() == Some(&'/') => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            line += 1;
+                            break;
+                        }
+                    }
+                }
+                '=' | ';' => tokens.push(Token::Punct(ch, line)),
+                '"' => {
+                    let start = line;
+                    let mut text = String::new();
+                    let mut closed = false;
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => {
+                                closed = true;
+                                break;
+                            }
+                            '\\' => match chars.next() {
+                                Some('n') => text.push('\n'),
+                                Some('t') => text.push('\t'),
+                                Some('r') => text.push('\r'),
+                                Some('U' | 'u') => {
+                                    let hex: String = chars.by_ref().take(4).collect();
+                                    if let Some(decoded) =
+                                        u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                                    {
+                                        text.push(decoded);
+                                    }
+                                }
+                                Some(other) => text.push(other),
+                                None => {}
+                            },
+                            '\n' => {
+                                line += 1;
+                                text.push(c);
+                            }
+                            _ => text.push(c),
+                        }
+                    }
+                    if !closed {
+                        return Err(SearchError::apple_strings_parse_error(
+                            path,
+                            format!("line {}: unterminated string", start),
+                        ));
+                    }
+                    tokens.push(Token::Text(text, start));
+                }
+                c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                    // Unquoted keys are allowed for identifier-like strings
+                    let mut text = c.to_string();
+                    while let Some(&next) = chars.peek() {
+                        if next.is_alphanumeric() || next == '_' || next == '.' {
+                            text.push(next);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    tokens.push(Token::Text(text, line));
+                }
+                other => {
+                    return Err(SearchError::apple_strings_parse_error(
+                        path,
+                        format!("line {}: unexpected '{}'", line, other),
+                    ))
+                }
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// Parse an Xcode string catalog; `path` is recorded in the entries and errors
+    pub fn parse_catalog(content: &str, path: &Path) -> Result<Vec<TranslationEntry>> {
+        let root: Value = serde_json::from_str(content).map_err(|e| {
+            SearchError::apple_strings_parse_error(path, format!("Invalid JSON syntax: {}", e))
+        })?;
+        let source_language = root
+            .get("sourceLanguage")
+            .and_then(Value::as_str)
+            .unwrap_or("en");
+        let Some(strings) = root.get("strings").and_then(Value::as_object) else {
+            return Ok(Vec::new());
+        };
+
+        let strings_start = content.find("\"strings\"").unwrap_or(0);
+        let mut entries = Vec::new();
+        for (key, string) in strings {
+            let line = Self::key_line(content, strings_start, key);
+            let push = |value: String, locale: &str, entries: &mut Vec<TranslationEntry>| {
+                entries.push(TranslationEntry {
+                    key: key.clone(),
+                    value,
+                    line,
+                    file: PathBuf::from(path),
+                    branch: None,
+                    locale: Some(locale.to_string()),
+                });
+            };
+
+            let localizations = string.get("localizations").and_then(Value::as_object);
+            if !localizations.is_some_and(|l| l.contains_key(source_language)) {
+                push(key.clone(), source_language, &mut entries);
+            }
+            for (locale, localization) in localizations.into_iter().flatten() {
+                let mut values = Vec::new();
+                Self::collect_string_units(localization, &mut values);
+                for value in values {
+                    push(value, locale, &mut entries);
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Values of every `stringUnit` under a localization, including those
+    /// nested in `variations` (plural, device, ...)
+    fn collect_string_units(value: &Value, values: &mut Vec<String>) {
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        for (name, child) in object {
+            if name == "stringUnit" {
+                if let Some(text) = child.get("value").and_then(Value::as_str) {
+                    values.push(text.to_string());
+                }
+            } else {
+                Self::collect_string_units(child, values);
+            }
+        }
+    }
+
+    /// Line of `"key" :` within the catalog's `strings` object
+    fn key_line(content: &str, from: usize, key: &str) -> usize {
+        let needle = serde_json::to_string(key).unwrap_or_default();
+        let mut offset = from;
+        while let Some(pos) = content[offset..].find(&needle) {
+            let end = offset + pos + needle.len();
+            if content[end..].trim_start().starts_with(':') {
+                return content[..end].matches('\n').count() + 1;
+            }
+            offset = end;
+        }
+        0
+    }
+}
+
+/// `.strings` token with the line it starts on
+enum Token {
+    Text(String, usize),
+    Punct(char, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_strings() {
+        let content = r#"/* Button title */
+"add_new" = "Add New";
+// Shown in the toolbar
+"greeting" = "Say \"hi\"\n";
+title = "Unquoted key";
+"#;
+        let entries =
+            AppleStringsParser::parse_strings(content, Path::new("Localizable.strings")).unwrap();
+
+        let pairs: Vec<_> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("add_new", "Add New", 2),
+                ("greeting", "Say \"hi\"\n", 4),
+                ("title", "Unquoted key", 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_utf16_strings_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Localizable.strings");
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "\"add_new\" = \"Ajouter\";\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        fs::write(&path, bytes).unwrap();
+
+        let entries = AppleStringsParser::parse_file(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].value, "Ajouter");
+    }
+
+    #[test]
+    fn test_missing_semicolon() {
+        let err = AppleStringsParser::parse_strings(
+            "\"a\" = \"b\"\n\"c\" = \"d\";",
+            Path::new("Localizable.strings"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
+
+    #[test]
+    fn test_parse_catalog() {
+        let content = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "add_new" : {
+      "localizations" : {
+        "en" : { "stringUnit" : { "state" : "translated", "value" : "Add New" } },
+        "fr" : { "stringUnit" : { "state" : "translated", "value" : "Ajouter" } }
+      }
+    },
+    "%lld files" : {
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld file" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld files" } }
+            }
+          }
+        }
+      }
+    },
+    "Done" : { }
+  },
+  "version" : "1.0"
+}"#;
+        let entries =
+            AppleStringsParser::parse_catalog(content, Path::new("Localizable.xcstrings")).unwrap();
+
+        let mut pairs: Vec<_> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.line))
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                ("%lld files", "%lld file", 10),
+                ("%lld files", "%lld files", 10),
+                ("Done", "Done", 22),
+                ("add_new", "Add New", 4),
+                ("add_new", "Ajouter", 4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::error::{Result, SearchError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::message_format::{expand, Part};
+use super::translation::TranslationEntry;
+
+/// Parser for Mozilla Fluent translation files (`.ftl`).
+///
+/// Message ids are the keys (`add-new = Add New`), attributes become
+/// `message.attribute` and terms keep their leading dash (`-brand-name`).
+/// Messages with select expressions yield one entry per variant, with the
+/// chosen arms in `branch`:
+///
+/// ```text
+/// emails =
+///     { $unreadEmails ->
+///         [one] You have one unread email.
+///        *[other] You have { $unreadEmails } unread emails.
+///     }
+/// ```
+pub struct FluentParser;
+
+/// A message or attribute whose pattern is being collected
+struct Pending {
+    key: String,
+    line: usize,
+    lines: Vec<String>,
+}
+
+impl FluentParser {
+    pub fn parse_file(path: &Path) -> Result<Vec<TranslationEntry>> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            SearchError::fluent_parse_error(path, format!("Failed to read file: {}", e))
+        })?;
+        Self::parse_content(&content, path)
+    }
+
+    /// Parse Fluent text; `path` is recorded in the entries and errors
+    pub fn parse_content(content: &str, path: &Path) -> Result<Vec<TranslationEntry>> {
+        let mut entries = Vec::new();
+        let mut message_id: Option<String> = None;
+        let mut pending: Option<Pending> = None;
+
+        for (index, line) in content.lines().enumerate() {
+            let line_num = index + 1;
+            let indented = line.starts_with([' ', '\t']);
+            let trimmed = line.trim();
+
+            if !indented {
+                if trimmed.is_empty() {
+                    // Blank lines may separate lines of a multiline pattern;
+                    // the pattern ends at the next unindented line
+                    continue;
+                }
+                Self::finish(pending.take(), path, &mut entries)?;
+                message_id = None;
+                if trimmed.starts_with('#') {
+                    continue;
+                }
+
+                let (id, value) = trimmed.split_once('=').ok_or_else(|| {
+                    SearchError::fluent_parse_error(
+                        path,
+                        format!("line {}: expected 'message-id = value'", line_num),
+                    )
+                })?;
+                let id = id.trim();
+                if !Self::is_identifier(id.strip_prefix('-').unwrap_or(id)) {
+                    return Err(SearchError::fluent_parse_error(
+                        path,
+                        format!("line {}: invalid message id '{}'", line_num, id),
+                    ));
+                }
+                message_id = Some(id.to_string());
+                pending = Some(Pending {
+                    key: id.to_string(),
+                    line: line_num,
+                    lines: vec![value.trim().to_string()],
+                });
+                continue;
+            }
+
+            let attribute = trimmed
+                .strip_prefix('.')
+                .and_then(|rest| rest.split_once('='))
+                .filter(|(name, _)| Self::is_identifier(name.trim()));
+            match (attribute, &message_id) {
+                (Some((name, value)), Some(id)) => {
+                    Self::finish(pending.take(), path, &mut entries)?;
+                    pending = Some(Pending {
+                        key: format!("{}.{}", id, name.trim()),
+                        line: line_num,
+                        lines: vec![value.trim().to_string()],
+                    });
+                }
+                _ => match pending.as_mut() {
+                    Some(p) => p.lines.push(trimmed.to_string()),
+                    None => {
+                        return Err(SearchError::fluent_parse_error(
+                            path,
+                            format!("line {}: indented text outside a message", line_num),
+                        ))
+                    }
+                },
+            }
+        }
+
+        Self::finish(pending, path, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn is_identifier(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    /// Turn a collected pattern into one entry per variant
+    fn finish(
+        pending: Option<Pending>,
+        path: &Path,
+        entries: &mut Vec<TranslationEntry>,
+    ) -> Result<()> {
+        let Some(pending) = pending else {
+            return Ok(());
+        };
+        let source = pending
+            .lines
+            .iter()
+            .map(String::as_str)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        // Messages with only attributes have no value of their own
+        if source.is_empty() {
+            return Ok(());
+        }
+
+        let mut parser = PatternParser {
+            chars: source.chars().collect(),
+            pos: 0,
+        };
+        let parts = parser.parse_pattern(false).ok_or_else(|| {
+            SearchError::fluent_parse_error(
+                path,
+                format!(
+                    "line {}: malformed placeable in '{}'",
+                    pending.line, pending.key
+                ),
+            )
+        })?;
+
+        for variant in expand(&parts) {
+            entries.push(TranslationEntry {
+                key: pending.key.clone(),
+                value: variant.text.trim().to_string(),
+                line: pending.line,
+                file: PathBuf::from(path),
+                branch: (!variant.branch.is_empty()).then_some(variant.branch),
+                locale: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Parser for a Fluent pattern: text, `{ placeables }` and select expressions
+struct PatternParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl PatternParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Parse up to the end of input or, inside a variant, up to the line
+    /// starting the next variant or the `}` closing the select expression
+    fn parse_pattern(&mut self, in_variant: bool) -> Option<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        while let Some(ch) = self.peek() {
+            match ch {
+                '}' if in_variant => break,
+                '\n' if in_variant && self.next_line_ends_variant() => break,
+                '{' => {
+                    self.pos += 1;
+                    match self.parse_placeable()? {
+                        Part::Text(placeable) => text.push_str(&placeable),
+                        choice => {
+                            if !text.is_empty() {
+                                parts.push(Part::Text(std::mem::take(&mut text)));
+                            }
+                            parts.push(choice);
+                        }
+                    }
+                }
+                _ => {
+                    text.push(ch);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Some(parts)
+    }
+
+    fn next_line_ends_variant(&self) -> bool {
+        let rest: String = self.chars[self.pos + 1..].iter().take(2).collect();
+        rest.starts_with('[') || rest.starts_with("*[") || rest.starts_with('}')
+    }
+
+    /// Parse a placeable after its `{`: a string literal, a reference kept as
+    /// written, or a select expression
+    fn parse_placeable(&mut self) -> Option<Part> {
+        self.skip_whitespace();
+        if self.peek() == Some('"') {
+            self.pos += 1;
+            let mut literal = String::new();
+            loop {
+                match self.peek()? {
+                    '"' => break,
+                    '\\' => {
+                        self.pos += 1;
+                        literal.push(self.peek()?);
+                    }
+                    c => literal.push(c),
+                }
+                self.pos += 1;
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            return self.expect('}').map(|_| Part::Text(literal));
+        }
+
+        let mut expression = String::new();
+        loop {
+            match self.peek()? {
+                '}' => {
+                    self.pos += 1;
+                    return Some(Part::Text(format!("{{ {} }}", expression.trim())));
+                }
+                '-' if self.chars.get(self.pos + 1) == Some(&'>') => {
+                    self.pos += 2;
+                    break;
+                }
+                c => {
+                    expression.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        let mut arms = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                '}' => {
+                    self.pos += 1;
+                    break;
+                }
+                '*' => self.pos += 1,
+                '[' => {
+                    self.pos += 1;
+                    let mut key = String::new();
+                    while self.peek()? != ']' {
+                        key.push(self.peek()?);
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                    let arm = self.parse_pattern(true)?;
+                    arms.push((key.trim().to_string(), Self::trim_parts(arm)));
+                }
+                _ => return None,
+            }
+        }
+
+        Some(Part::Choice {
+            name: expression.trim().to_string(),
+            arms,
+        })
+    }
+
+    /// Variant values are trimmed like the lines around them
+    fn trim_parts(mut parts: Vec<Part>) -> Vec<Part> {
+        if let Some(Part::Text(first)) = parts.first_mut() {
+            *first = first.trim_start().to_string();
+        }
+        if let Some(Part::Text(last)) = parts.last_mut() {
+            *last = last.trim_end().to_string();
+        }
+        parts
+    }
+
+    fn expect(&mut self, ch: char) -> Option<()> {
+        (self.peek()? == ch).then(|| self.pos += 1)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Vec<TranslationEntry> {
+        FluentParser::parse_content(content, Path::new("en-US/main.ftl")).unwrap()
+    }
+
+    #[test]
+    fn test_messages_attributes_and_terms() {
+        let entries = parse(
+            r#"# Toolbar
+-brand-name = Firefox
+add-new = Add New
+login-input = Predefined value
+    .placeholder = email@example.com
+about = About { -brand-name }
+welcome = Welcome, { "literal" } friend
+"#,
+        );
+
+        let pairs: Vec<_> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("-brand-name", "Firefox", 2),
+                ("add-new", "Add New", 3),
+                ("login-input", "Predefined value", 4),
+                ("login-input.placeholder", "email@example.com", 5),
+                ("about", "About { -brand-name }", 6),
+                ("welcome", "Welcome, literal friend", 7),
+            ]
+        );
+        assert!(entries.iter().all(|e| e.branch.is_none()));
+    }
+
+    #[test]
+    fn test_select_expression_variants() {
+        let entries = parse(
+            r#"emails =
+    { $unreadEmails ->
+        [one] You have one unread email.
+       *[other] You have { $unreadEmails } unread emails.
+    }
+"#,
+        );
+
+        let variants: Vec<_> = entries
+            .iter()
+            .map(|e| (e.branch.as_deref().unwrap(), e.value.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            variants,
+            vec![
+                ("$unreadEmails:one", "You have one unread email.", 1),
+                (
+                    "$unreadEmails:other",
+                    "You have { $unreadEmails } unread emails.",
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiline_text() {
+        let entries = parse("intro =\n    First line\n    second line\n");
+        assert_eq!(entries[0].value, "First line\nsecond line");
+    }
+
+    #[test]
+    fn test_invalid_entry() {
+        let err = FluentParser::parse_content("just text\n", Path::new("main.ftl")).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
+}
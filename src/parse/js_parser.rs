@@ -1,4 +1,5 @@
 use crate::error::{Result, SearchError};
+use crate::parse::message_format::{contains_ignore_case, file_has_icu_choices};
 use crate::parse::translation::TranslationEntry;
 use std::collections::HashMap;
 use std::fs;
@@ -182,6 +183,8 @@ impl JsParser {
                         value: s.clone(),
                         file: file_path.to_path_buf(),
                         line: 1, // JavaScript files don't have reliable line numbers for nested objects
+                        branch: None,
+                        locale: None,
                     });
                 }
                 serde_json::Value::Object(nested_obj) => {
@@ -201,6 +204,8 @@ impl JsParser {
                                 value: s.clone(),
                                 file: file_path.to_path_buf(),
                                 line: 1,
+                                branch: None,
+                                locale: None,
                             });
                         } else if let serde_json::Value::Object(nested_obj) = v {
                             let nested_map: HashMap<String, serde_json::Value> = nested_obj
@@ -487,9 +492,10 @@ impl JsParser {
         if let Some(q) = query {
             // Pre-filter with ripgrep
             match Self::contains_query(file_path, q) {
-                Ok(false) => return Ok(Vec::new()),
-                Err(_) => {}   // Fall through to full parsing
-                Ok(true) => {} // Continue with parsing
+                Ok(false) if !file_has_icu_choices(file_path) => return Ok(Vec::new()),
+                Ok(false) => {} // The query may span an ICU argument
+                Err(_) => {}    // Fall through to full parsing
+                Ok(true) => {}  // Continue with parsing
             }
         }
 
@@ -497,7 +503,7 @@ impl JsParser {
 
         if let Some(q) = query {
             let q_lower = q.to_lowercase();
-            entries.retain(|e| e.value.to_lowercase().contains(&q_lower));
+            entries.retain(|e| contains_ignore_case(&e.value, &q_lower));
         }
 
         Ok(entries)
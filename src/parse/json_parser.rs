@@ -3,6 +3,7 @@ use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::message_format::contains_ignore_case;
 use super::translation::TranslationEntry;
 
 /// Parser for JSON translation files
@@ -38,7 +39,7 @@ impl JsonParser {
         // Filter by query if provided (since bottom-up trace is disabled)
         if let Some(q) = query {
             let q_lower = q.to_lowercase();
-            entries.retain(|e| e.value.to_lowercase().contains(&q_lower));
+            entries.retain(|e| contains_ignore_case(&e.value, &q_lower));
         }
 
         Ok(entries)
@@ -131,6 +132,8 @@ impl JsonParser {
                     value: s.clone(),
                     line: 0, // Placeholder - serde_json doesn't provide line numbers
                     file: PathBuf::from(file_path),
+                    branch: None,
+                    locale: None,
                 });
             }
             Value::Number(n) => {
@@ -139,6 +142,8 @@ impl JsonParser {
                     value: n.to_string(),
                     line: 0,
                     file: PathBuf::from(file_path),
+                    branch: None,
+                    locale: None,
                 });
             }
             Value::Bool(b) => {
@@ -147,6 +152,8 @@ impl JsonParser {
                     value: b.to_string(),
                     line: 0,
                     file: PathBuf::from(file_path),
+                    branch: None,
+                    locale: None,
                 });
             }
             Value::Array(arr) => {
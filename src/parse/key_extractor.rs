@@ -2,11 +2,20 @@
 
 use crate::cache::SearchResultCache;
 use crate::error::Result;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
+use super::android_parser::AndroidStringsParser;
+use super::apple_strings_parser::AppleStringsParser;
+use super::fluent_parser::FluentParser;
 use super::js_parser::JsParser;
 use super::json_parser::JsonParser;
+use super::locale::{is_catalog_file, is_locale_file_in, locale_from_path};
+use super::message_format::icu_variants;
+use super::po_parser::PoParser;
 use super::translation::TranslationEntry;
 use super::yaml_parser::YamlParser;
 
@@ -15,13 +24,95 @@ use super::yaml_parser::YamlParser;
 /// associated file path and line number for each match.
 pub struct KeyExtractor {
     exclusions: Vec<String>,
+    locale_dirs: Vec<String>, // Extra locale directory names (from .cs.toml)
     verbose: bool,
     quiet: bool,          // Suppress progress indicators (for --simple mode)
     case_sensitive: bool, // Case-sensitive matching
     cache: Option<SearchResultCache>,
+    parsed: Option<ParsedFiles>, // In-memory parses used instead of `cache`
     progress_count: std::cell::Cell<usize>, // Track progress for better indicator
 }
 
+/// Fully parsed translation files kept in memory across searches.
+///
+/// The query daemon holds one per project. Each file is parsed once, in
+/// full, and every search filters the stored entries; a file whose
+/// modification time or size changed is parsed again. Clones share the
+/// same files.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFiles {
+    files: Arc<Mutex<HashMap<PathBuf, ParsedFile>>>,
+}
+
+#[derive(Debug)]
+struct ParsedFile {
+    mtime: SystemTime,
+    size: u64,
+    entries: Arc<Vec<TranslationEntry>>,
+}
+
+impl ParsedFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the entries of `path`, e.g. because a watcher saw it change
+    pub fn forget(&self, path: &Path) {
+        if let Ok(mut files) = self.files.lock() {
+            files.remove(path);
+        }
+    }
+
+    /// Drop every file, e.g. because watcher events were lost
+    pub fn clear(&self) {
+        if let Ok(mut files) = self.files.lock() {
+            files.clear();
+        }
+    }
+
+    /// Number of files held
+    pub fn len(&self) -> usize {
+        self.files.lock().map_or(0, |files| files.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The entries of `path`, parsing it with `parse` unless the stored
+    /// parse is still current
+    fn get_or_parse(
+        &self,
+        path: &Path,
+        parse: impl FnOnce() -> Result<Vec<TranslationEntry>>,
+    ) -> Result<Arc<Vec<TranslationEntry>>> {
+        let metadata = std::fs::metadata(path)?;
+        let (mtime, size) = (metadata.modified()?, metadata.len());
+
+        if let Ok(files) = self.files.lock() {
+            if let Some(file) = files
+                .get(path)
+                .filter(|file| file.mtime == mtime && file.size == size)
+            {
+                return Ok(Arc::clone(&file.entries));
+            }
+        }
+
+        let entries = Arc::new(parse()?);
+        if let Ok(mut files) = self.files.lock() {
+            files.insert(
+                path.to_path_buf(),
+                ParsedFile {
+                    mtime,
+                    size,
+                    entries: Arc::clone(&entries),
+                },
+            );
+        }
+        Ok(entries)
+    }
+}
+
 impl Default for KeyExtractor {
     fn default() -> Self {
         Self::new()
@@ -34,10 +125,27 @@ impl KeyExtractor {
         let cache = SearchResultCache::new().ok(); // Silently disable cache on error
         Self {
             exclusions: Vec::new(),
+            locale_dirs: Vec::new(),
             verbose: false,
             quiet: false,
             case_sensitive: false,
             cache,
+            parsed: None,
+            progress_count: std::cell::Cell::new(0),
+        }
+    }
+
+    /// Create a `KeyExtractor` that parses files in full into `parsed` and
+    /// filters them in memory, bypassing the on-disk cache
+    pub fn with_parsed_files(parsed: ParsedFiles) -> Self {
+        Self {
+            exclusions: Vec::new(),
+            locale_dirs: Vec::new(),
+            verbose: false,
+            quiet: false,
+            case_sensitive: false,
+            cache: None,
+            parsed: Some(parsed),
             progress_count: std::cell::Cell::new(0),
         }
     }
@@ -47,6 +155,11 @@ impl KeyExtractor {
         self.exclusions = exclusions;
     }
 
+    /// Set extra directory names that hold locale files (see `extract_all`)
+    pub fn set_locale_dirs(&mut self, locale_dirs: Vec<String>) {
+        self.locale_dirs = locale_dirs;
+    }
+
     /// Set verbose mode for detailed error messages
     pub fn set_verbose(&mut self, verbose: bool) {
         self.verbose = verbose;
@@ -109,11 +222,41 @@ impl KeyExtractor {
         }
     }
 
-    /// Recursively walk `base_dir` for `*.yml` (or `*.yaml`) files, parse each,
-    /// and return entries whose **value** contains `query`.
+    /// Recursively walk `base_dir` for translation files (YAML, JSON, JS,
+    /// gettext, Fluent, Apple `.strings`/`.xcstrings` and Android `values*/*.xml`),
+    /// parse each, and return entries whose **value** contains `query`.
     ///
     /// Matching respects case sensitivity setting.
     pub fn extract(&self, base_dir: &Path, query: &str) -> Result<Vec<TranslationEntry>> {
+        let files: Vec<PathBuf> = WalkDir::new(base_dir)
+            .into_iter()
+            .filter_entry(|e| {
+                if is_ignored(e) {
+                    return false;
+                }
+                let name = e.file_name().to_string_lossy();
+                !self.exclusions.iter().any(|excl| name == excl.as_str())
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| is_translation_candidate(base_dir, e.path(), &self.exclusions))
+            .map(|e| e.into_path())
+            .collect();
+        self.extract_from_files(&files, query)
+    }
+
+    /// Parse the given translation files and return entries whose **value**
+    /// contains `query`.
+    ///
+    /// This is [`extract`](Self::extract) without the directory walk, for
+    /// callers that already classified the project's files (see
+    /// [`ProjectScanner`](crate::search::ProjectScanner)). Files should pass
+    /// [`is_translation_candidate`]; files that fail to parse are skipped.
+    pub fn extract_from_files(
+        &self,
+        files: &[PathBuf],
+        query: &str,
+    ) -> Result<Vec<TranslationEntry>> {
         let mut matches = Vec::new();
         let search_query = if self.case_sensitive {
             query.to_string()
@@ -122,6 +265,118 @@ impl KeyExtractor {
         };
         let mut skipped_files = 0;
 
+        for path in files {
+            // Files are parsed in full and cached regardless of the query,
+            // so later searches only filter entries in memory
+            let parse = || {
+                self.parse_all_cached(path)
+                    .map(|entries| with_path_locale(entries, path))
+            };
+            let parsed = match &self.parsed {
+                Some(parsed) => parsed.get_or_parse(path, parse),
+                None => parse().map(Arc::new),
+            };
+            let all_entries = match parsed {
+                Ok(entries) => entries,
+                Err(e) => {
+                    skipped_files += 1;
+                    self.print_progress('S');
+                    if self.verbose {
+                        eprintln!(
+                            "\nWarning: Failed to parse {} {}: {}",
+                            describe_format(path),
+                            path.display(),
+                            e
+                        );
+                    }
+                    continue;
+                }
+            };
+
+            // Filter for matching entries
+            matches.extend(
+                all_entries
+                    .iter()
+                    .filter_map(|e| self.match_entry(e, &search_query)),
+            );
+        }
+
+        // Print final newline and summary if files were skipped (only in verbose mode)
+        // Note: Skipped files are typically config files (package.json, tsconfig.json, etc.)
+        // that aren't translation files, which is expected behavior.
+        if !self.quiet {
+            // Always print final newline if we showed any progress
+            if self.progress_count.get() > 0 {
+                eprintln!();
+            }
+
+            if skipped_files > 0 && self.verbose {
+                eprintln!(
+                    "(Skipped {} non-translation file{})",
+                    skipped_files,
+                    if skipped_files == 1 { "" } else { "s" }
+                );
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Check an entry's value against `search_query` (already lowercased
+    /// when matching case-insensitively).
+    ///
+    /// ICU plural/select messages are matched arm by arm rather than as raw
+    /// text, so a search hits text inside `{count, plural, other {# items}}`
+    /// but not the `plural` keyword. When only some arms match, the first
+    /// matching arm is recorded in `branch`.
+    fn match_entry(
+        &self,
+        entry: &TranslationEntry,
+        search_query: &str,
+    ) -> Option<TranslationEntry> {
+        let fold = |text: &str| {
+            if self.case_sensitive {
+                text.to_string()
+            } else {
+                text.to_lowercase()
+            }
+        };
+
+        if entry.branch.is_none() {
+            if let Some(variants) = icu_variants(&entry.value) {
+                let hits: Vec<_> = variants
+                    .iter()
+                    .filter(|v| fold(&v.text).contains(search_query))
+                    .collect();
+                if hits.is_empty() {
+                    return None;
+                }
+                let mut entry = entry.clone();
+                // A hit in every arm is text shared outside the arguments
+                if hits.len() < variants.len() {
+                    entry.branch = Some(hits[0].branch.clone());
+                }
+                return Some(entry);
+            }
+        }
+
+        fold(&entry.value)
+            .contains(search_query)
+            .then(|| entry.clone())
+    }
+
+    /// Walk `base_dir` and return **every** flattened entry from the locale
+    /// files it contains (YAML, JSON, JS and catalog formats such as gettext
+    /// or Android resources), regardless of value.
+    ///
+    /// Unlike [`extract`](Self::extract), which finds entries by value, this is
+    /// used for whole-project audits such as the unused-keys report. Only
+    /// files recognised by [`is_locale_file_in`] are parsed, so `package.json`
+    /// and ordinary JavaScript sources are not mistaken for translations.
+    pub fn extract_all(&self, base_dir: &Path) -> Result<Vec<TranslationEntry>> {
+        let mut entries = Vec::new();
+        let mut skipped_files = 0;
+
         let walker = WalkDir::new(base_dir).into_iter();
         for entry in walker
             .filter_entry(|e| {
@@ -129,289 +384,83 @@ impl KeyExtractor {
                     return false;
                 }
                 let name = e.file_name().to_string_lossy();
-                for excl in &self.exclusions {
-                    if name == excl.as_str() {
-                        return false;
-                    }
-                }
-                true
+                !self.exclusions.iter().any(|excl| name == excl.as_str())
             })
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
         {
             let path = entry.path();
-            if let Some(ext) = path.extension() {
-                let ext_str = ext.to_string_lossy();
-
-                if ext_str == "yml" || ext_str == "yaml" {
-                    // OPTIMIZATION: Use ripgrep to pre-filter files before parsing
-                    // This avoids expensive YAML parsing for files without matches
-                    match YamlParser::contains_query(path, query) {
-                        Ok(false) => {
-                            // No match in file, skip it entirely
-                            self.print_progress('-');
-                            continue;
-                        }
-                        Err(_e) => {
-                            // ripgrep failed, fall back to full parsing
-                            // (don't skip the file, just proceed with parsing)
-                        }
-                        Ok(true) => {
-                            // Match found, proceed with parsing below
-                        }
-                    }
-
-                    // Try cache first
-                    let metadata = std::fs::metadata(path).ok();
-                    let cached_results = if let (Some(cache), Some(meta)) = (&self.cache, metadata)
-                    {
-                        let mtime = meta.modified().ok();
-                        let size = meta.len();
-                        if let Some(mt) = mtime {
-                            cache.get(path, query, false, mt, size)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-
-                    let all_entries = if let Some(cached) = cached_results {
-                        if !self.quiet {
-                            self.print_progress('C');
-                        }
-                        cached
-                    } else {
-                        // Cache miss - parse file with query for optimization
-                        match YamlParser::parse_file_with_query(path, Some(query)) {
-                            Ok(entries) => {
-                                self.print_progress('.');
-
-                                // Store in cache
-                                if let (Some(cache), Ok(meta)) =
-                                    (&self.cache, std::fs::metadata(path))
-                                {
-                                    if let (Ok(mtime), size) = (meta.modified(), meta.len()) {
-                                        let _ =
-                                            cache.set(path, query, false, mtime, size, &entries);
-                                    }
-                                }
-
-                                entries
-                            }
-                            Err(e) => {
-                                skipped_files += 1;
-                                self.print_progress('S');
-                                if self.verbose {
-                                    eprintln!(
-                                        "\nWarning: Failed to parse YAML file {}: {}",
-                                        path.display(),
-                                        e
-                                    );
-                                }
-                                continue;
-                            }
-                        }
-                    };
-
-                    // Filter for matching entries
-                    for e in all_entries {
-                        let value_to_check = if self.case_sensitive {
-                            e.value.clone()
-                        } else {
-                            e.value.to_lowercase()
-                        };
-
-                        if value_to_check.contains(&search_query) {
-                            matches.push(e);
-                        }
-                    }
-                } else if ext_str == "json" {
-                    // OPTIMIZATION: Use ripgrep to pre-filter files before parsing
-                    // Note: We don't have a contains_query for JSON yet, so we use YAML's
-                    match YamlParser::contains_query(path, query) {
-                        Ok(false) => {
-                            // No match in file, skip it entirely
-                            self.print_progress('-');
-                            continue;
-                        }
-                        Err(_e) => {
-                            // ripgrep failed, fall back to full parsing
-                        }
-                        Ok(true) => {
-                            // Match found, proceed with parsing below
-                        }
-                    }
-
-                    // Try cache first
-                    let metadata = std::fs::metadata(path).ok();
-                    let cached_results = if let (Some(cache), Some(meta)) = (&self.cache, metadata)
-                    {
-                        let mtime = meta.modified().ok();
-                        let size = meta.len();
-                        if let Some(mt) = mtime {
-                            cache.get(path, query, false, mt, size)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-
-                    let all_entries = if let Some(cached) = cached_results {
-                        if !self.quiet {
-                            self.print_progress('C');
-                        }
-                        cached
-                    } else {
-                        // Cache miss - parse file with query for optimization
-                        match JsonParser::parse_file_with_query(path, Some(query)) {
-                            Ok(entries) => {
-                                self.print_progress('.');
-
-                                // Store in cache
-                                if let (Some(cache), Ok(meta)) =
-                                    (&self.cache, std::fs::metadata(path))
-                                {
-                                    if let (Ok(mtime), size) = (meta.modified(), meta.len()) {
-                                        let _ =
-                                            cache.set(path, query, false, mtime, size, &entries);
-                                    }
-                                }
-
-                                entries
-                            }
-                            Err(e) => {
-                                skipped_files += 1;
-                                self.print_progress('S');
-                                if self.verbose {
-                                    eprintln!(
-                                        "\nWarning: Failed to parse JSON file {}: {}",
-                                        path.display(),
-                                        e
-                                    );
-                                }
-                                continue;
-                            }
-                        }
-                    };
-
-                    // Filter for matching entries
-                    for e in all_entries {
-                        let value_to_check = if self.case_sensitive {
-                            e.value.clone()
-                        } else {
-                            e.value.to_lowercase()
-                        };
-
-                        if value_to_check.contains(&search_query) {
-                            matches.push(e);
-                        }
-                    }
-                } else if ext_str == "js" {
-                    // OPTIMIZATION: Use ripgrep to pre-filter files before parsing
-                    match JsParser::contains_query(path, query) {
-                        Ok(false) => {
-                            // No match in file, skip it entirely
-                            self.print_progress('-');
-                            continue;
-                        }
-                        Err(_e) => {
-                            // ripgrep failed, fall back to full parsing
-                        }
-                        Ok(true) => {
-                            // Match found, proceed with parsing below
-                        }
-                    }
+            let relative = path.strip_prefix(base_dir).unwrap_or(path);
+            let is_locale = match path.extension().and_then(|e| e.to_str()) {
+                // Catalog formats are translation files wherever they live
+                _ if is_catalog_file(path) => true,
+                Some("yml" | "yaml" | "json" | "js") => {
+                    is_locale_file_in(relative, &self.locale_dirs)
+                }
+                _ => false,
+            };
+            if !is_locale {
+                continue;
+            }
 
-                    // Try cache first
-                    let metadata = std::fs::metadata(path).ok();
-                    let cached_results = if let (Some(cache), Some(meta)) = (&self.cache, metadata)
-                    {
-                        let mtime = meta.modified().ok();
-                        let size = meta.len();
-                        if let Some(mt) = mtime {
-                            cache.get(path, query, false, mt, size)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-
-                    let all_entries = if let Some(cached) = cached_results {
-                        if !self.quiet {
-                            self.print_progress('C');
-                        }
-                        cached
-                    } else {
-                        // Cache miss - parse file with query for optimization
-                        match JsParser::parse_file_with_query(path, Some(query)) {
-                            Ok(entries) => {
-                                self.print_progress('.');
-
-                                // Store in cache
-                                if let (Some(cache), Ok(meta)) =
-                                    (&self.cache, std::fs::metadata(path))
-                                {
-                                    if let (Ok(mtime), size) = (meta.modified(), meta.len()) {
-                                        let _ =
-                                            cache.set(path, query, false, mtime, size, &entries);
-                                    }
-                                }
-
-                                entries
-                            }
-                            Err(e) => {
-                                skipped_files += 1;
-                                self.print_progress('S');
-                                if self.verbose {
-                                    eprintln!(
-                                        "\nWarning: Failed to parse JavaScript file {}: {}",
-                                        path.display(),
-                                        e
-                                    );
-                                }
-                                continue;
-                            }
-                        }
-                    };
-
-                    // Filter for matching entries
-                    for e in all_entries {
-                        let value_to_check = if self.case_sensitive {
-                            e.value.clone()
-                        } else {
-                            e.value.to_lowercase()
-                        };
-
-                        if value_to_check.contains(&search_query) {
-                            matches.push(e);
-                        }
+            match self.parse_all_cached(path) {
+                Ok(file_entries) => entries.extend(with_path_locale(file_entries, path)),
+                Err(e) => {
+                    skipped_files += 1;
+                    self.print_progress('S');
+                    if self.verbose {
+                        eprintln!("\nWarning: Failed to parse {}: {}", path.display(), e);
                     }
                 }
             }
         }
 
-        // Print final newline and summary if files were skipped (only in verbose mode)
-        // Note: Skipped files are typically config files (package.json, tsconfig.json, etc.)
-        // that aren't translation files, which is expected behavior.
         if !self.quiet {
-            // Always print final newline if we showed any progress
             if self.progress_count.get() > 0 {
                 eprintln!();
             }
 
             if skipped_files > 0 && self.verbose {
                 eprintln!(
-                    "(Skipped {} non-translation file{})",
+                    "(Skipped {} unparseable locale file{})",
                     skipped_files,
                     if skipped_files == 1 { "" } else { "s" }
                 );
             }
         }
 
-        Ok(matches)
+        Ok(entries)
+    }
+
+    /// Parse every entry of a locale file, going through the cache of
+    /// parsed files.
+    fn parse_all_cached(&self, path: &Path) -> Result<Vec<TranslationEntry>> {
+        let metadata = std::fs::metadata(path).ok();
+        let fingerprint = metadata.and_then(|m| m.modified().ok().map(|mt| (mt, m.len())));
+
+        if let (Some(cache), Some((mtime, size))) = (&self.cache, fingerprint) {
+            if let Some(cached) = cache.get(path, mtime, size) {
+                self.print_progress('C');
+                return Ok(cached);
+            }
+        }
+
+        let entries = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => JsonParser::parse_file(path)?,
+            Some("js") => JsParser::parse_file(path)?,
+            Some("po" | "pot") => PoParser::parse_file(path)?,
+            Some("ftl") => FluentParser::parse_file(path)?,
+            Some("strings" | "xcstrings") => AppleStringsParser::parse_file(path)?,
+            Some("xml") => AndroidStringsParser::parse_file(path)?,
+            _ => YamlParser::parse_file_in(path, &self.locale_dirs)?,
+        };
+        self.print_progress('.');
+
+        if let (Some(cache), Some((mtime, size))) = (&self.cache, fingerprint) {
+            let _ = cache.set(path, mtime, size, &entries);
+        }
+
+        Ok(entries)
     }
 }
 
@@ -421,18 +470,59 @@ fn is_ignored(entry: &walkdir::DirEntry) -> bool {
         return false;
     }
 
-    entry
-        .file_name()
-        .to_str()
-        .map(|s| {
-            s.starts_with('.') // Hidden files/dirs
-                || s == "node_modules"
-                || s == "target"
-                || s == "dist"
-                || s == "build"
-                || s == "vendor"
-        })
-        .unwrap_or(false)
+    entry.file_name().to_str().is_some_and(is_ignored_name)
+}
+
+/// Hidden files/dirs and dependency or build output directories
+fn is_ignored_name(name: &str) -> bool {
+    name.starts_with('.')
+        || name == "node_modules"
+        || name == "target"
+        || name == "dist"
+        || name == "build"
+        || name == "vendor"
+}
+
+/// Whether `path` (found under `base_dir`) is a file [`KeyExtractor::extract`]
+/// parses for translations: a YAML, JSON, JS or catalog file that is not
+/// inside a hidden, dependency or build directory, and whose path doesn't
+/// contain a component named in `exclusions`.
+pub fn is_translation_candidate(base_dir: &Path, path: &Path, exclusions: &[String]) -> bool {
+    let has_translation_ext = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml" | "yaml" | "json" | "js")
+    ) || is_catalog_file(path);
+    if !has_translation_ext {
+        return false;
+    }
+
+    let relative = path.strip_prefix(base_dir).unwrap_or(path);
+    !relative.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        is_ignored_name(&name) || exclusions.iter().any(|excl| name == excl.as_str())
+    })
+}
+
+/// Fill in the locale of entries whose file content doesn't name it (most
+/// formats) from the file's path, e.g. `locales/fr/common.json` → `fr`
+fn with_path_locale(mut entries: Vec<TranslationEntry>, path: &Path) -> Vec<TranslationEntry> {
+    if entries.iter().any(|e| e.locale.is_none()) {
+        let locale = locale_from_path(path);
+        for entry in entries.iter_mut().filter(|e| e.locale.is_none()) {
+            entry.locale = locale.clone();
+        }
+    }
+    entries
+}
+
+/// Human-readable format name for parse warnings
+fn describe_format(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        _ if is_catalog_file(path) => "translation catalog",
+        Some("json") => "JSON file",
+        Some("js") => "JavaScript file",
+        _ => "YAML file",
+    }
 }
 
 #[cfg(test)]
@@ -653,4 +743,64 @@ export default {
 
         Ok(())
     }
+
+    #[test]
+    fn test_extract_all_reads_every_locale_entry() -> Result<()> {
+        let dir = tempdir()?;
+        let locales = dir.path().join("config/locales");
+        fs::create_dir_all(&locales)?;
+        fs::write(
+            locales.join("en.yml"),
+            "en:\n  greeting:\n    hello: \"Hello\"\n    bye: \"Bye\"",
+        )?;
+        fs::write(
+            locales.join("de.json"),
+            r#"{"greeting": {"hello": "Hallo"}}"#,
+        )?;
+        // Not a locale file: must not be parsed as translations
+        fs::write(dir.path().join("package.json"), r#"{"name": "app"}"#)?;
+
+        let mut extractor = KeyExtractor::new();
+        extractor.set_quiet(true);
+        let mut keys: Vec<_> = extractor
+            .extract_all(dir.path())?
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        keys.sort();
+
+        // The YAML locale root ("en:") is not part of the key
+        assert_eq!(
+            keys,
+            vec!["greeting.bye", "greeting.hello", "greeting.hello"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parsed_files_reparse_changed_files() -> Result<()> {
+        let dir = tempdir()?;
+        let en_path = dir.path().join("en.yml");
+        fs::write(&en_path, "en:\n  greeting: \"Hello\"\n")?;
+
+        let parsed = ParsedFiles::new();
+        let mut extractor = KeyExtractor::with_parsed_files(parsed.clone());
+        extractor.set_quiet(true);
+        let found = extractor.extract_from_files(std::slice::from_ref(&en_path), "Hello")?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(parsed.len(), 1);
+
+        // Same store, new content: the fingerprint no longer matches
+        fs::write(
+            &en_path,
+            "en:\n  greeting: \"Hello there\"\n  bye: \"Bye\"\n",
+        )?;
+        let found = extractor.extract_from_files(std::slice::from_ref(&en_path), "Bye")?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].key, "bye");
+
+        parsed.forget(&en_path);
+        assert!(parsed.is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,242 @@
+use super::android_parser::AndroidStringsParser;
+use std::path::Path;
+
+/// Directory names that conventionally hold translation files
+const LOCALE_DIR_NAMES: &[&str] = &[
+    "locales",
+    "locale",
+    "i18n",
+    "l10n",
+    "lang",
+    "langs",
+    "languages",
+    "translations",
+    "messages",
+];
+
+/// ISO 639-1 language codes, used to recognise `en.yml`, `fr/common.json`, ...
+const LANGUAGE_CODES: &[&str] = &[
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bh",
+    "bi", "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da",
+    "de", "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+    "fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz",
+    "ia", "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj",
+    "kk", "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln",
+    "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb",
+    "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi",
+    "pl", "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk",
+    "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti",
+    "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo",
+    "wa", "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+/// Whether `name` looks like a locale identifier: `en`, `pt-BR`, `zh_Hant`, `en-GB`.
+pub fn is_locale_code(name: &str) -> bool {
+    let mut parts = name.splitn(2, ['-', '_']);
+    let language = parts.next().unwrap_or_default();
+    if !LANGUAGE_CODES.contains(&language) {
+        return false;
+    }
+    match parts.next() {
+        None => true,
+        Some(region) => {
+            (2..=4).contains(&region.len()) && region.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+    }
+}
+
+/// The locale named by a directory, with the directory name rewritten as a
+/// sibling pattern: `de` → `{locale}`, iOS `fr.lproj` → `{locale}.lproj`,
+/// Android `values-pt-rBR` → `values-{locale}`.
+fn dir_locale(name: &str) -> Option<(&str, String)> {
+    if is_locale_code(name) {
+        return Some((name, "{locale}".to_string()));
+    }
+    if let Some(locale) = name.strip_suffix(".lproj").filter(|l| is_locale_code(l)) {
+        return Some((locale, "{locale}.lproj".to_string()));
+    }
+    name.strip_prefix("values-")
+        .filter(|l| is_locale_code(l))
+        .map(|locale| (locale, "values-{locale}".to_string()))
+}
+
+/// The locale a translation file belongs to, derived from its path.
+///
+/// The file stem wins (`config/locales/fr.yml` → `fr`, `devise.fr.yml` → `fr`),
+/// otherwise the closest parent directory named like a locale
+/// (`locales/de/common.json` → `de`, `fr.lproj/Localizable.strings` → `fr`,
+/// `res/values-es/strings.xml` → `es`).
+pub fn locale_from_path(path: &Path) -> Option<String> {
+    let stem_locale = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|stem| stem.rsplit('.').next());
+    if let Some(locale) = stem_locale.filter(|s| is_locale_code(s)) {
+        return Some(locale.to_string());
+    }
+
+    path.parent()?
+        .components()
+        .rev()
+        .filter_map(|c| c.as_os_str().to_str())
+        .find_map(dir_locale)
+        .map(|(locale, _)| locale.to_string())
+}
+
+/// Split a locale file path into its locale and a sibling pattern in which
+/// the locale is replaced by `{locale}`.
+///
+/// Files sharing a pattern are translations of each other:
+/// `config/locales/en.yml` and `config/locales/fr.yml` both map to
+/// `config/locales/{locale}.yml`, `locales/de/common.json` to
+/// `locales/{locale}/common.json`.
+pub fn locale_pattern(path: &Path) -> Option<(String, String)> {
+    let locale = locale_from_path(path)?;
+    let mut parts: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let file_name = parts.pop()?;
+
+    let stem = path.file_stem()?.to_string_lossy();
+    let stem_locale = stem.rsplit('.').next().unwrap_or_default();
+    if stem_locale == locale {
+        let prefix = &stem[..stem.len() - locale.len()];
+        let suffix = &file_name[stem.len()..];
+        parts.push(format!("{}{{locale}}{}", prefix, suffix));
+    } else {
+        // locale_from_path picked the closest matching parent directory
+        let index = parts
+            .iter()
+            .rposition(|p| dir_locale(p).is_some_and(|(l, _)| l == locale))?;
+        let (_, pattern) = dir_locale(&parts[index])?;
+        parts[index] = pattern;
+        parts.push(file_name);
+    }
+
+    Some((locale, parts.join("/")))
+}
+
+/// Whether `path` has a format used only for translations, so it needs no
+/// locale directory or locale name to be recognised: gettext catalogs, Fluent,
+/// Apple
+/// `.strings`/`.xcstrings` and Android `values*/` resources
+pub fn is_catalog_file(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str());
+    matches!(ext, Some("po" | "pot" | "ftl" | "strings" | "xcstrings"))
+        || AndroidStringsParser::is_resource_file(path)
+}
+
+/// Whether a YAML/JSON/JS file is a translation file rather than, say,
+/// `package.json`: it either lives under a conventional locale directory or
+/// is named after a locale.
+pub fn is_locale_file(path: &Path) -> bool {
+    is_locale_file_in(path, &[])
+}
+
+/// Like [`is_locale_file`], also accepting the directory names in
+/// `extra_dirs` (from the `[locales]` section of `.cs.toml`)
+pub fn is_locale_file_in(path: &Path, extra_dirs: &[String]) -> bool {
+    let in_locale_dir = path.parent().is_some_and(|parent| {
+        parent.components().any(|c| {
+            c.as_os_str().to_str().is_some_and(|name| {
+                let name = name.to_lowercase();
+                LOCALE_DIR_NAMES.contains(&name.as_str())
+                    || extra_dirs.iter().any(|d| d.to_lowercase() == name)
+            })
+        })
+    });
+
+    in_locale_dir || locale_from_path(path).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_locale_code() {
+        assert!(is_locale_code("en"));
+        assert!(is_locale_code("pt-BR"));
+        assert!(is_locale_code("zh_Hant"));
+        assert!(!is_locale_code("app"));
+        assert!(!is_locale_code("db"));
+        assert!(!is_locale_code("en-"));
+    }
+
+    #[test]
+    fn test_locale_from_path() {
+        assert_eq!(
+            locale_from_path(Path::new("config/locales/fr.yml")),
+            Some("fr".to_string())
+        );
+        assert_eq!(
+            locale_from_path(Path::new("public/locales/de/common.json")),
+            Some("de".to_string())
+        );
+        assert_eq!(
+            locale_from_path(Path::new("config/locales/devise.pt-BR.yml")),
+            Some("pt-BR".to_string())
+        );
+        assert_eq!(
+            locale_from_path(Path::new("res/values-pt-rBR/strings.xml")),
+            Some("pt-rBR".to_string())
+        );
+        assert_eq!(locale_from_path(Path::new("res/values/strings.xml")), None);
+        assert_eq!(locale_from_path(Path::new("package.json")), None);
+    }
+
+    #[test]
+    fn test_locale_pattern() {
+        let pattern = |p: &str| locale_pattern(Path::new(p));
+        assert_eq!(
+            pattern("config/locales/fr.yml"),
+            Some(("fr".to_string(), "config/locales/{locale}.yml".to_string()))
+        );
+        assert_eq!(
+            pattern("config/locales/devise.pt-BR.yml"),
+            Some((
+                "pt-BR".to_string(),
+                "config/locales/devise.{locale}.yml".to_string()
+            ))
+        );
+        assert_eq!(
+            pattern("public/locales/de/common.json"),
+            Some((
+                "de".to_string(),
+                "public/locales/{locale}/common.json".to_string()
+            ))
+        );
+        assert_eq!(
+            pattern("ios/App/fr.lproj/Localizable.strings"),
+            Some((
+                "fr".to_string(),
+                "ios/App/{locale}.lproj/Localizable.strings".to_string()
+            ))
+        );
+        assert_eq!(
+            pattern("res/values-es/strings.xml"),
+            Some((
+                "es".to_string(),
+                "res/values-{locale}/strings.xml".to_string()
+            ))
+        );
+        assert_eq!(pattern("src/i18n/messages.json"), None);
+    }
+
+    #[test]
+    fn test_is_locale_file() {
+        assert!(is_locale_file(Path::new("config/locales/devise.en.yml")));
+        assert!(is_locale_file(Path::new("src/i18n/messages.json")));
+        assert!(is_locale_file(Path::new("real_world/en.json")));
+        assert!(!is_locale_file(Path::new("package.json")));
+        assert!(!is_locale_file(Path::new("config/database.yml")));
+        assert!(is_catalog_file(Path::new("app/res/values-fr/strings.xml")));
+        assert!(is_catalog_file(Path::new("Base.lproj/Localizable.strings")));
+        assert!(!is_catalog_file(Path::new("app/res/layout/main.xml")));
+        assert!(is_locale_file_in(
+            Path::new("res/strings/app.json"),
+            &["strings".to_string()]
+        ));
+    }
+}
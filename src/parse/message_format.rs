@@ -0,0 +1,363 @@
+//! Plural/select-aware handling of translation values.
+//!
+//! ICU MessageFormat values such as
+//! `{count, plural, one {# item} other {# items}}` and Fluent select
+//! expressions render differently depending on their arguments. Searching the
+//! raw value misses text that spans an argument boundary and can't tell which
+//! arm matched, so messages are expanded into [`Variant`]s: one per
+//! combination of arms, each with the plain text it displays.
+
+use std::path::Path;
+
+/// Upper bound on the variants a single message expands to, so deeply nested
+/// selects can't blow up a search
+const MAX_VARIANTS: usize = 256;
+
+/// One way a message can render
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    /// The arm chosen for each plural/select argument, outermost first,
+    /// e.g. `gender:female > count:one`
+    pub branch: String,
+    /// The displayed text, with simple placeholders kept as written
+    pub text: String,
+}
+
+/// A parsed message: literal text interleaved with plural/select arguments
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Part {
+    Text(String),
+    Choice {
+        /// The selector, e.g. `count` or `$unreadEmails`
+        name: String,
+        /// `(key, parts)` per arm, e.g. `("one", [Text("# item")])`
+        arms: Vec<(String, Vec<Part>)>,
+    },
+}
+
+/// Expand parsed parts into every variant they can render
+pub(crate) fn expand(parts: &[Part]) -> Vec<Variant> {
+    let mut variants = vec![(Vec::<String>::new(), String::new())];
+    for part in parts {
+        match part {
+            Part::Text(text) => {
+                for (_, rendered) in &mut variants {
+                    rendered.push_str(text);
+                }
+            }
+            Part::Choice { name, arms } => {
+                let mut next = Vec::new();
+                'combine: for (branch, rendered) in &variants {
+                    for (key, arm) in arms {
+                        for inner in expand(arm) {
+                            let mut branch = branch.clone();
+                            branch.push(format!("{}:{}", name, key));
+                            if !inner.branch.is_empty() {
+                                branch.push(inner.branch);
+                            }
+                            next.push((branch, format!("{}{}", rendered, inner.text)));
+                            if next.len() >= MAX_VARIANTS {
+                                break 'combine;
+                            }
+                        }
+                    }
+                }
+                variants = next;
+            }
+        }
+    }
+
+    variants
+        .into_iter()
+        .map(|(branch, text)| Variant {
+            branch: branch.join(" > "),
+            text,
+        })
+        .collect()
+}
+
+/// Expand an ICU MessageFormat value into its variants.
+///
+/// Returns `None` for values without `plural`, `select` or `selectordinal`
+/// arguments (and for values that aren't valid ICU), which callers should
+/// match as plain text.
+pub fn icu_variants(message: &str) -> Option<Vec<Variant>> {
+    // Cheap check before parsing: most values have no arguments at all
+    if !message.contains('{') {
+        return None;
+    }
+    let mut parser = IcuParser {
+        chars: message.chars().collect(),
+        pos: 0,
+    };
+    let parts = parser.parse_message(false)?;
+    if parser.pos != parser.chars.len() || !parts.iter().any(|p| matches!(p, Part::Choice { .. })) {
+        return None;
+    }
+    Some(expand(&parts))
+}
+
+/// Opening of an ICU plural/select argument, e.g. `{count, plural,`
+const ICU_CHOICE_PATTERN: &str = r"\{\s*[\w.]+\s*,\s*(?:plural|select|selectordinal)\s*,";
+
+/// Whether the file at `path` contains ICU plural/select arguments.
+///
+/// Literal pre-filters can't rule such files out: a query may match text that
+/// spans an argument (`# items in your cart`) without appearing verbatim.
+pub fn file_has_icu_choices(path: &Path) -> bool {
+    use grep_regex::RegexMatcher;
+    use grep_searcher::sinks::UTF8;
+    use grep_searcher::SearcherBuilder;
+
+    let Ok(matcher) = RegexMatcher::new(ICU_CHOICE_PATTERN) else {
+        return false;
+    };
+    let mut found = false;
+    let _ = SearcherBuilder::new().build().search_path(
+        &matcher,
+        path,
+        UTF8(|_, _| {
+            found = true;
+            Ok(false)
+        }),
+    );
+    found
+}
+
+/// Case-insensitive containment that also looks inside ICU plural/select
+/// arms; `query_lower` must already be lowercased
+pub fn contains_ignore_case(value: &str, query_lower: &str) -> bool {
+    value.to_lowercase().contains(query_lower)
+        || icu_variants(value).is_some_and(|variants| {
+            variants
+                .iter()
+                .any(|v| v.text.to_lowercase().contains(query_lower))
+        })
+}
+
+struct IcuParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl IcuParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Parse text and arguments up to the end of input or, inside an arm,
+    /// up to (not including) its closing `}`
+    fn parse_message(&mut self, in_arm: bool) -> Option<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        while let Some(ch) = self.peek() {
+            match ch {
+                '}' if in_arm => break,
+                '}' => return None,
+                '{' => {
+                    match self.parse_argument()? {
+                        Part::Text(arg) => text.push_str(&arg),
+                        choice => {
+                            if !text.is_empty() {
+                                parts.push(Part::Text(std::mem::take(&mut text)));
+                            }
+                            parts.push(choice);
+                        }
+                    }
+                    continue;
+                }
+                '\'' => self.parse_quoted(&mut text),
+                _ => {
+                    text.push(ch);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Some(parts)
+    }
+
+    /// `''` is an apostrophe; `'{...}'` quotes syntax characters
+    fn parse_quoted(&mut self, text: &mut String) {
+        self.pos += 1;
+        match self.peek() {
+            Some('\'') => {
+                text.push('\'');
+                self.pos += 1;
+            }
+            Some('{' | '}' | '#' | '|') => {
+                while let Some(ch) = self.peek() {
+                    self.pos += 1;
+                    if ch == '\'' {
+                        break;
+                    }
+                    text.push(ch);
+                }
+            }
+            _ => text.push('\''),
+        }
+    }
+
+    /// Parse `{name}`, `{name, type, ...}` starting at `{`
+    fn parse_argument(&mut self) -> Option<Part> {
+        let start = self.pos;
+        self.pos += 1;
+        let name = self.read_until(&[',', '}'])?;
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Some(Part::Text(format!("{{{}}}", name.trim())));
+        }
+
+        self.pos += 1; // ','
+        let kind = self.read_until(&[',', '}'])?;
+        if !matches!(kind.trim(), "plural" | "select" | "selectordinal") || self.peek() != Some(',')
+        {
+            // number/date/time arguments render as a placeholder
+            self.skip_to_matching_brace()?;
+            return Some(Part::Text(self.chars[start..self.pos].iter().collect()));
+        }
+        self.pos += 1; // ','
+
+        let mut arms = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                '}' => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {
+                    let key = self.read_until(&['{', ' ', '\t', '\n', '\r'])?;
+                    self.skip_whitespace();
+                    if key.starts_with("offset:") && self.peek() != Some('{') {
+                        continue;
+                    }
+                    if self.peek() != Some('{') {
+                        return None;
+                    }
+                    self.pos += 1;
+                    let arm = self.parse_message(true)?;
+                    if self.peek() != Some('}') {
+                        return None;
+                    }
+                    self.pos += 1;
+                    arms.push((key, arm));
+                }
+            }
+        }
+
+        Some(Part::Choice {
+            name: name.trim().to_string(),
+            arms,
+        })
+    }
+
+    fn read_until(&mut self, stops: &[char]) -> Option<String> {
+        let mut out = String::new();
+        while let Some(ch) = self.peek() {
+            if stops.contains(&ch) {
+                return Some(out);
+            }
+            out.push(ch);
+            self.pos += 1;
+        }
+        None
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_to_matching_brace(&mut self) -> Option<()> {
+        let mut depth = 1;
+        while let Some(ch) = self.peek() {
+            self.pos += 1;
+            match ch {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(message: &str) -> Vec<(String, String)> {
+        icu_variants(message)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.branch, v.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_values_are_not_icu() {
+        assert_eq!(icu_variants("Add New"), None);
+        assert_eq!(icu_variants("Hello {name}"), None);
+        assert_eq!(icu_variants("Unbalanced {count, plural, one {x}"), None);
+    }
+
+    #[test]
+    fn test_plural_arms() {
+        assert_eq!(
+            pairs("You have {count, plural, =0 {no items} one {# item} other {# items}}"),
+            vec![
+                ("count:=0".to_string(), "You have no items".to_string()),
+                ("count:one".to_string(), "You have # item".to_string()),
+                ("count:other".to_string(), "You have # items".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_select_and_placeholders() {
+        let variants = pairs(
+            "{gender, select, female {{name} has {n, plural, one {a cat} other {cats}}} other {They do}}",
+        );
+        assert_eq!(
+            variants,
+            vec![
+                (
+                    "gender:female > n:one".to_string(),
+                    "{name} has a cat".to_string()
+                ),
+                (
+                    "gender:female > n:other".to_string(),
+                    "{name} has cats".to_string()
+                ),
+                ("gender:other".to_string(), "They do".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contains_ignore_case_spans_arguments() {
+        let message = "You have {count, plural, one {# item} other {# items}} left";
+        assert!(contains_ignore_case(message, "# items left"));
+        assert!(contains_ignore_case(message, "you have"));
+        assert!(!contains_ignore_case("Add New", "items"));
+    }
+
+    #[test]
+    fn test_quoting_and_offset() {
+        assert_eq!(
+            pairs("{n, plural, offset:1 other {It''s '{'#'}' more}}"),
+            vec![("n:other".to_string(), "It's {#} more".to_string())]
+        );
+    }
+}
@@ -1,13 +1,23 @@
+pub mod android_parser;
+pub mod apple_strings_parser;
+pub mod fluent_parser;
 pub mod js_parser;
 pub mod json_parser;
 pub mod key_extractor;
+pub mod locale;
+pub mod message_format;
+pub mod po_parser;
 pub mod sitter;
 pub mod translation;
 pub mod yaml_parser;
 
+pub use android_parser::AndroidStringsParser;
+pub use apple_strings_parser::AppleStringsParser;
+pub use fluent_parser::FluentParser;
 pub use js_parser::JsParser;
 pub use json_parser::JsonParser;
-pub use key_extractor::KeyExtractor;
+pub use key_extractor::{KeyExtractor, ParsedFiles};
+pub use po_parser::PoParser;
 pub use sitter::Sitter;
 pub use translation::TranslationEntry;
 pub use yaml_parser::YamlParser;
@@ -0,0 +1,335 @@
+use crate::error::{Result, SearchError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::translation::TranslationEntry;
+
+/// Parser for gettext translation catalogs (`.po` and `.pot` files).
+///
+/// Each message becomes a `TranslationEntry` whose key is the `msgid` (the
+/// string passed to `_()`, `gettext()`, ... in code) and whose value is the
+/// translated `msgstr`. Plural messages yield one entry per plural form. A
+/// `msgctxt` is kept in the entry's `branch` (`msgctxt:menu`), so messages
+/// that share a msgid stay apart.
+/// Untranslated messages, including everything in a `.pot` template, use the
+/// source text as their value, since that is what the UI displays.
+pub struct PoParser;
+
+/// A message being assembled while reading the catalog
+#[derive(Default)]
+struct Message {
+    line: usize,
+    msgctxt: Option<String>,
+    msgid: Option<String>,
+    msgid_plural: Option<String>,
+    msgstr: Vec<String>,
+}
+
+/// The field that continuation lines (`"..."`) append to
+#[derive(Clone, Copy)]
+enum Field {
+    Context,
+    Id,
+    IdPlural,
+    Str(usize),
+}
+
+impl Message {
+    fn field_mut(&mut self, field: Field) -> &mut String {
+        match field {
+            Field::Context => self.msgctxt.get_or_insert_with(String::new),
+            Field::Id => self.msgid.get_or_insert_with(String::new),
+            Field::IdPlural => self.msgid_plural.get_or_insert_with(String::new),
+            Field::Str(index) => {
+                if self.msgstr.len() <= index {
+                    self.msgstr.resize(index + 1, String::new());
+                }
+                &mut self.msgstr[index]
+            }
+        }
+    }
+
+    fn into_entries(self, file: &Path, entries: &mut Vec<TranslationEntry>) {
+        let Some(msgid) = self.msgid else {
+            return;
+        };
+        // The header entry (empty msgid) holds catalog metadata
+        if msgid.is_empty() {
+            return;
+        }
+
+        let context = self.msgctxt.map(|context| format!("msgctxt:{}", context));
+        let forms = self.msgstr.len().max(1);
+        for form in 0..forms {
+            let translated = self.msgstr.get(form).filter(|s| !s.is_empty());
+            let source = match (form, &self.msgid_plural) {
+                (0, _) | (_, None) => &msgid,
+                (_, Some(plural)) => plural,
+            };
+            entries.push(TranslationEntry {
+                key: msgid.clone(),
+                value: translated.unwrap_or(source).clone(),
+                line: self.line,
+                file: PathBuf::from(file),
+                branch: match (&context, &self.msgid_plural) {
+                    (Some(context), Some(_)) => Some(format!("{} > msgstr[{}]", context, form)),
+                    (Some(context), None) => Some(context.clone()),
+                    (None, Some(_)) => Some(format!("msgstr[{}]", form)),
+                    (None, None) => None,
+                },
+                locale: None,
+            });
+        }
+    }
+}
+
+impl PoParser {
+    pub fn parse_file(path: &Path) -> Result<Vec<TranslationEntry>> {
+        Self::parse_file_with_query(path, None)
+    }
+
+    /// Parse a catalog, optionally keeping only entries whose value contains
+    /// `query` (case-insensitive)
+    pub fn parse_file_with_query(
+        path: &Path,
+        query: Option<&str>,
+    ) -> Result<Vec<TranslationEntry>> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            SearchError::po_parse_error(path, format!("Failed to read file: {}", e))
+        })?;
+
+        let mut entries = Self::parse_content(&content, path)?;
+
+        if let Some(q) = query {
+            let q_lower = q.to_lowercase();
+            entries.retain(|e| e.value.to_lowercase().contains(&q_lower));
+        }
+
+        Ok(entries)
+    }
+
+    /// Parse catalog text; `path` is recorded in the entries and errors
+    pub fn parse_content(content: &str, path: &Path) -> Result<Vec<TranslationEntry>> {
+        let mut entries = Vec::new();
+        let mut message = Message::default();
+        let mut field: Option<Field> = None;
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line_num = index + 1;
+            let line = raw_line.trim();
+
+            // Comments (translator, extracted, references, flags) and
+            // obsolete `#~` entries carry nothing we search for
+            if line.is_empty() || line.starts_with('#') {
+                if line.is_empty() {
+                    field = None;
+                }
+                continue;
+            }
+
+            if line.starts_with('"') {
+                let Some(current) = field else {
+                    return Err(SearchError::po_parse_error(
+                        path,
+                        format!("line {}: string without a keyword", line_num),
+                    ));
+                };
+                let text = Self::unquote(line, path, line_num)?;
+                message.field_mut(current).push_str(&text);
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(char::is_whitespace).ok_or_else(|| {
+                SearchError::po_parse_error(path, format!("line {}: missing string", line_num))
+            })?;
+
+            let next = match keyword {
+                "msgctxt" => Field::Context,
+                "msgid" => Field::Id,
+                "msgid_plural" => Field::IdPlural,
+                "msgstr" => Field::Str(0),
+                _ => match keyword
+                    .strip_prefix("msgstr[")
+                    .and_then(|k| k.strip_suffix(']'))
+                    .and_then(|n| n.parse().ok())
+                {
+                    Some(n) => Field::Str(n),
+                    None => {
+                        return Err(SearchError::po_parse_error(
+                            path,
+                            format!("line {}: unknown keyword '{}'", line_num, keyword),
+                        ))
+                    }
+                },
+            };
+
+            // A msgctxt, or a msgid following another msgid, starts the next
+            // message (blank lines between messages are optional)
+            let starts_message = match next {
+                Field::Context => true,
+                Field::Id => message.msgid.is_some() || !message.msgstr.is_empty(),
+                _ => false,
+            };
+            if starts_message {
+                std::mem::take(&mut message).into_entries(path, &mut entries);
+            }
+            if message.line == 0 {
+                message.line = line_num;
+            }
+
+            let text = Self::unquote(rest.trim(), path, line_num)?;
+            message.field_mut(next).push_str(&text);
+            field = Some(next);
+        }
+
+        message.into_entries(path, &mut entries);
+        Ok(entries)
+    }
+
+    /// Decode a C-style quoted string: `"Say \"hi\"\n"`
+    fn unquote(quoted: &str, path: &Path, line_num: usize) -> Result<String> {
+        let inner = quoted
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .ok_or_else(|| {
+                SearchError::po_parse_error(
+                    path,
+                    format!("line {}: expected a quoted string", line_num),
+                )
+            })?;
+
+        let mut result = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                result.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('r') => result.push('\r'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Vec<TranslationEntry> {
+        PoParser::parse_content(content, Path::new("fr.po")).unwrap()
+    }
+
+    #[test]
+    fn test_simple_messages() {
+        let entries = parse(
+            r#"msgid ""
+msgstr ""
+"Language: fr\n"
+
+#: app/views.py:12
+msgid "Save changes"
+msgstr "Enregistrer"
+
+msgid "Untranslated"
+msgstr ""
+"#,
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].key, "Save changes");
+        assert_eq!(entries[0].value, "Enregistrer");
+        assert_eq!(entries[0].line, 6);
+        // Untranslated messages fall back to the source text
+        assert_eq!(entries[1].value, "Untranslated");
+    }
+
+    #[test]
+    fn test_multiline_and_escapes() {
+        let entries = parse(
+            "msgid \"\"\n\"Say \\\"hi\\\" \"\n\"to everyone\"\nmsgstr \"Dites \\\"salut\\\"\"\n",
+        );
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "Say \"hi\" to everyone");
+        assert_eq!(entries[0].value, "Dites \"salut\"");
+        assert_eq!(entries[0].line, 1);
+    }
+
+    #[test]
+    fn test_plural_forms() {
+        let entries = parse(
+            r#"msgid "%d file"
+msgid_plural "%d files"
+msgstr[0] "%d fichier"
+msgstr[1] "%d fichiers"
+"#,
+        );
+
+        let values: Vec<_> = entries.iter().map(|e| e.value.as_str()).collect();
+        assert_eq!(values, vec!["%d fichier", "%d fichiers"]);
+        assert!(entries.iter().all(|e| e.key == "%d file"));
+        assert_eq!(entries[1].branch.as_deref(), Some("msgstr[1]"));
+    }
+
+    #[test]
+    fn test_template_plural_uses_source_text() {
+        let entries =
+            parse("msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n");
+
+        let values: Vec<_> = entries.iter().map(|e| e.value.as_str()).collect();
+        assert_eq!(values, vec!["%d file", "%d files"]);
+    }
+
+    #[test]
+    fn test_context_starts_new_message() {
+        let entries = parse(
+            r#"msgctxt "menu"
+msgid "Open"
+msgstr "Ouvrir"
+msgctxt "door"
+msgid "Open"
+msgstr "Ouvert"
+"#,
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].value, "Ouvrir");
+        assert_eq!(entries[0].line, 1);
+        assert_eq!(entries[1].value, "Ouvert");
+        assert_eq!(entries[1].line, 4);
+
+        // Both share the msgid, so the context tells them apart
+        assert!(entries.iter().all(|e| e.key == "Open"));
+        assert_eq!(entries[0].branch.as_deref(), Some("msgctxt:menu"));
+        assert_eq!(entries[1].branch.as_deref(), Some("msgctxt:door"));
+    }
+
+    #[test]
+    fn test_context_of_plural_message() {
+        let entries = parse(
+            r#"msgctxt "inbox"
+msgid "%d message"
+msgid_plural "%d messages"
+msgstr[0] "%d message"
+msgstr[1] "%d messages"
+"#,
+        );
+
+        assert_eq!(
+            entries[1].branch.as_deref(),
+            Some("msgctxt:inbox > msgstr[1]")
+        );
+    }
+
+    #[test]
+    fn test_invalid_keyword() {
+        let err = PoParser::parse_content("msgfoo \"x\"\n", Path::new("bad.po")).unwrap_err();
+        assert!(err.to_string().contains("unknown keyword"));
+    }
+}
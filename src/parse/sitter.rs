@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
-use tree_sitter::{Language, Parser, Query, QueryCursor};
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor};
 
 /// Supported languages for Tree-sitter parsing
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -12,10 +12,39 @@ pub enum SupportedLanguage {
     TypeScript,
     Ruby,
     CSharp,
+    Go,
+    Java,
     // Erb, // Temporarily disabled due to tree-sitter version conflict
 }
 
 impl SupportedLanguage {
+    /// Language names accepted by [`SupportedLanguage::from_name`]
+    pub const NAMES: &'static [&'static str] = &[
+        "rust",
+        "python",
+        "javascript",
+        "typescript",
+        "ruby",
+        "csharp",
+        "go",
+        "java",
+    ];
+
+    /// Parse a language name as written in `.cs.toml`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" => Some(Self::Rust),
+            "python" => Some(Self::Python),
+            "javascript" | "js" => Some(Self::JavaScript),
+            "typescript" | "ts" => Some(Self::TypeScript),
+            "ruby" => Some(Self::Ruby),
+            "csharp" | "c#" => Some(Self::CSharp),
+            "go" | "golang" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            _ => None,
+        }
+    }
+
     pub fn from_path(path: &Path) -> Option<Self> {
         match path.extension()?.to_str()? {
             "rs" => Some(Self::Rust),
@@ -24,6 +53,8 @@ impl SupportedLanguage {
             "ts" | "tsx" => Some(Self::TypeScript),
             "rb" => Some(Self::Ruby),
             "cs" => Some(Self::CSharp),
+            "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
             // "erb" => Some(Self::Erb), // Temporarily disabled
             _ => None,
         }
@@ -37,6 +68,8 @@ impl SupportedLanguage {
             Self::TypeScript => tree_sitter_typescript::language_typescript(),
             Self::Ruby => tree_sitter_ruby::language(),
             Self::CSharp => tree_sitter_c_sharp::language(),
+            Self::Go => tree_sitter_go::language(),
+            Self::Java => tree_sitter_java::language(),
             // Self::Erb => tree_sitter_embedded_template::language(),
         }
     }
@@ -44,8 +77,10 @@ impl SupportedLanguage {
 
 /// Sitter handles Tree-sitter parsing for multiple languages
 pub struct Sitter {
+    extension_map: HashMap<String, SupportedLanguage>,
     parsers: HashMap<SupportedLanguage, Parser>,
     queries: HashMap<SupportedLanguage, Query>,
+    call_queries: HashMap<SupportedLanguage, Query>,
 }
 
 impl Default for Sitter {
@@ -57,14 +92,34 @@ impl Default for Sitter {
 impl Sitter {
     pub fn new() -> Self {
         Self {
+            extension_map: HashMap::new(),
             parsers: HashMap::new(),
             queries: HashMap::new(),
+            call_queries: HashMap::new(),
         }
     }
 
+    /// Parse extra file extensions with the given languages (e.g. `mjs` as
+    /// JavaScript); these take precedence over the built-in mapping
+    pub fn with_language_map(mut self, map: HashMap<String, SupportedLanguage>) -> Self {
+        self.extension_map = map;
+        self
+    }
+
+    /// The language used to parse `path`, if any
+    pub fn language_for(&self, path: &Path) -> Option<SupportedLanguage> {
+        let mapped = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.extension_map.get(ext));
+        mapped
+            .copied()
+            .or_else(|| SupportedLanguage::from_path(path))
+    }
+
     /// Check if the file at the given path is supported by Tree-sitter
     pub fn is_supported(&self, path: &Path) -> bool {
-        SupportedLanguage::from_path(path).is_some()
+        self.language_for(path).is_some()
     }
 
     /// Get or create a parser for the given language
@@ -116,6 +171,18 @@ impl Sitter {
                     (method_declaration name: (identifier) @name)
                     (local_function_statement name: (identifier) @name)
                 "#
+                }
+                SupportedLanguage::Go => {
+                    r#"
+                    (function_declaration name: (identifier) @name)
+                    (method_declaration name: (field_identifier) @name)
+                "#
+                }
+                SupportedLanguage::Java => {
+                    r#"
+                    (method_declaration name: (identifier) @name)
+                    (constructor_declaration name: (identifier) @name)
+                "#
                 } // SupportedLanguage::Erb => "", // ERB usually doesn't define functions
             };
 
@@ -126,9 +193,286 @@ impl Sitter {
         Ok(self.queries.get(&lang).unwrap())
     }
 
+    /// Get or create the query matching call expressions for the given language.
+    ///
+    /// Every pattern captures the called name as `@callee`: plain calls,
+    /// method/field calls and path-qualified calls.
+    fn get_call_query(&mut self, lang: SupportedLanguage) -> Result<&Query> {
+        if let std::collections::hash_map::Entry::Vacant(e) = self.call_queries.entry(lang) {
+            let query_str = match lang {
+                SupportedLanguage::Rust => {
+                    r#"
+                    (call_expression function: (identifier) @callee)
+                    (call_expression function: (field_expression field: (field_identifier) @callee))
+                    (call_expression function: (scoped_identifier name: (identifier) @callee))
+                    (call_expression function: (generic_function function: (identifier) @callee))
+                "#
+                }
+                SupportedLanguage::Python => {
+                    r#"
+                    (call function: (identifier) @callee)
+                    (call function: (attribute attribute: (identifier) @callee))
+                "#
+                }
+                SupportedLanguage::JavaScript | SupportedLanguage::TypeScript => {
+                    r#"
+                    (call_expression function: (identifier) @callee)
+                    (call_expression function: (member_expression property: (property_identifier) @callee))
+                "#
+                }
+                SupportedLanguage::Ruby => {
+                    r#"
+                    (call method: (identifier) @callee)
+                "#
+                }
+                SupportedLanguage::CSharp => {
+                    r#"
+                    (invocation_expression function: (identifier) @callee)
+                    (invocation_expression function: (member_access_expression name: (identifier) @callee))
+                    (invocation_expression function: (generic_name (identifier) @callee))
+                "#
+                }
+                SupportedLanguage::Go => {
+                    r#"
+                    (call_expression function: (identifier) @callee)
+                    (call_expression function: (selector_expression field: (field_identifier) @callee))
+                "#
+                }
+                SupportedLanguage::Java => {
+                    r#"
+                    (method_invocation name: (identifier) @callee)
+                "#
+                }
+            };
+
+            let query = Query::new(lang.language(), query_str)
+                .map_err(|e| anyhow::anyhow!("Failed to create call query: {:?}", e))?;
+            e.insert(query);
+        }
+        Ok(self.call_queries.get(&lang).unwrap())
+    }
+
+    /// Find the names called from the body of the function `name` whose name
+    /// appears on (1-based) `line`.
+    ///
+    /// The body range comes from the function's syntax node, so nested
+    /// closures are included while strings, comments and code after the
+    /// function are not. Returns `Ok(None)` for unsupported languages or when
+    /// no such function exists in the file.
+    pub fn find_calls_in_function(
+        &mut self,
+        path: &Path,
+        code: &str,
+        name: &str,
+        line: usize,
+    ) -> Result<Option<Vec<String>>> {
+        let lang = match self.language_for(path) {
+            Some(l) => l,
+            None => return Ok(None),
+        };
+
+        let parser = self.get_parser(lang)?;
+        let tree = parser.parse(code, None).context("Failed to parse code")?;
+
+        // Make sure both queries exist before borrowing them together
+        self.get_query(lang)?;
+        self.get_call_query(lang)?;
+        let query = &self.queries[&lang];
+        let call_query = &self.call_queries[&lang];
+
+        let name_idx = query.capture_index_for_name("name").unwrap_or(0);
+        let mut cursor = QueryCursor::new();
+        let function_node = cursor
+            .matches(query, tree.root_node(), code.as_bytes())
+            .flat_map(|m| m.captures.to_vec())
+            .filter(|c| c.index == name_idx && c.node.start_position().row + 1 == line)
+            .find(|c| c.node.utf8_text(code.as_bytes()).is_ok_and(|t| t == name))
+            .and_then(|c| Self::function_node(c.node));
+
+        let Some(function_node) = function_node else {
+            return Ok(None);
+        };
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(function_node.byte_range());
+        let mut calls = BTreeSet::new();
+        for m in cursor.matches(call_query, function_node, code.as_bytes()) {
+            for capture in m.captures {
+                calls.insert(capture.node.utf8_text(code.as_bytes())?.to_string());
+            }
+        }
+
+        Ok(Some(calls.into_iter().collect()))
+    }
+
+    /// Find every call in the file, with the innermost named function
+    /// containing it.
+    ///
+    /// Only call expressions in the syntax tree count, so names followed by
+    /// parentheses inside strings and comments are not calls. Returns an
+    /// empty list for unsupported languages.
+    pub fn find_calls(&mut self, path: &Path, code: &str) -> Result<Vec<CallMatch>> {
+        let lang = match self.language_for(path) {
+            Some(l) => l,
+            None => return Ok(Vec::new()),
+        };
+
+        let parser = self.get_parser(lang)?;
+        let tree = parser.parse(code, None).context("Failed to parse code")?;
+
+        self.get_query(lang)?;
+        self.get_call_query(lang)?;
+        let query = &self.queries[&lang];
+        let call_query = &self.call_queries[&lang];
+
+        let name_idx = query.capture_index_for_name("name").unwrap_or(0);
+        let mut cursor = QueryCursor::new();
+        let mut functions = Vec::new();
+        for m in cursor.matches(query, tree.root_node(), code.as_bytes()) {
+            for capture in m.captures.iter().filter(|c| c.index == name_idx) {
+                let range = Self::function_node(capture.node)
+                    .unwrap_or(capture.node)
+                    .byte_range();
+                functions.push((range, capture.node.utf8_text(code.as_bytes())?));
+            }
+        }
+
+        let mut cursor = QueryCursor::new();
+        let mut seen = BTreeSet::new();
+        let mut calls = Vec::new();
+        for m in cursor.matches(call_query, tree.root_node(), code.as_bytes()) {
+            for capture in m.captures {
+                let callee = capture.node.utf8_text(code.as_bytes())?;
+                let line = capture.node.start_position().row + 1;
+                if !seen.insert((callee, line)) {
+                    continue;
+                }
+                let at = capture.node.start_byte();
+                let caller = functions
+                    .iter()
+                    .filter(|(range, _)| range.contains(&at))
+                    .min_by_key(|(range, _)| range.len())
+                    .map(|(_, name)| name.to_string());
+                calls.push(CallMatch {
+                    callee: callee.to_string(),
+                    caller,
+                    line,
+                });
+            }
+        }
+
+        Ok(calls)
+    }
+
+    /// The definition node owning a function name capture (the name's parent:
+    /// `function_item`, `method`, `variable_declarator` for arrow functions, ...)
+    fn function_node(name_node: Node) -> Option<Node> {
+        name_node.parent()
+    }
+
+    /// Names of the classes, modules, impl blocks, namespaces and packages
+    /// around a function name capture (and a Go method's receiver type),
+    /// outermost first
+    fn enclosing_scope(lang: SupportedLanguage, name_node: Node, code: &str) -> Vec<String> {
+        let text = |node: Node| node.utf8_text(code.as_bytes()).unwrap_or_default();
+        let mut scope = Vec::new();
+
+        // `func (s *Server[T]) Start()` is scoped by `Server`
+        let receiver = Self::function_node(name_node)
+            .filter(|node| lang == SupportedLanguage::Go && node.kind() == "method_declaration")
+            .and_then(|node| node.child_by_field_name("receiver"))
+            .and_then(|receiver| receiver.named_child(0))
+            .and_then(|parameter| parameter.child_by_field_name("type"));
+        if let Some(receiver) = receiver {
+            let name = text(receiver).trim_start_matches('*');
+            scope.push(name.split('[').next().unwrap_or_default().to_string());
+        }
+
+        let mut ancestor = Self::function_node(name_node).and_then(|n| n.parent());
+        while let Some(node) = ancestor {
+            let scope_name = match (lang, node.kind()) {
+                // `impl<T> fmt::Display for Wrapper<T>` is scoped by `Wrapper`
+                (SupportedLanguage::Rust, "impl_item") => node
+                    .child_by_field_name("type")
+                    .map(|ty| text(ty).split('<').next().unwrap_or_default())
+                    .and_then(|path| path.rsplit("::").next()),
+                (SupportedLanguage::Rust, "mod_item" | "trait_item")
+                | (SupportedLanguage::Python, "class_definition")
+                | (
+                    SupportedLanguage::JavaScript | SupportedLanguage::TypeScript,
+                    "class_declaration"
+                    | "class"
+                    | "abstract_class_declaration"
+                    | "internal_module"
+                    | "module",
+                )
+                | (SupportedLanguage::Ruby, "class" | "module")
+                | (
+                    SupportedLanguage::CSharp,
+                    "class_declaration"
+                    | "struct_declaration"
+                    | "interface_declaration"
+                    | "record_declaration"
+                    | "namespace_declaration",
+                )
+                | (
+                    SupportedLanguage::Java,
+                    "class_declaration"
+                    | "interface_declaration"
+                    | "enum_declaration"
+                    | "record_declaration",
+                ) => node.child_by_field_name("name").map(text),
+                _ => None,
+            };
+            if let Some(scope_name) = scope_name {
+                // Qualified names (`Billing::Invoice`, `Acme.Billing`) are
+                // several scopes at once
+                let segments = scope_name.split("::").flat_map(|s| s.split('.'));
+                scope.splice(0..0, segments.map(str::to_string));
+            }
+            ancestor = node.parent();
+        }
+
+        // `namespace Acme.Billing;` and Go and Java packages scope the rest
+        // of the file without being an ancestor of its declarations
+        let mut cursor = name_node.walk();
+        let root = {
+            let mut node = name_node;
+            while let Some(parent) = node.parent() {
+                node = parent;
+            }
+            node
+        };
+        let file_scope = root
+            .children(&mut cursor)
+            .find_map(|child| match (lang, child.kind()) {
+                (SupportedLanguage::CSharp, "file_scoped_namespace_declaration") => {
+                    child.child_by_field_name("name")
+                }
+                (SupportedLanguage::Go, "package_clause")
+                | (SupportedLanguage::Java, "package_declaration") => {
+                    let mut cursor = child.walk();
+                    let name = child.named_children(&mut cursor).find(|name| {
+                        matches!(
+                            name.kind(),
+                            "package_identifier" | "identifier" | "scoped_identifier"
+                        )
+                    });
+                    name
+                }
+                _ => None,
+            });
+        if let Some(file_scope) = file_scope {
+            scope.splice(0..0, text(file_scope).split('.').map(str::to_string));
+        }
+
+        scope.retain(|segment| !segment.is_empty());
+        scope
+    }
+
     /// Find function definitions in the given file
     pub fn find_functions(&mut self, path: &Path, code: &str) -> Result<Vec<FunctionMatch>> {
-        let lang = match SupportedLanguage::from_path(path) {
+        let lang = match self.language_for(path) {
             Some(l) => l,
             None => return Ok(Vec::new()), // Unsupported language
         };
@@ -147,14 +491,21 @@ impl Sitter {
         for m in matches {
             for capture in m.captures {
                 if capture.index == name_idx {
-                    let range = capture.node.range();
-                    let start_line = range.start_point.row + 1; // 1-based
-                    let end_line = range.end_point.row + 1;
+                    let start_line = capture.node.start_position().row + 1; // 1-based
+
+                    // The body ends where the whole definition node ends
+                    let end_line = Self::function_node(capture.node)
+                        .unwrap_or(capture.node)
+                        .end_position()
+                        .row
+                        + 1;
 
                     let name = capture.node.utf8_text(code.as_bytes())?.to_string();
+                    let scope = Self::enclosing_scope(lang, capture.node, code);
 
                     functions.push(FunctionMatch {
                         name,
+                        scope,
                         start_line,
                         end_line,
                     });
@@ -169,6 +520,21 @@ impl Sitter {
 #[derive(Debug)]
 pub struct FunctionMatch {
     pub name: String,
+    /// Enclosing classes, modules, impl blocks and namespaces, outermost first
+    pub scope: Vec<String>,
+    /// Line of the function name (1-based)
     pub start_line: usize,
+    /// Last line of the function definition, including its body (1-based)
     pub end_line: usize,
 }
+
+/// A call found by [`Sitter::find_calls`]
+#[derive(Debug)]
+pub struct CallMatch {
+    /// The called name
+    pub callee: String,
+    /// Innermost named function containing the call, `None` at the top level
+    pub caller: Option<String>,
+    /// Line of the called name (1-based)
+    pub line: usize,
+}
@@ -12,4 +12,12 @@ pub struct TranslationEntry {
     pub line: usize,
     /// The file path where this entry was found
     pub file: PathBuf,
+    /// For plural/select messages, the arm(s) whose text this entry holds or
+    /// matched (e.g. "count:other", "$unreadEmails:one")
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// The locale the entry belongs to (e.g. "en", "pt-BR"), from the file's
+    /// root key (Rails `en:`), its catalog or its path, when known
+    #[serde(default)]
+    pub locale: Option<String>,
 }
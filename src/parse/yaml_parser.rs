@@ -4,6 +4,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use yaml_rust::{Yaml, YamlLoader};
 
+use super::locale::{is_locale_code, is_locale_file, is_locale_file_in};
+use super::message_format::contains_ignore_case;
 use super::translation::TranslationEntry;
 
 /// Parser for YAML translation files
@@ -49,6 +51,13 @@ impl YamlParser {
         Self::parse_file_with_query(path, None)
     }
 
+    /// Like [`parse_file`](Self::parse_file), also treating the directory
+    /// names in `locale_dirs` as locale directories when deciding whether a
+    /// top-level `en:` is a locale root
+    pub fn parse_file_in(path: &Path, locale_dirs: &[String]) -> Result<Vec<TranslationEntry>> {
+        Self::parse(path, None, is_locale_file_in(path, locale_dirs))
+    }
+
     /// Parse YAML file, optionally filtering by query for better performance.
     /// If query is provided, uses bottom-up approach: finds exact matches with grep,
     /// then traces keys upward WITHOUT parsing the entire YAML structure.
@@ -56,6 +65,12 @@ impl YamlParser {
         path: &Path,
         query: Option<&str>,
     ) -> Result<Vec<TranslationEntry>> {
+        Self::parse(path, query, is_locale_file(path))
+    }
+
+    /// Locale roots are only stripped from `locale_file`s: `id:` or `no:` at
+    /// the top of an ordinary config file are keys, not locales
+    fn parse(path: &Path, query: Option<&str>, locale_file: bool) -> Result<Vec<TranslationEntry>> {
         let content = fs::read_to_string(path).map_err(|e| {
             SearchError::yaml_parse_error(path, format!("Failed to read file: {}", e))
         })?;
@@ -85,13 +100,32 @@ impl YamlParser {
 
         let mut entries = Vec::new();
         for doc in docs {
-            Self::flatten_yaml(doc, String::new(), path, &value_to_line, &mut entries, true);
+            match doc {
+                // Rails files nest everything under the locale (`en:`), which
+                // code never mentions: strip it and record it on the entries
+                Yaml::Hash(hash) if locale_file && Self::is_locale_rooted(&hash) => {
+                    for (locale, value) in hash {
+                        let start = entries.len();
+                        Self::flatten_yaml(
+                            value,
+                            String::new(),
+                            path,
+                            &value_to_line,
+                            &mut entries,
+                        );
+                        for entry in &mut entries[start..] {
+                            entry.locale = locale.as_str().map(str::to_string);
+                        }
+                    }
+                }
+                doc => Self::flatten_yaml(doc, String::new(), path, &value_to_line, &mut entries),
+            }
         }
 
         // Filter by query if provided (since bottom-up trace is disabled)
         if let Some(q) = query {
             let q_lower = q.to_lowercase();
-            entries.retain(|e| e.value.to_lowercase().contains(&q_lower));
+            entries.retain(|e| contains_ignore_case(&e.value, &q_lower));
         }
 
         Ok(entries)
@@ -139,46 +173,32 @@ impl YamlParser {
         result
     }
 
+    /// Whether every top-level key is a locale code (`en`, `pt-BR`, `zh_Hant`)
+    /// holding a mapping, as in Rails locale files
+    fn is_locale_rooted(hash: &yaml_rust::yaml::Hash) -> bool {
+        !hash.is_empty()
+            && hash.iter().all(|(key, value)| {
+                key.as_str().is_some_and(is_locale_code) && matches!(value, Yaml::Hash(_))
+            })
+    }
+
     fn flatten_yaml(
         yaml: Yaml,
         prefix: String,
         file_path: &Path,
         value_to_line: &HashMap<String, usize>,
         entries: &mut Vec<TranslationEntry>,
-        is_root: bool,
     ) {
         match yaml {
             Yaml::Hash(hash) => {
                 for (key, value) in hash {
                     if let Some(key_str) = key.as_str() {
-                        // Check if this is a locale root BEFORE building prefix
-                        let is_locale_root = is_root
-                            && prefix.is_empty()
-                            && (key_str == "en"
-                                || key_str == "fr"
-                                || key_str == "de"
-                                || key_str == "es"
-                                || key_str == "ja"
-                                || key_str == "zh");
-
-                        // For locale roots, skip the locale prefix entirely
-                        let new_prefix = if is_locale_root {
-                            String::new()
-                        } else if prefix.is_empty() {
+                        let new_prefix = if prefix.is_empty() {
                             key_str.to_string()
                         } else {
                             format!("{}.{}", prefix, key_str)
                         };
-
-                        // Only flatten once, not twice!
-                        Self::flatten_yaml(
-                            value,
-                            new_prefix,
-                            file_path,
-                            value_to_line,
-                            entries,
-                            false,
-                        );
+                        Self::flatten_yaml(value, new_prefix, file_path, value_to_line, entries);
                     }
                 }
             }
@@ -190,6 +210,8 @@ impl YamlParser {
                     value,
                     line,
                     file: PathBuf::from(file_path),
+                    branch: None,
+                    locale: None,
                 });
             }
             Yaml::Integer(value) => {
@@ -201,6 +223,8 @@ impl YamlParser {
                     value: value_str,
                     line,
                     file: PathBuf::from(file_path),
+                    branch: None,
+                    locale: None,
                 });
             }
             Yaml::Boolean(value) => {
@@ -212,6 +236,8 @@ impl YamlParser {
                     value: value_str,
                     line,
                     file: PathBuf::from(file_path),
+                    branch: None,
+                    locale: None,
                 });
             }
             Yaml::Array(arr) => {
@@ -221,7 +247,7 @@ impl YamlParser {
                     } else {
                         format!("{}.{}", prefix, index)
                     };
-                    Self::flatten_yaml(val, new_prefix, file_path, value_to_line, entries, false);
+                    Self::flatten_yaml(val, new_prefix, file_path, value_to_line, entries);
                 }
             }
             _ => {
@@ -235,7 +261,7 @@ impl YamlParser {
 mod tests {
     use super::*;
     use std::io::Write;
-    use tempfile::NamedTempFile;
+    use tempfile::{tempdir, NamedTempFile};
 
     #[test]
     fn test_parse_simple_yaml() {
@@ -309,22 +335,66 @@ nested:
 
     #[test]
     fn test_bottom_up_trace() {
-        let mut file = NamedTempFile::new().unwrap();
-        write!(
-            file,
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("en.yml");
+        fs::write(
+            &path,
             "en:
   js:
     user:
       log_in: \"Log In\"
       sign_up: \"Sign Up\"
-"
+",
         )
         .unwrap();
 
-        let entries = YamlParser::parse_file_with_query(file.path(), Some("Log In")).unwrap();
+        let entries = YamlParser::parse_file_with_query(&path, Some("Log In")).unwrap();
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].key, "js.user.log_in");
         assert_eq!(entries[0].value, "Log In");
         assert_eq!(entries[0].line, 4);
     }
+
+    #[test]
+    fn test_locale_root_is_stripped_and_recorded() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("pt-BR.yml");
+        fs::write(&path, "pt-BR:\n  invoice:\n    add_new: \"Adicionar\"\n").unwrap();
+
+        let entries = YamlParser::parse_file(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "invoice.add_new");
+        assert_eq!(entries[0].locale.as_deref(), Some("pt-BR"));
+    }
+
+    #[test]
+    fn test_non_locale_root_is_kept() {
+        // `id` is a language code, but `title` isn't, so this is no locale root
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "id:\n  name: \"Identifier\"\ntitle: \"Title\"\n").unwrap();
+
+        let entries = YamlParser::parse_file(file.path()).unwrap();
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["id.name", "title"]);
+        assert!(entries.iter().all(|e| e.locale.is_none()));
+    }
+
+    #[test]
+    fn test_locale_root_is_kept_outside_locale_files() {
+        // Language-code keys at the top of a config file are not locales
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("config")).unwrap();
+        let path = dir.path().join("config/features.yml");
+        fs::write(&path, "es:\n  search: true\nno:\n  search: false\n").unwrap();
+
+        let entries = YamlParser::parse_file(&path).unwrap();
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["es.search", "no.search"]);
+        assert!(entries.iter().all(|e| e.locale.is_none()));
+
+        // Unless `.cs.toml` names the directory as a locale directory
+        let entries = YamlParser::parse_file_in(&path, &["config".to_string()]).unwrap();
+        assert_eq!(entries[0].key, "search");
+        assert_eq!(entries[0].locale.as_deref(), Some("es"));
+    }
 }
@@ -0,0 +1,29 @@
+//! Cooperative cancellation for long-running searches.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag shared between a search and whoever may want to stop it.
+///
+/// Clones share the same flag. Walkers check it before every file, so a
+/// cancelled search stops within one file per thread and reports
+/// [`SearchError::Cancelled`](crate::SearchError::Cancelled).
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every search holding this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
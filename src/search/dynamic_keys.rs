@@ -0,0 +1,359 @@
+//! Detection of translation keys that are computed at runtime.
+//!
+//! The i18n patterns only see keys written out in full, such as
+//! `t('invoice.add')`. Code often builds the key instead:
+//!
+//! ```text
+//! t(`invoice.${status}.label`)         // template literal
+//! t("invoice.#{status}.label")         // Ruby interpolation
+//! t('invoice.' + kind)                 // concatenation
+//! const KEY = 'invoice.add'; t(KEY)    // constant
+//! ```
+//!
+//! [`DynamicKeyFinder`] recognises such calls and describes each one as a
+//! [`DynamicUsage`]: the shape of the key, with the parts only known at
+//! runtime written as `*` (`invoice.*.label`), and a [`Confidence`] saying how
+//! likely it is to use a key of that shape.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Calls whose first argument is a translation key
+pub const DYNAMIC_CALL_PATTERN: &str = r"(?:\b(?:I18n|i18n)\.t|\$t|\bt)\(";
+
+/// How sure a [`CodeReference`](super::CodeReference) is that the code uses
+/// its key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// The key is written out in full (or is a resolved lazy lookup)
+    #[default]
+    Exact,
+    /// The key is a string constant defined in the same file
+    High,
+    /// Only part of the key is known, e.g. the prefix of a template
+    Low,
+}
+
+impl Confidence {
+    /// Whether this is a possible rather than a certain usage
+    pub fn is_possible(self) -> bool {
+        self != Confidence::Exact
+    }
+}
+
+/// A translation call whose key is computed at runtime
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicUsage {
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// The key with its runtime parts as `*`, e.g. `invoice.*.label`
+    pub shape: String,
+    pub confidence: Confidence,
+}
+
+impl DynamicUsage {
+    /// Whether `key` is one of the keys this call can produce
+    pub fn matches(&self, key: &str) -> bool {
+        key_matches_shape(&self.shape, key)
+    }
+}
+
+/// Whether `key` fits `shape`, where each `*` stands for one or more
+/// characters
+pub fn key_matches_shape(shape: &str, key: &str) -> bool {
+    let mut parts = shape.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = key.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        // The wildcard before `part` takes at least one character
+        let Some(found) = rest.get(1..).and_then(|r| r.find(part)) else {
+            return false;
+        };
+        rest = &rest[1 + found + part.len()..];
+    }
+    rest.len() > last.len() && rest.ends_with(last)
+}
+
+/// One piece of a key expression
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    /// Only known at runtime
+    Unknown,
+}
+
+/// Finds [`DynamicUsage`]s in source files
+#[derive(Debug, Clone)]
+pub struct DynamicKeyFinder {
+    call: Regex,
+    constant: Regex,
+}
+
+impl DynamicKeyFinder {
+    pub fn new() -> Self {
+        Self {
+            call: Regex::new(DYNAMIC_CALL_PATTERN).unwrap(),
+            // `const KEY = 'a.b'`, `KEY = "a.b".freeze`, `val key: String = "a.b"`
+            constant: Regex::new(
+                r#"(?m)^\s*(?:export\s+)?(?:(?:const|let|var|val|final|static)\s+)*([A-Za-z_$][\w$]*)\s*(?::\s*\w+\s*)?=\s*(?:'([^'\s]+)'|"([^"\s]+)")(?:\.freeze)?\s*;?\s*$"#,
+            )
+            .unwrap(),
+        }
+    }
+
+    /// Every computed-key call in `content`, in line order.
+    ///
+    /// Calls with a plain literal key are left to the i18n patterns, and
+    /// calls whose key is entirely unknown (`t(key)` with `key` a parameter)
+    /// are skipped, since they could use any key.
+    pub fn find(&self, content: &str) -> Vec<DynamicUsage> {
+        if !content.contains("t(") {
+            return Vec::new();
+        }
+        let constants = self.constants(content);
+
+        let mut usages = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            for call in self.call.find_iter(line) {
+                if let Some((shape, confidence)) = key_shape(&line[call.end()..], &constants) {
+                    usages.push(DynamicUsage {
+                        line: index + 1,
+                        shape,
+                        confidence,
+                    });
+                }
+            }
+        }
+        usages
+    }
+
+    /// String constants assigned exactly once in `content`
+    fn constants<'a>(&self, content: &'a str) -> HashMap<&'a str, Option<&'a str>> {
+        let mut constants: HashMap<&str, Option<&str>> = HashMap::new();
+        for captures in self.constant.captures_iter(content) {
+            let name = captures.get(1).unwrap().as_str();
+            let value = captures
+                .get(2)
+                .or_else(|| captures.get(3))
+                .unwrap()
+                .as_str();
+            constants
+                .entry(name)
+                // Reassigned with a different value: no longer known
+                .and_modify(|known| {
+                    if *known != Some(value) {
+                        *known = None;
+                    }
+                })
+                .or_insert(Some(value));
+        }
+        constants
+    }
+}
+
+impl Default for DynamicKeyFinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The shape of the key passed as the first argument in `args` (the text
+/// after the call's opening parenthesis), if it is computed
+fn key_shape(args: &str, constants: &HashMap<&str, Option<&str>>) -> Option<(String, Confidence)> {
+    let mut chars = args.char_indices().peekable();
+    let mut pieces = Vec::new();
+    let mut operands = 0;
+    let mut used_constant = false;
+
+    loop {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let &(start, first) = chars.peek()?;
+        operands += 1;
+        match first {
+            '\'' | '"' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    let (_, c) = chars.next()?;
+                    match c {
+                        '\\' => text.push(chars.next()?.1),
+                        c if c == first => break,
+                        // `${...}` in templates, `#{...}` in Ruby strings
+                        '$' | '#'
+                            if (first == '`') == (c == '$')
+                                && first != '\''
+                                && chars.next_if(|(_, c)| *c == '{').is_some() =>
+                        {
+                            skip_braces(&mut chars)?;
+                            if !text.is_empty() {
+                                pieces.push(Piece::Text(std::mem::take(&mut text)));
+                            }
+                            pieces.push(Piece::Unknown);
+                        }
+                        c => text.push(c),
+                    }
+                }
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(text));
+                }
+            }
+            _ => {
+                // Any other expression, up to the next top-level `+`, `,` or `)`
+                let mut depth = 0usize;
+                let mut end = args.len();
+                while let Some(&(i, c)) = chars.peek() {
+                    match c {
+                        '(' | '[' | '{' => depth += 1,
+                        ')' | ']' | '}' if depth > 0 => depth -= 1,
+                        '+' | ',' | ')' if depth == 0 => {
+                            end = i;
+                            break;
+                        }
+                        _ => {}
+                    }
+                    chars.next();
+                }
+                let expression = args[start..end].trim();
+                match constants.get(expression).copied().flatten() {
+                    Some(value) => {
+                        used_constant = true;
+                        pieces.push(Piece::Text(value.to_string()));
+                    }
+                    None => pieces.push(Piece::Unknown),
+                }
+            }
+        }
+
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        match chars.next()?.1 {
+            '+' => continue,
+            ',' | ')' => break,
+            // Ternaries, method calls on the key and the like aren't followed
+            _ => return None,
+        }
+    }
+
+    let known = pieces.iter().any(|piece| match piece {
+        Piece::Text(text) => text.chars().any(char::is_alphanumeric),
+        Piece::Unknown => false,
+    });
+    let computed = operands > 1 || used_constant || pieces.contains(&Piece::Unknown);
+    if !known || !computed {
+        return None;
+    }
+
+    let confidence = if pieces.contains(&Piece::Unknown) {
+        Confidence::Low
+    } else {
+        Confidence::High
+    };
+    let mut shape = String::new();
+    for piece in &pieces {
+        match piece {
+            Piece::Text(text) => shape.push_str(text),
+            Piece::Unknown if shape.ends_with('*') => {}
+            Piece::Unknown => shape.push('*'),
+        }
+    }
+    Some((shape, confidence))
+}
+
+/// Skip to just past the `}` closing an interpolation whose `{` was consumed
+fn skip_braces(chars: &mut std::iter::Peekable<std::str::CharIndices>) -> Option<()> {
+    let mut depth = 1;
+    while depth > 0 {
+        match chars.next()?.1 {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shapes(content: &str) -> Vec<(usize, String, Confidence)> {
+        DynamicKeyFinder::new()
+            .find(content)
+            .into_iter()
+            .map(|u| (u.line, u.shape, u.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn test_templates_and_concatenation() {
+        let content = r#"const a = t(`invoice.${status}.label`);
+const b = i18n.t('invoice.' + kind);
+const c = t('invoice.' + kind + '.title', { count: 2 });
+<%= t("invoice.#{kind}.label") %>
+"#;
+        assert_eq!(
+            shapes(content),
+            vec![
+                (1, "invoice.*.label".to_string(), Confidence::Low),
+                (2, "invoice.*".to_string(), Confidence::Low),
+                (3, "invoice.*.title".to_string(), Confidence::Low),
+                (4, "invoice.*.label".to_string(), Confidence::Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constants_are_propagated() {
+        let content = r#"const KEY = 'invoice.add';
+const PREFIX = "invoice.labels.";
+label(t(KEY));
+other(t(PREFIX + name));
+"#;
+        assert_eq!(
+            shapes(content),
+            vec![
+                (3, "invoice.add".to_string(), Confidence::High),
+                (4, "invoice.labels.*".to_string(), Confidence::Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_literal_and_unknown_keys_are_skipped() {
+        let content = r#"let key = 'a.b';
+key = 'c.d';
+t('invoice.add');
+t(key);
+t(param);
+t(`plain`);
+t(flag ? 'a' : 'b');
+t('invoice.' + "add");
+"#;
+        // Only the last call is computed, and from literals alone
+        assert_eq!(
+            shapes(content),
+            vec![(8, "invoice.add".to_string(), Confidence::High)]
+        );
+    }
+
+    #[test]
+    fn test_key_matches_shape() {
+        assert!(key_matches_shape("invoice.*.label", "invoice.paid.label"));
+        assert!(key_matches_shape("invoice.*", "invoice.labels.add"));
+        assert!(key_matches_shape("*.title", "page.title"));
+        assert!(key_matches_shape("invoice.add", "invoice.add"));
+        assert!(!key_matches_shape("invoice.*.label", "invoice..label"));
+        assert!(!key_matches_shape("invoice.*", "invoice."));
+        assert!(!key_matches_shape("invoice.*.label", "invoice.paid.title"));
+        assert!(!key_matches_shape("invoice.add", "invoice.add_new"));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::error::Result;
 use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 /// Result of a file search
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMatch {
     pub path: PathBuf,
 }
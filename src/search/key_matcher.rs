@@ -0,0 +1,159 @@
+use crate::parse::translation::TranslationEntry;
+use aho_corasick::AhoCorasick;
+use std::collections::HashMap;
+
+/// A set of translation keys matched together in one pass.
+///
+/// Looking keys up one at a time costs a full project search per key. A
+/// `KeyMatcher` holds every key (and, optionally, every partial key from
+/// [`generate_partial_keys`](crate::generate_partial_keys)) in a single
+/// Aho-Corasick automaton, so a line can be checked for all of them at once,
+/// and remembers which [`TranslationEntry`]s each key came from so hits can
+/// be attributed back to them.
+///
+/// Keys are numbered in the order they were first added; see [`id`](Self::id).
+#[derive(Debug, Clone)]
+pub struct KeyMatcher {
+    keys: Vec<String>,
+    ids: HashMap<String, usize>,
+    /// Indices into the entries the matcher was built from, per key id
+    owners: Vec<Vec<usize>>,
+    automaton: Option<AhoCorasick>,
+}
+
+impl KeyMatcher {
+    /// Match the keys of `entries` exactly
+    pub fn for_entries(entries: &[TranslationEntry]) -> Self {
+        Self::build(
+            entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| (entry.key.clone(), index)),
+        )
+    }
+
+    /// Match the keys of `entries` along with their partial keys, which
+    /// code using namespaced lookups references instead of the full key
+    pub fn with_partial_keys(entries: &[TranslationEntry]) -> Self {
+        Self::build(entries.iter().enumerate().flat_map(|(index, entry)| {
+            crate::generate_partial_keys(&entry.key)
+                .into_iter()
+                .map(move |key| (key, index))
+        }))
+    }
+
+    /// Match arbitrary keys; each key's owner is its position in `keys`
+    pub fn from_keys<I>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Self::build(
+            keys.into_iter()
+                .enumerate()
+                .map(|(index, key)| (key, index)),
+        )
+    }
+
+    fn build(keys: impl Iterator<Item = (String, usize)>) -> Self {
+        let mut matcher = Self {
+            keys: Vec::new(),
+            ids: HashMap::new(),
+            owners: Vec::new(),
+            automaton: None,
+        };
+        for (key, owner) in keys {
+            let id = *matcher.ids.entry(key.clone()).or_insert_with(|| {
+                matcher.keys.push(key);
+                matcher.owners.push(Vec::new());
+                matcher.keys.len() - 1
+            });
+            if !matcher.owners[id].contains(&owner) {
+                matcher.owners[id].push(owner);
+            }
+        }
+        if !matcher.keys.is_empty() {
+            matcher.automaton = AhoCorasick::new(&matcher.keys).ok();
+        }
+        matcher
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether any key occurs anywhere in `text`.
+    ///
+    /// A cheap pre-filter: a line can only reference a key it contains.
+    pub fn occurs_in(&self, text: &str) -> bool {
+        match &self.automaton {
+            Some(automaton) => automaton.is_match(text),
+            // Building the automaton only fails for absurdly large key sets
+            None => !self.keys.is_empty(),
+        }
+    }
+
+    /// The id of `key`, i.e. the order in which it was first added
+    pub fn id(&self, key: &str) -> Option<usize> {
+        self.ids.get(key).copied()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.ids.contains_key(key)
+    }
+
+    /// Indices of the entries (or keys, for [`from_keys`](Self::from_keys))
+    /// that `key` was derived from; empty for unknown keys
+    pub fn owners(&self, key: &str) -> &[usize] {
+        self.id(key).map_or(&[], |id| &self.owners[id])
+    }
+
+    /// All keys, in id order
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(key: &str) -> TranslationEntry {
+        TranslationEntry {
+            key: key.to_string(),
+            value: String::new(),
+            line: 1,
+            file: PathBuf::from("en.yml"),
+            branch: None,
+            locale: None,
+        }
+    }
+
+    #[test]
+    fn test_partial_keys_are_attributed_to_every_owner() {
+        let entries = vec![
+            entry("invoice.labels.add_new"),
+            entry("invoice.labels.delete"),
+        ];
+        let matcher = KeyMatcher::with_partial_keys(&entries);
+
+        assert_eq!(matcher.owners("invoice.labels.add_new"), &[0]);
+        assert_eq!(matcher.owners("labels.delete"), &[1]);
+        // The shared parent namespace belongs to both entries
+        assert_eq!(matcher.owners("invoice.labels"), &[0, 1]);
+        assert!(matcher.owners("labels").is_empty());
+        assert_eq!(matcher.id("invoice.labels.add_new"), Some(0));
+    }
+
+    #[test]
+    fn test_occurs_in() {
+        let matcher = KeyMatcher::from_keys(vec!["add_new".to_string(), "delete".to_string()]);
+        assert!(matcher.occurs_in("t('delete')"));
+        assert!(!matcher.occurs_in("t('remove')"));
+        assert!(!KeyMatcher::from_keys(Vec::new()).occurs_in("anything"));
+    }
+}
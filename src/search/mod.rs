@@ -1,7 +1,17 @@
+pub mod cancel;
+pub mod dynamic_keys;
 pub mod file_search;
+pub mod key_matcher;
 pub mod pattern_match;
+pub mod project_scan;
+pub mod session;
 pub mod text_search;
 
+pub use cancel::CancellationToken;
+pub use dynamic_keys::{Confidence, DynamicKeyFinder, DynamicUsage};
 pub use file_search::{FileMatch, FileSearcher};
+pub use key_matcher::KeyMatcher;
 pub use pattern_match::{CodeReference, PatternMatcher};
+pub use project_scan::{project_files, ProjectScan, ProjectScanner};
+pub use session::{SearchEvent, SearchEvents, SearchSession};
 pub use text_search::{Match, TextSearcher};
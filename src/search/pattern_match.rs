@@ -1,18 +1,24 @@
 use crate::config::default_patterns;
 use crate::error::Result;
+use crate::parse::locale::{is_catalog_file, is_locale_file_in};
 use crate::parse::translation::TranslationEntry;
-use crate::search::text_search::TextSearcher;
+use crate::search::dynamic_keys::{Confidence, DynamicKeyFinder, DYNAMIC_CALL_PATTERN};
+use crate::search::key_matcher::KeyMatcher;
+use crate::search::project_scan::surrounding_lines;
+use crate::search::text_search::{Match, TextSearcher};
 use regex::Regex;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 /// Represents a code reference to a translation key
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CodeReference {
     /// Path to the file containing the reference
     pub file: PathBuf,
     /// Line number (1-indexed)
     pub line: usize,
-    /// The regex pattern that matched
+    /// The regex pattern that matched, or for possible usages the shape of
+    /// the computed key (e.g. `invoice.*.label`)
     pub pattern: String,
     /// The actual line of code containing the match
     pub context: String,
@@ -22,13 +28,20 @@ pub struct CodeReference {
     pub context_before: Vec<String>,
     /// Context lines after the match
     pub context_after: Vec<String>,
+    /// How sure it is that the code uses `key_path`. Anything but
+    /// [`Confidence::Exact`] is a possible usage through a key computed at
+    /// runtime (see [`DynamicKeyFinder`])
+    pub confidence: Confidence,
 }
 
 /// Pattern matcher for finding i18n key usage in code
 pub struct PatternMatcher {
+    base_dir: PathBuf,
     exclusions: Vec<String>,
+    locale_dirs: Vec<String>,
     searcher: TextSearcher,
     patterns: Vec<Regex>,
+    dynamic_keys: DynamicKeyFinder,
 }
 
 impl PatternMatcher {
@@ -36,8 +49,11 @@ impl PatternMatcher {
     pub fn new(base_dir: PathBuf) -> Self {
         Self {
             exclusions: Vec::new(),
-            searcher: TextSearcher::new(base_dir),
+            locale_dirs: Vec::new(),
+            searcher: TextSearcher::new(base_dir.clone()),
+            base_dir,
             patterns: default_patterns(),
+            dynamic_keys: DynamicKeyFinder::new(),
         }
     }
 
@@ -45,8 +61,11 @@ impl PatternMatcher {
     pub fn with_patterns(patterns: Vec<Regex>, base_dir: PathBuf) -> Self {
         Self {
             exclusions: Vec::new(),
-            searcher: TextSearcher::new(base_dir),
+            locale_dirs: Vec::new(),
+            searcher: TextSearcher::new(base_dir.clone()),
+            base_dir,
             patterns,
+            dynamic_keys: DynamicKeyFinder::new(),
         }
     }
 
@@ -55,40 +74,32 @@ impl PatternMatcher {
         self.exclusions = exclusions;
     }
 
-    /// Find all code references for a given translation key
+    /// Set extra directory names that hold locale files, so references
+    /// "found" inside them are ignored by `find_all_references`
+    pub fn set_locale_dirs(&mut self, locale_dirs: Vec<String>) {
+        self.locale_dirs = locale_dirs;
+    }
+
+    /// Find all code references for a given translation key, including
+    /// Rails lazy lookups that resolve to it (see [`resolve_lazy_key`])
     pub fn find_usages(&self, key_path: &str) -> Result<Vec<CodeReference>> {
-        // Search for the key path using ripgrep
-        let matches = self.searcher.search(key_path)?;
+        // Search for the key path using ripgrep. A lazy lookup only spells
+        // out the end of the key (`t('.add_new')`), so look for that too
+        let matches = match key_path.rsplit_once('.') {
+            Some((_, last)) => TextSearcher::new(self.base_dir.clone())
+                .is_regex(true)
+                .search(&format!(
+                    "{}|\\.{}",
+                    regex::escape(key_path),
+                    regex::escape(last)
+                ))?,
+            None => self.searcher.search(key_path)?,
+        };
 
         let mut code_refs = Vec::new();
 
         for m in matches {
-            // Apply exclusions: skip if any exclusion matches the file path
-            let file_str = m.file.to_string_lossy();
-            if self.exclusions.iter().any(|ex| file_str.contains(ex)) {
-                continue;
-            }
-
-            // Skip tool's own source files and documentation (cross-platform)
-            let file_str = m.file.to_string_lossy().to_lowercase();
-            let path_components: Vec<_> = m
-                .file
-                .components()
-                .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
-                .collect();
-
-            // Check if path starts with "src" or "tests" (but not "tests/fixtures")
-            let skip_file = !path_components.is_empty()
-                && (path_components[0] == "src"
-                    || (path_components[0] == "tests"
-                        && (path_components.len() < 2 || path_components[1] != "fixtures")));
-
-            // Also skip markdown files
-            if skip_file
-                || file_str.ends_with("readme.md")
-                || file_str.ends_with("evaluation.md")
-                || file_str.ends_with(".md")
-            {
+            if self.is_skipped_file(&m.file) {
                 continue;
             }
 
@@ -97,7 +108,7 @@ impl PatternMatcher {
                 if let Some(captures) = pattern.captures(&m.content) {
                     // Extract the key from the capture group
                     if let Some(captured_key) = captures.get(1) {
-                        if captured_key.as_str() == key_path {
+                        if resolve_key(&m.file, captured_key.as_str()) == key_path {
                             code_refs.push(CodeReference {
                                 file: m.file.clone(),
                                 line: m.line,
@@ -106,6 +117,7 @@ impl PatternMatcher {
                                 key_path: key_path.to_string(),
                                 context_before: m.context_before.clone(),
                                 context_after: m.context_after.clone(),
+                                confidence: Confidence::Exact,
                             });
                             break; // Found a match, no need to check other patterns
                         }
@@ -117,17 +129,239 @@ impl PatternMatcher {
         Ok(code_refs)
     }
 
-    /// Find usages for multiple translation entries
-    pub fn find_usages_batch(&self, entries: &[TranslationEntry]) -> Result<Vec<CodeReference>> {
-        let mut all_refs = Vec::new();
+    /// Find every translation key referenced anywhere in code.
+    ///
+    /// Runs all patterns as a single search and returns one reference per
+    /// key occurrence, with `key_path` set to the key captured by the pattern.
+    /// Translation files themselves are skipped.
+    pub fn find_all_references(&self) -> Result<Vec<CodeReference>> {
+        let mut code_refs = Vec::new();
+        for m in self.search_pattern_lines()? {
+            let is_translation_file = m.file.extension().is_some_and(|ext| {
+                matches!(ext.to_string_lossy().as_ref(), "yml" | "yaml" | "json")
+            }) || is_catalog_file(&m.file);
+            let relative = m.file.strip_prefix(&self.base_dir).unwrap_or(&m.file);
+            if is_translation_file
+                || is_locale_file_in(relative, &self.locale_dirs)
+                || self.is_skipped_file(&m.file)
+            {
+                continue;
+            }
+
+            for pattern in &self.patterns {
+                for captures in pattern.captures_iter(&m.content) {
+                    if let Some(key) = captures.get(1) {
+                        code_refs.push(CodeReference {
+                            file: m.file.clone(),
+                            line: m.line,
+                            pattern: pattern.as_str().to_string(),
+                            context: m.content.clone(),
+                            key_path: resolve_key(&m.file, key.as_str()),
+                            context_before: m.context_before.clone(),
+                            context_after: m.context_after.clone(),
+                            confidence: Confidence::Exact,
+                        });
+                    }
+                }
+            }
+        }
+
+        code_refs.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+        code_refs.dedup_by(|a, b| a.file == b.file && a.line == b.line && a.key_path == b.key_path);
+        Ok(code_refs)
+    }
+
+    /// Find code references for every key in `keys` with a single search.
+    ///
+    /// All patterns are combined into one project search, and each matching
+    /// line is checked against every key at once, so the cost doesn't grow
+    /// with the number of keys. Each reference's `key_path` is the key it
+    /// uses; [`KeyMatcher::owners`] maps it back to the entries it came from.
+    ///
+    /// Calls that compute their key at runtime are reported as possible
+    /// usages of every key they could produce (see [`DynamicKeyFinder`]).
+    /// References are sorted by file, line, confidence and key id.
+    pub fn find_usages_of(&self, keys: &KeyMatcher) -> Result<Vec<CodeReference>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut code_refs = Vec::new();
+        for m in self.search_pattern_lines()? {
+            // Lazy lookups in views don't spell out the key they resolve to
+            let may_be_lazy = lazy_scope(&m.file).is_some();
+            if self.is_skipped_file(&m.file) || !(may_be_lazy || keys.occurs_in(&m.content)) {
+                continue;
+            }
+
+            let mut seen: Vec<String> = Vec::new();
+            for pattern in &self.patterns {
+                // As in `find_usages`, only the first capture of each pattern counts
+                let Some(key) = pattern.captures(&m.content).and_then(|c| c.get(1)) else {
+                    continue;
+                };
+                let key = resolve_key(&m.file, key.as_str());
+                if !keys.contains(&key) || seen.contains(&key) {
+                    continue;
+                }
+                seen.push(key.clone());
+                code_refs.push(CodeReference {
+                    file: m.file.clone(),
+                    line: m.line,
+                    pattern: pattern.as_str().to_string(),
+                    context: m.content.clone(),
+                    key_path: key,
+                    context_before: m.context_before.clone(),
+                    context_after: m.context_after.clone(),
+                    confidence: Confidence::Exact,
+                });
+            }
+        }
 
-        for entry in entries {
-            let refs = self.find_usages(&entry.key)?;
-            all_refs.extend(refs);
+        code_refs.extend(self.find_dynamic_usages_of(keys)?);
+        code_refs.sort_by_key(|r| {
+            (
+                r.file.clone(),
+                r.line,
+                r.confidence.is_possible(),
+                keys.id(&r.key_path),
+            )
+        });
+        Ok(code_refs)
+    }
+
+    /// Possible usages of `keys` by calls that compute their key, one per
+    /// call and key it could produce
+    fn find_dynamic_usages_of(&self, keys: &KeyMatcher) -> Result<Vec<CodeReference>> {
+        let mut files: Vec<PathBuf> = TextSearcher::new(self.base_dir.clone())
+            .is_regex(true)
+            .case_sensitive(true)
+            .search(DYNAMIC_CALL_PATTERN)?
+            .into_iter()
+            .map(|m| m.file)
+            .filter(|file| !self.is_skipped_file(file))
+            .collect();
+        files.sort();
+        files.dedup();
+
+        let mut code_refs = Vec::new();
+        for file in files {
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            let lines: Vec<&str> = content.lines().collect();
+            for usage in self.dynamic_keys.find(&content) {
+                let index = usage.line - 1;
+                for key in keys.keys().iter().filter(|key| usage.matches(key)) {
+                    let (context_before, context_after) = surrounding_lines(&lines, index, 2, 2);
+                    code_refs.push(CodeReference {
+                        file: file.clone(),
+                        line: usage.line,
+                        pattern: usage.shape.clone(),
+                        context: lines[index].trim_end().to_string(),
+                        key_path: key.clone(),
+                        context_before,
+                        context_after,
+                        confidence: usage.confidence,
+                    });
+                }
+            }
         }
+        Ok(code_refs)
+    }
+
+    /// Lines matching any pattern, from one search over the project
+    fn search_pattern_lines(&self) -> Result<Vec<Match>> {
+        let combined = self
+            .patterns
+            .iter()
+            .map(|p| format!("(?:{})", p.as_str()))
+            .collect::<Vec<_>>()
+            .join("|");
+        TextSearcher::new(self.base_dir.clone())
+            .is_regex(true)
+            .case_sensitive(true)
+            .search(&combined)
+    }
+
+    /// Whether references in `file` should be ignored (see [`is_skipped_reference`])
+    fn is_skipped_file(&self, file: &Path) -> bool {
+        is_skipped_reference(file, &self.exclusions)
+    }
+
+    /// Find usages for multiple translation entries in one search
+    /// (see [`find_usages_of`](Self::find_usages_of))
+    pub fn find_usages_batch(&self, entries: &[TranslationEntry]) -> Result<Vec<CodeReference>> {
+        self.find_usages_of(&KeyMatcher::for_entries(entries))
+    }
+}
+
+/// The key scope of a Rails view, which lazy lookups in it are relative to:
+/// `app/views/admin/invoices/index.html.erb` → `admin.invoices.index`.
+/// Partials drop their leading underscore (`_form.html.erb` → `form`).
+pub fn lazy_scope(file: &Path) -> Option<String> {
+    let parts: Vec<_> = file
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    let views = parts
+        .windows(2)
+        .rposition(|pair| pair[0] == "app" && pair[1] == "views")?
+        + 2;
+    let (file_name, dirs) = parts.get(views..)?.split_last()?;
+    let template = file_name.split('.').next()?;
+    let template = template.strip_prefix('_').unwrap_or(template);
+    if template.is_empty() {
+        return None;
+    }
+
+    let mut scope: Vec<&str> = dirs.iter().map(|d| d.as_ref()).collect();
+    scope.push(template);
+    Some(scope.join("."))
+}
+
+/// Resolve a Rails lazy lookup to the full key it stands for:
+/// `t('.add_new')` in `app/views/invoices/index.html.erb` means
+/// `invoices.index.add_new`. Returns `None` for keys that aren't lazy
+/// (no leading dot) and for files outside `app/views`.
+pub fn resolve_lazy_key(file: &Path, key: &str) -> Option<String> {
+    let relative = key.strip_prefix('.').filter(|k| !k.is_empty())?;
+    Some(format!("{}.{}", lazy_scope(file)?, relative))
+}
 
-        Ok(all_refs)
+/// The key a captured reference stands for: lazy lookups resolved, other
+/// keys as written
+pub(crate) fn resolve_key(file: &Path, captured: &str) -> String {
+    resolve_lazy_key(file, captured).unwrap_or_else(|| captured.to_string())
+}
+
+/// Whether references in `file` should be ignored: user exclusions, the
+/// tool's own sources and documentation
+pub(crate) fn is_skipped_reference(file: &Path, exclusions: &[String]) -> bool {
+    // Apply exclusions: skip if any exclusion matches the file path
+    let file_str = file.to_string_lossy();
+    if exclusions.iter().any(|ex| file_str.contains(ex)) {
+        return true;
     }
+
+    // Skip tool's own source files and documentation (cross-platform)
+    let file_str = file_str.to_lowercase();
+    let path_components: Vec<_> = file
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+
+    // Check if path starts with "src" or "tests" (but not "tests/fixtures")
+    let skip_file = !path_components.is_empty()
+        && (path_components[0] == "src"
+            || (path_components[0] == "tests"
+                && (path_components.len() < 2 || path_components[1] != "fixtures")));
+
+    // Also skip markdown files
+    skip_file
+        || file_str.ends_with("readme.md")
+        || file_str.ends_with("evaluation.md")
+        || file_str.ends_with(".md")
 }
 
 impl Default for PatternMatcher {
@@ -156,6 +390,7 @@ mod tests {
             key_path: "invoice.labels.add_new".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         };
 
         assert_eq!(code_ref.file, PathBuf::from("test.rb"));
@@ -170,4 +405,26 @@ mod tests {
             PatternMatcher::with_patterns(custom_patterns, std::env::current_dir().unwrap());
         assert_eq!(matcher.patterns.len(), 1);
     }
+
+    #[test]
+    fn test_resolve_lazy_key() {
+        let view = Path::new("app/views/invoices/index.html.erb");
+        assert_eq!(
+            resolve_lazy_key(view, ".add_new"),
+            Some("invoices.index.add_new".to_string())
+        );
+        assert_eq!(
+            resolve_lazy_key(
+                Path::new("/srv/shop/app/views/admin/invoices/_form.html.haml"),
+                ".labels.save"
+            ),
+            Some("admin.invoices.form.labels.save".to_string())
+        );
+        // Absolute keys and files outside app/views are left alone
+        assert_eq!(resolve_lazy_key(view, "invoice.add_new"), None);
+        assert_eq!(
+            resolve_lazy_key(Path::new("app/models/invoice.rb"), ".add_new"),
+            None
+        );
+    }
 }
@@ -0,0 +1,672 @@
+//! Single-pass project scan behind [`run_search`](crate::run_search).
+//!
+//! A search needs four things from the project: the translation files to
+//! parse, the lines where i18n keys are used, the lines containing the query
+//! text and the files whose names contain it. Rather than walking the tree
+//! once per key and once more per kind of match, [`ProjectScanner`] walks it
+//! once in parallel, classifies every file and reads each file's contents at
+//! most once.
+//!
+//! Key usages are collected for every key the i18n patterns capture, before
+//! the translation files have been parsed; callers keep the usages whose key
+//! turns out to be relevant.
+
+use crate::error::{Result, SearchError};
+use crate::parse::key_extractor::is_translation_candidate;
+use crate::search::cancel::CancellationToken;
+use crate::search::dynamic_keys::{Confidence, DynamicKeyFinder};
+use crate::search::file_search::FileMatch;
+use crate::search::pattern_match::{is_skipped_reference, resolve_key, CodeReference};
+use crate::search::text_search::Match;
+use grep_matcher::Matcher;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use regex::Regex;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+
+/// Bytes sniffed for a NUL before a file is read in full, as ripgrep does
+const SNIFF_LEN: u64 = 8 * 1024;
+
+/// Everything a single walk found
+#[derive(Debug, Default)]
+pub struct ProjectScan {
+    /// Files to parse for translations, sorted
+    pub locale_files: Vec<PathBuf>,
+    /// Lines containing the query text, sorted by file and line
+    pub direct_matches: Vec<Match>,
+    /// Lines where an i18n pattern captured a key, with `key_path` set to
+    /// the captured key; sorted by file and line
+    pub key_usages: Vec<CodeReference>,
+    /// Calls that compute their key at runtime, with `key_path` set to the
+    /// shape of the key (see [`DynamicUsage`](crate::search::DynamicUsage)); sorted by file and line
+    pub possible_usages: Vec<CodeReference>,
+    /// Files whose names contain the query text, sorted
+    pub file_matches: Vec<FileMatch>,
+}
+
+/// Walks a project once and feeds every kind of search from that walk.
+///
+/// Files are classified as they are found:
+/// - **locale files** (see [`is_translation_candidate`]) are collected for
+///   parsing; they are also scanned for key usages, since `.js` and `.json`
+///   files may be code too
+/// - **code files** are read once and matched against both the query text
+///   and the i18n patterns
+/// - every file's name is compared with the query text
+///
+/// Like [`TextSearcher`](crate::search::TextSearcher), the walk respects
+/// `.gitignore` and includes hidden files.
+pub struct ProjectScanner {
+    base_dir: PathBuf,
+    case_sensitive: bool,
+    word_match: bool,
+    is_regex: bool,
+    globs: Vec<String>,
+    exclusions: Vec<String>,
+    patterns: Vec<Regex>,
+    only_file: Option<PathBuf>,
+    /// Files to scan instead of walking `base_dir` (see [`with_files`](Self::with_files))
+    files: Option<Arc<Vec<PathBuf>>>,
+    before_context: usize,
+    after_context: usize,
+    dynamic_keys: DynamicKeyFinder,
+    cancel: CancellationToken,
+}
+
+/// What every file is matched against during one scan
+struct Matchers {
+    direct: RegexMatcher,
+    usage_filter: Option<Regex>,
+    overrides: Override,
+    name_query: String,
+}
+
+/// Per-file result sent from the walker threads
+#[derive(Default)]
+pub(crate) struct FileScan {
+    pub(crate) locale_file: Option<PathBuf>,
+    pub(crate) direct_matches: Vec<Match>,
+    pub(crate) key_usages: Vec<CodeReference>,
+    pub(crate) possible_usages: Vec<CodeReference>,
+    pub(crate) file_match: Option<FileMatch>,
+}
+
+impl ProjectScanner {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            case_sensitive: false,
+            word_match: false,
+            is_regex: false,
+            globs: Vec::new(),
+            exclusions: Vec::new(),
+            patterns: Vec::new(),
+            only_file: None,
+            files: None,
+            before_context: 2,
+            after_context: 2,
+            dynamic_keys: DynamicKeyFinder::new(),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Set whether text and file name matching is case-sensitive (default: false)
+    pub fn case_sensitive(mut self, value: bool) -> Self {
+        self.case_sensitive = value;
+        self
+    }
+
+    /// Set whether to match the query text as a whole word (default: false)
+    pub fn word_match(mut self, value: bool) -> Self {
+        self.word_match = value;
+        self
+    }
+
+    /// Set whether to treat the query text as a regex (default: false)
+    pub fn is_regex(mut self, value: bool) -> Self {
+        self.is_regex = value;
+        self
+    }
+
+    /// Add glob patterns limiting which files are matched against the query text
+    pub fn add_globs(mut self, globs: Vec<String>) -> Self {
+        self.globs.extend(globs);
+        self
+    }
+
+    /// Add exclusion patterns: files whose path contains one are skipped
+    pub fn add_exclusions(mut self, exclusions: Vec<String>) -> Self {
+        self.exclusions.extend(exclusions);
+        self
+    }
+
+    /// Set the i18n patterns used to find key usages (default: none, which
+    /// disables key usage matching and locale file collection)
+    pub fn with_patterns(mut self, patterns: Vec<Regex>) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    /// Only match the query text inside `file`; file names are still matched
+    /// across the whole walk
+    pub fn only_file(mut self, file: PathBuf) -> Self {
+        self.only_file = Some(file);
+        self
+    }
+
+    /// Scan `files` instead of walking `base_dir`. The list should be what
+    /// [`project_files`] returns for `base_dir`; the query daemon keeps it in
+    /// memory between searches.
+    pub fn with_files(mut self, files: Arc<Vec<PathBuf>>) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    /// Stop the scan once `token` is cancelled; [`scan`](Self::scan) then
+    /// fails with [`SearchError::Cancelled`]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Set number of context lines to capture around matches (default: 2)
+    pub fn context_lines(mut self, lines: usize) -> Self {
+        self.before_context = lines;
+        self.after_context = lines;
+        self
+    }
+
+    /// Set number of context lines to capture before matches (default: 2)
+    pub fn before_context(mut self, lines: usize) -> Self {
+        self.before_context = lines;
+        self
+    }
+
+    /// Set number of context lines to capture after matches (default: 2)
+    pub fn after_context(mut self, lines: usize) -> Self {
+        self.after_context = lines;
+        self
+    }
+
+    /// Walk the project, matching file contents and names against `text`
+    pub fn scan(&self, text: &str) -> Result<ProjectScan> {
+        self.scan_each(text, |_| {})
+    }
+
+    /// Like [`scan`](Self::scan), but hands each file's findings to `visit`
+    /// on the calling thread as soon as that file has been scanned
+    pub(crate) fn scan_each(
+        &self,
+        text: &str,
+        mut visit: impl FnMut(&FileScan),
+    ) -> Result<ProjectScan> {
+        let direct = RegexMatcherBuilder::new()
+            .case_insensitive(!self.case_sensitive)
+            .word(self.word_match)
+            .fixed_strings(!self.is_regex)
+            .build(text)
+            .map_err(|e| SearchError::Generic(format!("Failed to build matcher: {}", e)))?;
+
+        // One regex over the whole file decides whether its lines are worth
+        // running every pattern against
+        let usage_filter = if self.patterns.is_empty() {
+            None
+        } else {
+            let combined = self
+                .patterns
+                .iter()
+                .map(|p| format!("(?:{})", p.as_str()))
+                .collect::<Vec<_>>()
+                .join("|");
+            Some(Regex::new(&format!("(?mR){}", combined)).map_err(|e| {
+                SearchError::Generic(format!("Failed to combine i18n patterns: {}", e))
+            })?)
+        };
+
+        let matchers = Matchers {
+            direct,
+            usage_filter,
+            overrides: self.build_overrides()?,
+            name_query: if self.case_sensitive {
+                text.to_string()
+            } else {
+                text.to_lowercase()
+            },
+        };
+
+        // The walk runs beside the receiving loop so `visit` sees every file
+        // while the rest of the project is still being scanned
+        let (tx, rx) = mpsc::channel();
+        let matchers = &matchers;
+        let mut result = ProjectScan::default();
+        std::thread::scope(|scope| {
+            scope.spawn(move || match &self.files {
+                Some(files) => self.scan_files(files, matchers, tx),
+                None => self.walk(matchers, tx),
+            });
+
+            for scan in rx {
+                visit(&scan);
+                result.locale_files.extend(scan.locale_file);
+                result.direct_matches.extend(scan.direct_matches);
+                result.key_usages.extend(scan.key_usages);
+                result.possible_usages.extend(scan.possible_usages);
+                result.file_matches.extend(scan.file_match);
+            }
+        });
+        if self.cancel.is_cancelled() {
+            return Err(SearchError::Cancelled);
+        }
+
+        // The parallel walk finishes files in any order
+        result.locale_files.sort();
+        result
+            .direct_matches
+            .sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+        result
+            .key_usages
+            .sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+        result
+            .possible_usages
+            .sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+        result.file_matches.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(result)
+    }
+
+    /// Scan every file found by walking `base_dir`
+    fn walk(&self, matchers: &Matchers, tx: mpsc::Sender<FileScan>) {
+        walk_builder(&self.base_dir).build_parallel().run(|| {
+            let tx = tx.clone();
+            Box::new(move |entry| {
+                if self.cancel.is_cancelled() {
+                    return WalkState::Quit;
+                }
+                let entry = match entry {
+                    Ok(e) => e,
+                    Err(_) => return WalkState::Continue,
+                };
+                if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                    return WalkState::Continue;
+                }
+
+                let _ = tx.send(self.scan_file(entry.path(), matchers));
+                WalkState::Continue
+            })
+        });
+    }
+
+    /// Scan a known list of files, split across threads like the walk
+    fn scan_files(&self, files: &[PathBuf], matchers: &Matchers, tx: mpsc::Sender<FileScan>) {
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let chunk_size = files.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            for chunk in files.chunks(chunk_size) {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    for path in chunk {
+                        if self.cancel.is_cancelled() {
+                            break;
+                        }
+                        let _ = tx.send(self.scan_file(path, matchers));
+                    }
+                });
+            }
+        });
+    }
+
+    fn build_overrides(&self) -> Result<Override> {
+        let mut builder = OverrideBuilder::new(&self.base_dir);
+        for glob in &self.globs {
+            builder.add(glob).map_err(|e| {
+                SearchError::Generic(format!("Invalid glob pattern '{}': {}", glob, e))
+            })?;
+        }
+        builder
+            .build()
+            .map_err(|e| SearchError::Generic(format!("Invalid glob patterns: {}", e)))
+    }
+
+    /// Classify one file and run every match it is eligible for
+    fn scan_file(&self, path: &Path, matchers: &Matchers) -> FileScan {
+        let Matchers {
+            direct,
+            usage_filter,
+            overrides,
+            name_query,
+        } = matchers;
+        let usage_filter = usage_filter.as_ref();
+        let mut scan = FileScan::default();
+
+        // Locale files follow the extractor's own exclusion rules
+        if usage_filter.is_some()
+            && is_translation_candidate(&self.base_dir, path, &self.exclusions)
+        {
+            scan.locale_file = Some(path.to_path_buf());
+        }
+
+        let path_str = path.to_string_lossy();
+        if self
+            .exclusions
+            .iter()
+            .any(|ex| path_str.contains(ex.as_str()))
+        {
+            return scan;
+        }
+
+        if let Some(file_name) = path.file_name() {
+            let file_name = file_name.to_string_lossy();
+            let matches = if self.case_sensitive {
+                file_name.contains(name_query.as_str())
+            } else {
+                file_name.to_lowercase().contains(name_query.as_str())
+            };
+            if matches {
+                scan.file_match = Some(FileMatch {
+                    path: path.to_path_buf(),
+                });
+            }
+        }
+
+        let match_text = match &self.only_file {
+            Some(only) => path == only,
+            None => is_included(overrides, &self.base_dir, path),
+        };
+        let usage_filter = usage_filter.filter(|_| !is_skipped_reference(path, &self.exclusions));
+        if !match_text && usage_filter.is_none() {
+            return scan;
+        }
+
+        // The single read of this file
+        let Some(bytes) = read_text_file(path) else {
+            return scan;
+        };
+        let content = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = content.lines().collect();
+
+        if match_text && (self.is_regex || direct.is_match(content.as_bytes()).unwrap_or(true)) {
+            for (index, line) in lines.iter().enumerate() {
+                if direct.is_match(line.as_bytes()).unwrap_or(false) {
+                    let (context_before, context_after) = self.context(&lines, index);
+                    scan.direct_matches.push(Match {
+                        file: path.to_path_buf(),
+                        line: index + 1,
+                        content: line.trim_end().to_string(),
+                        context_before,
+                        context_after,
+                    });
+                }
+            }
+        }
+
+        if let Some(filter) = usage_filter.filter(|f| f.is_match(&content)) {
+            for (index, line) in lines.iter().enumerate() {
+                if !filter.is_match(line) {
+                    continue;
+                }
+                let mut seen: Vec<String> = Vec::new();
+                for pattern in &self.patterns {
+                    // Like `PatternMatcher::find_usages`, only the first
+                    // capture of each pattern on a line counts
+                    let Some(key) = pattern.captures(line).and_then(|c| c.get(1)) else {
+                        continue;
+                    };
+                    // Rails lazy lookups (`t('.add_new')`) are resolved to full keys
+                    let key = resolve_key(path, key.as_str());
+                    if seen.contains(&key) {
+                        continue;
+                    }
+                    seen.push(key.clone());
+
+                    let (context_before, context_after) = self.context(&lines, index);
+                    scan.key_usages.push(CodeReference {
+                        file: path.to_path_buf(),
+                        line: index + 1,
+                        pattern: pattern.as_str().to_string(),
+                        context: line.trim_end().to_string(),
+                        key_path: key,
+                        context_before,
+                        context_after,
+                        confidence: Confidence::Exact,
+                    });
+                }
+            }
+        }
+
+        if usage_filter.is_some() {
+            for usage in self.dynamic_keys.find(&content) {
+                let index = usage.line - 1;
+                let (context_before, context_after) = self.context(&lines, index);
+                scan.possible_usages.push(CodeReference {
+                    file: path.to_path_buf(),
+                    line: usage.line,
+                    pattern: usage.shape.clone(),
+                    context: lines[index].trim_end().to_string(),
+                    key_path: usage.shape,
+                    context_before,
+                    context_after,
+                    confidence: usage.confidence,
+                });
+            }
+        }
+
+        scan
+    }
+
+    fn context(&self, lines: &[&str], index: usize) -> (Vec<String>, Vec<String>) {
+        surrounding_lines(lines, index, self.before_context, self.after_context)
+    }
+}
+
+/// Every file a scan of `base_dir` looks at, in walk order
+pub fn project_files(base_dir: &Path) -> Vec<PathBuf> {
+    walk_builder(base_dir)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Like [`TextSearcher`](crate::search::TextSearcher), walks respect
+/// `.gitignore` and include hidden files
+fn walk_builder(base_dir: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(base_dir);
+    builder
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .hidden(false);
+    builder
+}
+
+/// Up to `before` lines before and `after` lines after `lines[index]`
+pub(crate) fn surrounding_lines(
+    lines: &[&str],
+    index: usize,
+    before: usize,
+    after: usize,
+) -> (Vec<String>, Vec<String>) {
+    let start = index.saturating_sub(before);
+    let end = std::cmp::min(index + after + 1, lines.len());
+    (
+        lines[start..index].iter().map(|s| s.to_string()).collect(),
+        lines[index + 1..end]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    )
+}
+
+/// Whether the glob overrides let `path` through. Like the walker itself,
+/// a directory excluded by a glob excludes everything below it.
+fn is_included(overrides: &Override, base_dir: &Path, path: &Path) -> bool {
+    if overrides.is_empty() {
+        return true;
+    }
+    if overrides.matched(path, false).is_ignore() {
+        return false;
+    }
+    !path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(base_dir) && *dir != base_dir)
+        .any(|dir| overrides.matched(dir, true).is_ignore())
+}
+
+/// The contents of `path`, or `None` for binary files. Most binaries are told
+/// apart before the whole file is read, by a NUL in the first block.
+fn read_text_file(path: &Path) -> Option<Vec<u8>> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+
+    let mut bytes = Vec::with_capacity(len as usize);
+    (&mut file).take(SNIFF_LEN).read_to_end(&mut bytes).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    let sniffed = bytes.len();
+    file.read_to_end(&mut bytes).ok()?;
+    // A NUL further in still makes the file binary
+    if bytes[sniffed..].contains(&0) {
+        return None;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let locales = dir.path().join("config/locales");
+        fs::create_dir_all(&locales).unwrap();
+        fs::write(locales.join("en.yml"), "en:\n  add_new: \"Add New\"\n").unwrap();
+        fs::write(
+            dir.path().join("app.rb"),
+            "# Add New button\nlabel = I18n.t('add_new')\nother = I18n.t('remove')\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("add_new_form.html"), "<form></form>\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scan_classifies_and_matches_in_one_walk() {
+        let dir = project();
+        let scan = ProjectScanner::new(dir.path().to_path_buf())
+            .with_patterns(crate::config::default_patterns())
+            .scan("add_new")
+            .unwrap();
+
+        assert_eq!(
+            scan.locale_files,
+            vec![dir.path().join("config/locales/en.yml")]
+        );
+
+        let usages: Vec<_> = scan
+            .key_usages
+            .iter()
+            .map(|r| (r.line, r.key_path.as_str()))
+            .collect();
+        assert_eq!(usages, vec![(2, "add_new"), (3, "remove")]);
+        assert_eq!(scan.key_usages[0].context_before, vec!["# Add New button"]);
+
+        let direct: Vec<_> = scan
+            .direct_matches
+            .iter()
+            .map(|m| {
+                (
+                    m.file.file_name().unwrap().to_string_lossy().into_owned(),
+                    m.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            direct,
+            vec![("app.rb".to_string(), 2), ("en.yml".to_string(), 2)]
+        );
+
+        assert_eq!(scan.file_matches.len(), 1);
+        assert!(scan.file_matches[0].path.ends_with("add_new_form.html"));
+    }
+
+    #[test]
+    fn test_scan_of_given_files_matches_walk() {
+        let dir = project();
+        let scanner = || {
+            ProjectScanner::new(dir.path().to_path_buf())
+                .with_patterns(crate::config::default_patterns())
+        };
+        let walked = scanner().scan("add_new").unwrap();
+        let listed = scanner()
+            .with_files(Arc::new(project_files(dir.path())))
+            .scan("add_new")
+            .unwrap();
+
+        assert_eq!(listed.locale_files, walked.locale_files);
+        assert_eq!(listed.key_usages, walked.key_usages);
+        assert_eq!(listed.direct_matches.len(), walked.direct_matches.len());
+        assert_eq!(listed.file_matches.len(), walked.file_matches.len());
+    }
+
+    #[test]
+    fn test_globs_limit_text_matches_only() {
+        let dir = project();
+        let scan = ProjectScanner::new(dir.path().to_path_buf())
+            .with_patterns(crate::config::default_patterns())
+            .add_globs(vec!["*.yml".to_string()])
+            .scan("add_new")
+            .unwrap();
+
+        assert_eq!(scan.direct_matches.len(), 1);
+        assert!(scan.direct_matches[0].file.ends_with("en.yml"));
+        assert_eq!(scan.key_usages.len(), 2);
+    }
+
+    #[test]
+    fn test_only_file_and_no_patterns() {
+        let dir = project();
+        let scan = ProjectScanner::new(dir.path().to_path_buf())
+            .case_sensitive(true)
+            .only_file(dir.path().join("app.rb"))
+            .scan("Add New")
+            .unwrap();
+
+        assert!(scan.locale_files.is_empty());
+        assert!(scan.key_usages.is_empty());
+        assert_eq!(scan.direct_matches.len(), 1);
+        assert_eq!(scan.direct_matches[0].line, 1);
+    }
+
+    #[test]
+    fn test_binary_files_are_not_read() {
+        let dir = TempDir::new().unwrap();
+        let text = dir.path().join("app.rb");
+        fs::write(&text, "label = 'Add New'\n").unwrap();
+        assert_eq!(read_text_file(&text).unwrap(), b"label = 'Add New'\n");
+
+        let binary = dir.path().join("logo.png");
+        fs::write(&binary, b"\x89PNG\0Add New").unwrap();
+        assert!(read_text_file(&binary).is_none());
+
+        let late_nul = dir.path().join("dump.txt");
+        let mut bytes = vec![b'a'; SNIFF_LEN as usize + 10];
+        bytes.push(0);
+        fs::write(&late_nul, bytes).unwrap();
+        assert!(read_text_file(&late_nul).is_none());
+
+        // Large text files are searched like any other
+        let large = dir.path().join("bundle.js");
+        let mut bytes = vec![b'a'; 11 * 1024 * 1024];
+        bytes.extend_from_slice(b"\nlabel = 'Add New'\n");
+        fs::write(&large, &bytes).unwrap();
+        assert_eq!(read_text_file(&large).unwrap().len(), bytes.len());
+    }
+}
@@ -0,0 +1,412 @@
+//! Incremental searches behind [`run_search`](crate::run_search).
+//!
+//! A [`SearchSession`] runs the same search as `run_search`, but hands out
+//! every result as a [`SearchEvent`] as soon as it is final instead of
+//! returning them all at the end:
+//!
+//! 1. file name matches, and text matches on lines without an i18n call,
+//!    arrive while the project is still being walked
+//! 2. translation entries follow once the walk has found every
+//!    translation file and they have been parsed
+//! 3. code references using a matching key come last, together with the
+//!    text matches on lines where such a reference may take their place
+//!
+//! A [`CancellationToken`] stops a session at any point, including in the
+//! middle of the walk.
+
+use crate::cache::daemon::WarmProject;
+use crate::config;
+use crate::error::{Result, SearchError};
+use crate::parse::{self, KeyExtractor, TranslationEntry};
+use crate::search::cancel::CancellationToken;
+use crate::search::dynamic_keys::{key_matches_shape, Confidence};
+use crate::search::file_search::FileMatch;
+use crate::search::key_matcher::KeyMatcher;
+use crate::search::pattern_match::CodeReference;
+use crate::search::project_scan::ProjectScanner;
+use crate::search::text_search::Match;
+use crate::SearchQuery;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+/// One result of a [`SearchSession`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SearchEvent {
+    /// A translation whose key or value matches the query
+    Translation(TranslationEntry),
+    /// A line of code using a matching key or containing the query text
+    CodeReference(CodeReference),
+    /// A file whose name contains the query text
+    File(FileMatch),
+}
+
+/// A search that yields its results as they are found.
+///
+/// ```no_run
+/// use cs::{SearchEvent, SearchQuery, SearchSession};
+///
+/// let session = SearchSession::new(SearchQuery::new("Add New".to_string()));
+/// let cancel = session.cancellation();
+/// session.run(|event| {
+///     if let SearchEvent::CodeReference(r) = event {
+///         println!("{}:{}", r.file.display(), r.line);
+///         cancel.cancel(); // The first reference is enough
+///     }
+/// })
+/// .ok();
+/// ```
+pub struct SearchSession {
+    query: SearchQuery,
+    cancel: CancellationToken,
+}
+
+/// Iterator over the results of a session running on a background thread
+/// (see [`SearchSession::events`]). Dropping it cancels the session.
+pub struct SearchEvents {
+    events: mpsc::Receiver<Result<SearchEvent>>,
+    cancel: CancellationToken,
+}
+
+impl SearchSession {
+    pub fn new(query: SearchQuery) -> Self {
+        Self {
+            query,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stop the session once `token` is cancelled (default: a token of its
+    /// own, see [`cancellation`](Self::cancellation))
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// A token that cancels this session, for use from another thread or
+    /// from inside the event callback
+    pub fn cancellation(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Run the search, handing every result to `on_event` on the calling
+    /// thread. Fails with [`SearchError::Cancelled`] when the session is
+    /// cancelled before it finishes.
+    pub fn run(self, on_event: impl FnMut(SearchEvent)) -> Result<()> {
+        self.run_with(None, on_event)
+    }
+
+    /// Run the search on a background thread and iterate over its results.
+    /// An error, including [`SearchError::Cancelled`], ends the iteration.
+    pub fn events(self) -> SearchEvents {
+        let (tx, rx) = mpsc::channel();
+        let cancel = self.cancel.clone();
+        std::thread::spawn(move || {
+            let sent = self.run(|event| {
+                let _ = tx.send(Ok(event));
+            });
+            if let Err(e) = sent {
+                let _ = tx.send(Err(e));
+            }
+        });
+        SearchEvents { events: rx, cancel }
+    }
+
+    /// The session behind [`run`](Self::run). The query daemon passes the
+    /// `warm` state it keeps for the project, so the file list and parsed
+    /// translation files come from memory instead of the disk.
+    pub(crate) fn run_with(
+        self,
+        warm: Option<&WarmProject>,
+        mut on_event: impl FnMut(SearchEvent),
+    ) -> Result<()> {
+        let query = self.query;
+
+        // Determine the base directory to search
+        let raw_base_dir = query
+            .base_dir
+            .clone()
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+        // Handle case where base_dir is a file vs directory
+        let (search_dir, specific_file) = if raw_base_dir.is_file() {
+            // If it's a file, search in its parent directory but only that specific file
+            let parent_dir = raw_base_dir
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from("."));
+            (parent_dir, Some(raw_base_dir.clone()))
+        } else {
+            // If it's a directory, search the whole directory
+            (raw_base_dir.clone(), None)
+        };
+
+        // Use the search directory for project type detection
+        let project_type = config::detect_project_type(&search_dir);
+        let mut exclusions = query.config.exclusions(project_type);
+        exclusions.extend(query.exclude_patterns.clone());
+
+        // Step 1: Walk the project once, collecting translation files, key
+        // usages, direct text matches and file name matches
+        let mut scanner = ProjectScanner::new(search_dir.clone())
+            .case_sensitive(query.case_sensitive)
+            .word_match(query.word_match)
+            .is_regex(query.is_regex)
+            .before_context(query.before_context)
+            .after_context(query.after_context)
+            .add_globs(query.include_patterns.clone())
+            .add_exclusions(exclusions.clone())
+            .with_cancellation(self.cancel.clone());
+        if let Some(warm) = warm {
+            scanner = scanner.with_files(warm.files());
+        }
+        scanner = match &specific_file {
+            // Only search the specific file; translations are skipped
+            Some(file) => scanner.only_file(file.clone()),
+            None => scanner.with_patterns(query.config.patterns()),
+        };
+
+        // Direct matches are final unless an i18n call on the same line may
+        // turn out to use a matching key, so the others go out right away
+        let mut streamed = HashSet::new();
+        let scan = scanner.scan_each(&query.text, |file| {
+            if let Some(file_match) = &file.file_match {
+                on_event(SearchEvent::File(file_match.clone()));
+            }
+            let calls: HashSet<usize> = file
+                .key_usages
+                .iter()
+                .chain(&file.possible_usages)
+                .map(|r| r.line)
+                .collect();
+            for m in &file.direct_matches {
+                if is_code_match(m, specific_file.as_deref()) && !calls.contains(&m.line) {
+                    streamed.insert((m.file.clone(), m.line));
+                    on_event(SearchEvent::CodeReference(direct_reference(m, &query.text)));
+                }
+            }
+        })?;
+
+        // Step 2: Extract translation entries matching the search text
+        let translation_entries = if specific_file.is_none() {
+            let mut extractor = match warm {
+                Some(warm) => KeyExtractor::with_parsed_files(warm.parsed_files()),
+                None => KeyExtractor::new(),
+            };
+            extractor.set_exclusions(exclusions.clone());
+            extractor.set_verbose(query.verbose);
+            extractor.set_quiet(query.quiet);
+            extractor.set_case_sensitive(query.case_sensitive);
+            extractor.extract_from_files(&scan.locale_files, &query.text)?
+        } else {
+            Vec::new()
+        };
+        if self.cancel.is_cancelled() {
+            return Err(SearchError::Cancelled);
+        }
+
+        // Step 3: Keep the key usages for each translation entry
+        // Match full key AND partial keys (for namespace caching patterns). Key
+        // ids follow entry order, so sorting by id makes the dedup below keep the
+        // same reference for a line as searching key by key would. Calls that
+        // compute their key are possible usages of every key they could produce,
+        // and lose to an exact usage on the same line
+        let keys = KeyMatcher::with_partial_keys(&translation_entries);
+        for entry in translation_entries {
+            if self.cancel.is_cancelled() {
+                return Err(SearchError::Cancelled);
+            }
+            on_event(SearchEvent::Translation(entry));
+        }
+        let mut traced: Vec<(usize, CodeReference)> = scan
+            .key_usages
+            .into_iter()
+            .filter_map(|r| keys.id(&r.key_path).map(|id| (id, r)))
+            .collect();
+        for usage in scan.possible_usages {
+            for (id, key) in keys.keys().iter().enumerate() {
+                if key_matches_shape(&usage.key_path, key) {
+                    traced.push((
+                        id,
+                        CodeReference {
+                            key_path: key.clone(),
+                            ..usage.clone()
+                        },
+                    ));
+                }
+            }
+        }
+        traced.sort_by_key(|(id, r)| (r.confidence.is_possible(), *id));
+        let mut all_code_refs: Vec<CodeReference> = traced.into_iter().map(|(_, r)| r).collect();
+
+        // Step 4: Add the direct text matches that were held back
+        // This ensures we find hardcoded text even if no translation keys are found
+        all_code_refs.extend(
+            scan.direct_matches
+                .iter()
+                .filter(|m| is_code_match(m, specific_file.as_deref()))
+                .filter(|m| !streamed.contains(&(m.file.clone(), m.line)))
+                .map(|m| direct_reference(m, &query.text)),
+        );
+
+        // Deduplicate code references (in case same reference matches multiple key variations)
+        // We prioritize "traced" matches (where key_path != query) over "direct" matches (where key_path == query)
+        // This ensures that if we have both for the same line, we keep the one that links to a translation key.
+        all_code_refs.sort_by(|a, b| {
+            a.file.cmp(&b.file).then(a.line.cmp(&b.line)).then_with(|| {
+                let a_is_direct = a.key_path == query.text;
+                let b_is_direct = b.key_path == query.text;
+                // We want traced (false) to come before direct (true) so it is kept by dedup
+                a_is_direct.cmp(&b_is_direct)
+            })
+        });
+        all_code_refs.dedup_by(|a, b| a.file == b.file && a.line == b.line);
+
+        for reference in all_code_refs {
+            if self.cancel.is_cancelled() {
+                return Err(SearchError::Cancelled);
+            }
+            on_event(SearchEvent::CodeReference(reference));
+        }
+        if self.cancel.is_cancelled() {
+            return Err(SearchError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for SearchEvents {
+    type Item = Result<SearchEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.recv().ok()
+    }
+}
+
+impl Drop for SearchEvents {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Whether a direct text match counts as a code reference. Matches in
+/// translation files are reported as translation entries instead, unless
+/// the search is limited to a specific file.
+fn is_code_match(m: &Match, specific_file: Option<&Path>) -> bool {
+    let path_str = m.file.to_string_lossy();
+    specific_file.is_some()
+        || !(path_str.ends_with(".yml")
+            || path_str.ends_with(".yaml")
+            || path_str.ends_with(".json")
+            || path_str.ends_with(".js")
+            || parse::locale::is_catalog_file(&m.file))
+}
+
+/// Convert a direct text match to a code reference
+fn direct_reference(m: &Match, text: &str) -> CodeReference {
+    CodeReference {
+        file: m.file.clone(),
+        line: m.line,
+        pattern: "Direct Match".to_string(),
+        context: m.content.clone(),
+        key_path: text.to_string(), // Use the search text as the "key"
+        context_before: m.context_before.clone(),
+        context_after: m.context_after.clone(),
+        confidence: Confidence::Exact,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let locales = dir.path().join("config/locales");
+        fs::create_dir_all(&locales).unwrap();
+        fs::write(locales.join("en.yml"), "en:\n  add_new: \"Add New\"\n").unwrap();
+        fs::write(
+            dir.path().join("app.rb"),
+            "# Add New button\nlabel = I18n.t('add_new')\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("add_new.html"), "<p>Add New</p>\n").unwrap();
+        dir
+    }
+
+    fn query(dir: &TempDir) -> SearchQuery {
+        SearchQuery::new("Add New".to_string())
+            .with_base_dir(dir.path().to_path_buf())
+            .with_quiet(true)
+    }
+
+    #[test]
+    fn test_direct_matches_arrive_before_translations() {
+        let dir = project();
+        let mut events = Vec::new();
+        SearchSession::new(query(&dir))
+            .run(|event| events.push(event))
+            .unwrap();
+
+        let mut labels: Vec<String> = events
+            .iter()
+            .map(|event| match event {
+                SearchEvent::Translation(entry) => format!("translation {}", entry.key),
+                SearchEvent::CodeReference(r) => format!(
+                    "{}:{}",
+                    r.file.file_name().unwrap().to_string_lossy(),
+                    r.line
+                ),
+                SearchEvent::File(f) => format!("file {}", f.path.display()),
+            })
+            .collect();
+
+        // The comment and the HTML line come from the walk, in walk order
+        assert_eq!(labels.split_off(2), vec!["translation add_new", "app.rb:2"]);
+        labels.sort();
+        assert_eq!(labels, vec!["add_new.html:1", "app.rb:1"]);
+    }
+
+    #[test]
+    fn test_cancelled_session_stops_early() {
+        let dir = project();
+        let session = SearchSession::new(query(&dir));
+        session.cancellation().cancel();
+
+        let mut events = Vec::new();
+        let result = session.run(|event| events.push(event));
+        assert!(matches!(result, Err(SearchError::Cancelled)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_cancelling_from_the_callback_stops_the_search() {
+        let dir = project();
+        let session = SearchSession::new(query(&dir));
+        let cancel = session.cancellation();
+
+        // Stop as soon as the translation arrives: its code reference is dropped
+        let mut events = Vec::new();
+        let result = session.run(|event| {
+            if matches!(event, SearchEvent::Translation(_)) {
+                cancel.cancel();
+            }
+            events.push(event);
+        });
+        assert!(matches!(result, Err(SearchError::Cancelled)));
+        assert!(matches!(events.last(), Some(SearchEvent::Translation(_))));
+    }
+
+    #[test]
+    fn test_events_iterate_on_a_background_thread() {
+        let dir = project();
+        let events: Vec<SearchEvent> = SearchSession::new(query(&dir))
+            .events()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(events.len(), 4);
+    }
+}
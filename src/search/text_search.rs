@@ -33,13 +33,15 @@
 //! - Rust's ownership prevents data races at compile time
 
 use crate::error::{Result, SearchError};
-use grep_matcher::Matcher;
+use crate::search::cancel::CancellationToken;
+use crate::search::project_scan::surrounding_lines;
 use grep_regex::RegexMatcherBuilder;
 use grep_searcher::sinks::UTF8;
 use grep_searcher::SearcherBuilder;
 use ignore::overrides::OverrideBuilder;
 use ignore::WalkBuilder;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 
 /// Represents a single match from a text search.
@@ -50,7 +52,7 @@ use std::sync::mpsc;
 /// https://doc.rust-lang.org/book/ch05-01-defining-structs.html
 ///
 /// This is a simple data-carrying struct with public fields.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Match {
     /// File path where the match was found
     pub file: PathBuf,
@@ -105,8 +107,12 @@ pub struct TextSearcher {
     exclusions: Vec<String>,
     /// The base directory to search in
     base_dir: PathBuf,
-    /// Number of context lines to show before and after matches
-    context_lines: usize,
+    /// Number of context lines to show before matches
+    before_context: usize,
+    /// Number of context lines to show after matches
+    after_context: usize,
+    /// Stops the walk when cancelled
+    cancel: CancellationToken,
 }
 
 impl TextSearcher {
@@ -142,7 +148,9 @@ impl TextSearcher {
             globs: Vec::new(),
             exclusions: Vec::new(),
             base_dir,
-            context_lines: 2, // Default: 2 lines before and after
+            before_context: 2, // Default: 2 lines before and after
+            after_context: 2,
+            cancel: CancellationToken::new(),
         }
     }
 
@@ -222,7 +230,27 @@ impl TextSearcher {
 
     /// Set number of context lines to show before and after matches (default: 2)
     pub fn context_lines(mut self, lines: usize) -> Self {
-        self.context_lines = lines;
+        self.before_context = lines;
+        self.after_context = lines;
+        self
+    }
+
+    /// Set number of context lines to show before matches (default: 2)
+    pub fn before_context(mut self, lines: usize) -> Self {
+        self.before_context = lines;
+        self
+    }
+
+    /// Set number of context lines to show after matches (default: 2)
+    pub fn after_context(mut self, lines: usize) -> Self {
+        self.after_context = lines;
+        self
+    }
+
+    /// Stop the search once `token` is cancelled; [`search`](Self::search)
+    /// then fails with [`SearchError::Cancelled`]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
         self
     }
 
@@ -230,11 +258,33 @@ impl TextSearcher {
     ///
     /// # Rust Book Reference
     ///
+    /// **Chapter 13.1: Closures**
+    /// https://doc.rust-lang.org/book/ch13-01-closures.html
+    ///
+    /// This collects what [`search_each`](Self::search_each) hands out, using
+    /// a closure that mutably borrows `all_matches`.
+    ///
+    /// # Arguments
+    /// * `text` - The text to search for
+    ///
+    /// # Returns
+    /// A vector of Match structs containing file path, line number, and content
+    pub fn search(&self, text: &str) -> Result<Vec<Match>> {
+        let mut all_matches = Vec::new();
+        self.search_each(text, |m| all_matches.push(m))?;
+        Ok(all_matches)
+    }
+
+    /// Search for text, handing every match to `on_match` as soon as its
+    /// file has been searched.
+    ///
+    /// # Rust Book Reference
+    ///
     /// **Chapter 16.2: Message Passing with Channels**
     /// https://doc.rust-lang.org/book/ch16-02-message-passing.html
     ///
-    /// **Chapter 13.1: Closures**
-    /// https://doc.rust-lang.org/book/ch13-01-closures.html
+    /// **Chapter 16.1: Using Threads to Run Code Simultaneously**
+    /// https://doc.rust-lang.org/book/ch16-01-threads.html
     ///
     /// # Educational Notes - Concurrent Search with Channels
     ///
@@ -244,7 +294,8 @@ impl TextSearcher {
     /// 2. **Spawn workers**: Each thread gets a cloned sender (`tx.clone()`)
     /// 3. **Send results**: Workers send matches through the channel
     /// 4. **Drop original sender**: Critical for terminating the receiver
-    /// 5. **Collect results**: Main thread receives all matches
+    /// 5. **Receive results**: The calling thread receives matches while the
+    ///    walk is still running, because the walk runs on a scoped thread
     ///
     /// **Why channels instead of shared state?**
     /// - No locks needed (no `Mutex`)
@@ -252,12 +303,10 @@ impl TextSearcher {
     /// - Natural producer-consumer pattern
     /// - Rust's type system ensures thread safety
     ///
-    /// # Arguments
-    /// * `text` - The text to search for
-    ///
-    /// # Returns
-    /// A vector of Match structs containing file path, line number, and content
-    pub fn search(&self, text: &str) -> Result<Vec<Match>> {
+    /// `on_match` runs on the calling thread, so it needs to be neither `Send`
+    /// nor locked. One file's matches arrive together and in line order;
+    /// files arrive in whatever order the parallel walk finishes them.
+    pub fn search_each(&self, text: &str, mut on_match: impl FnMut(Match)) -> Result<()> {
         // Build the regex matcher with fixed string (literal) matching
         let matcher = RegexMatcherBuilder::new()
             .case_insensitive(!self.case_sensitive)
@@ -266,17 +315,10 @@ impl TextSearcher {
             .build(text)
             .map_err(|e| SearchError::Generic(format!("Failed to build matcher: {}", e)))?;
 
-        // Build searcher with context lines (for reference, but we use manual context capture)
-        let _searcher = SearcherBuilder::new()
-            .before_context(self.context_lines)
-            .after_context(self.context_lines)
-            .line_number(true)
-            .build();
-
         // CHANNEL CREATION: Create a channel for collecting matches from parallel threads
         // Chapter 16.2: mpsc = "multiple producer, single consumer"
         // tx (transmitter) can be cloned for each thread
-        // rx (receiver) stays in the main thread
+        // rx (receiver) stays in the calling thread
         let (tx, rx) = mpsc::channel();
 
         // Build parallel walker with .gitignore support
@@ -303,136 +345,110 @@ impl TextSearcher {
             }
         }
 
-        walk_builder.build_parallel().run(|| {
-            // CLONING FOR THREADS: Each thread gets its own sender and matcher
-            // Chapter 16.2: Clone tx so each thread can send messages
-            // Chapter 13.1: These clones will be moved into the closure below
-            let tx = tx.clone();
-            let matcher = matcher.clone();
-            let context_lines = self.context_lines;
-
-            // MOVE CLOSURE: Transfer ownership of tx and matcher to this thread
-            // Chapter 13.1: The `move` keyword forces the closure to take ownership
-            // Without `move`, the closure would try to borrow, which doesn't work across threads
-            Box::new(move |entry| {
-                use ignore::WalkState;
-
-                let entry = match entry {
-                    Ok(e) => e,
-                    Err(_) => return WalkState::Continue,
-                };
-
-                // Skip directories
-                if entry.file_type().is_none_or(|ft| ft.is_dir()) {
-                    return WalkState::Continue;
-                }
-
-                let path = entry.path();
-                let path_buf = path.to_path_buf();
-
-                // THREAD-LOCAL ACCUMULATOR: Each thread collects its own matches
-                // This avoids contention - no need for Mutex or Arc
-                let mut file_matches = Vec::new();
-
-                // Use grep-searcher to search the file with context
-                let mut searcher = SearcherBuilder::new()
-                    .before_context(context_lines)
-                    .after_context(context_lines)
-                    .line_number(true)
-                    .build();
-
-                let result = searcher.search_path(
-                    &matcher,
-                    path,
-                    UTF8(|line_num, line_content| {
-                        // line_content is already a &str from UTF8 sink
-                        let line_str = line_content;
-
-                        // For now, we'll collect all matches and handle context parsing later
-                        // The grep library provides context in the output, but we need to parse it
-                        file_matches.push(Match {
-                            file: path_buf.clone(),
-                            line: line_num as usize,
-                            content: line_str.trim_end().to_string(),
-                            context_before: Vec::new(), // Will be populated by post-processing
-                            context_after: Vec::new(),  // Will be populated by post-processing
-                        });
-
-                        Ok(true) // Continue searching
-                    }),
-                );
-
-                // SEND THROUGH CHANNEL: Send matches to main thread
-                // Chapter 16.2: tx.send() transfers ownership of file_matches
-                // The `let _ =` ignores send errors (receiver might be dropped)
-                if result.is_ok() && !file_matches.is_empty() {
-                    let _ = tx.send(file_matches);
-                }
-
-                WalkState::Continue
-            })
+        // SCOPED THREADS: The walk may borrow `self` and `matcher` because the
+        // scope guarantees it finishes before they go out of scope
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                walk_builder.build_parallel().run(|| {
+                    // CLONING FOR THREADS: Each thread gets its own sender and matcher
+                    // Chapter 16.2: Clone tx so each thread can send messages
+                    // Chapter 13.1: These clones will be moved into the closure below
+                    let tx = tx.clone();
+                    let matcher = matcher.clone();
+
+                    // MOVE CLOSURE: Transfer ownership of tx and matcher to this thread
+                    // Chapter 13.1: The `move` keyword forces the closure to take ownership
+                    // Without `move`, the closure would try to borrow, which doesn't work across threads
+                    Box::new(move |entry| {
+                        use ignore::WalkState;
+
+                        // Stop every walker thread once the search is cancelled
+                        if self.cancel.is_cancelled() {
+                            return WalkState::Quit;
+                        }
+
+                        let entry = match entry {
+                            Ok(e) => e,
+                            Err(_) => return WalkState::Continue,
+                        };
+
+                        // Skip directories
+                        if entry.file_type().is_none_or(|ft| ft.is_dir()) {
+                            return WalkState::Continue;
+                        }
+
+                        let path = entry.path();
+                        let path_buf = path.to_path_buf();
+
+                        // THREAD-LOCAL ACCUMULATOR: Each thread collects its own matches
+                        // This avoids contention - no need for Mutex or Arc
+                        let mut file_matches = Vec::new();
+
+                        // Use grep-searcher to find the matching lines
+                        let mut searcher = SearcherBuilder::new().line_number(true).build();
+
+                        let result = searcher.search_path(
+                            &matcher,
+                            path,
+                            UTF8(|line_num, line_content| {
+                                file_matches.push(Match {
+                                    file: path_buf.clone(),
+                                    line: line_num as usize,
+                                    content: line_content.trim_end().to_string(),
+                                    context_before: Vec::new(), // Filled in by add_context
+                                    context_after: Vec::new(),
+                                });
+
+                                Ok(true) // Continue searching
+                            }),
+                        );
+
+                        // SEND THROUGH CHANNEL: Send matches to the calling thread
+                        // Chapter 16.2: tx.send() transfers ownership of file_matches
+                        // The `let _ =` ignores send errors (receiver might be dropped)
+                        if result.is_ok() && !file_matches.is_empty() {
+                            self.add_context(path, &mut file_matches);
+                            let _ = tx.send(file_matches);
+                        }
+
+                        WalkState::Continue
+                    })
+                });
+
+                // CRITICAL: Drop the original sender so the receiving loop ends
+                // Chapter 16.2: The receiver's iterator only ends when ALL senders are dropped
+                // We cloned tx for each thread, but we still have the original here
+                // Without this drop, rx would wait forever!
+                drop(tx);
+            });
+
+            // RECEIVE RESULTS: Hand out matches while the walk goes on
+            // Chapter 16.2: The for loop iterates until all senders are dropped
+            for file_matches in rx {
+                file_matches.into_iter().for_each(&mut on_match);
+            }
         });
 
-        // CRITICAL: Drop the original sender so rx.iter() will terminate
-        // Chapter 16.2: The receiver's iterator only ends when ALL senders are dropped
-        // We cloned tx for each thread, but we still have the original here
-        // Without this drop, rx would wait forever!
-        drop(tx);
-
-        // COLLECT RESULTS: Receive all matches from worker threads
-        // Chapter 16.2: The for loop iterates until all senders are dropped
-        // This blocks until all threads finish and send their results
-        let mut all_matches = Vec::new();
-        for file_matches in rx {
-            all_matches.extend(file_matches);
+        if self.cancel.is_cancelled() {
+            return Err(SearchError::Cancelled);
         }
-
-        // Post-process to add context lines using a second pass
-        self.add_context_to_matches(&mut all_matches, &matcher)?;
-
-        Ok(all_matches)
+        Ok(())
     }
 
-    /// Add context lines to matches by re-reading files
-    fn add_context_to_matches(&self, matches: &mut [Match], _matcher: &impl Matcher) -> Result<()> {
-        use std::collections::HashMap;
-
-        // Group matches by file to minimize file reads
-        let mut matches_by_file: HashMap<PathBuf, Vec<usize>> = HashMap::new();
-        for (idx, m) in matches.iter().enumerate() {
-            matches_by_file.entry(m.file.clone()).or_default().push(idx);
-        }
-
-        // Process each file
-        for (file_path, match_indices) in matches_by_file {
-            if let Ok(content) = std::fs::read_to_string(&file_path) {
-                let lines: Vec<&str> = content.lines().collect();
-
-                for &match_idx in &match_indices {
-                    let match_ref = &mut matches[match_idx];
-                    let line_idx = match_ref.line.saturating_sub(1); // Convert to 0-indexed
-
-                    if line_idx < lines.len() {
-                        // Capture context lines
-                        let context_start = line_idx.saturating_sub(self.context_lines);
-                        let context_end =
-                            std::cmp::min(line_idx + self.context_lines + 1, lines.len());
-
-                        match_ref.context_before = lines[context_start..line_idx]
-                            .iter()
-                            .map(|s| s.to_string())
-                            .collect();
-
-                        match_ref.context_after = lines[line_idx + 1..context_end]
-                            .iter()
-                            .map(|s| s.to_string())
-                            .collect();
-                    }
-                }
+    /// Add context lines to one file's matches by reading the file again
+    fn add_context(&self, file: &Path, matches: &mut [Match]) {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            return;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+
+        for m in matches {
+            let line_idx = m.line.saturating_sub(1); // Convert to 0-indexed
+            if line_idx < lines.len() {
+                (m.context_before, m.context_after) =
+                    surrounding_lines(&lines, line_idx, self.before_context, self.after_context);
             }
         }
-
-        Ok(())
     }
 }
 
@@ -509,6 +525,31 @@ mod tests {
         assert_eq!(matches.len(), 0);
     }
 
+    #[test]
+    fn test_search_each_and_cancellation() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("test.txt"), "one\nfoo\nthree\nfoo").unwrap();
+
+        let mut lines = Vec::new();
+        TextSearcher::new(temp_dir.path().to_path_buf())
+            .search_each("foo", |m| lines.push((m.line, m.context_before)))
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (2, vec!["one".to_string()]),
+                (4, vec!["foo".to_string(), "three".to_string()])
+            ]
+        );
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = TextSearcher::new(temp_dir.path().to_path_buf())
+            .with_cancellation(token)
+            .search("foo");
+        assert!(matches!(result, Err(SearchError::Cancelled)));
+    }
+
     #[test]
     fn test_multiple_files() {
         let temp_dir = TempDir::new().unwrap();
@@ -581,7 +622,8 @@ mod tests {
 
         assert!(!searcher.case_sensitive);
         assert!(searcher.respect_gitignore);
-        assert_eq!(searcher.context_lines, 2);
+        assert_eq!(searcher.before_context, 2);
+        assert_eq!(searcher.after_context, 2);
     }
 
     #[test]
@@ -1,12 +1,23 @@
+use crate::cache::SymbolIndex;
 use crate::error::Result;
+use crate::parse::sitter::SupportedLanguage;
+use crate::parse::Sitter;
 use crate::search::TextSearcher;
 use regex::Regex;
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::FunctionDef;
 
+/// Caller name reported for calls outside any function
+pub(crate) const TOP_LEVEL: &str = "<top-level>";
+
+/// Keywords followed by parentheses that a method pattern can mistake for a name
+const CONTROL_KEYWORDS: [&str; 6] = ["if", "for", "while", "switch", "catch", "synchronized"];
+
 /// Information about a function that calls another function
 #[derive(Debug, Clone)]
 pub struct CallerInfo {
@@ -20,6 +31,9 @@ pub struct CallExtractor {
     searcher: TextSearcher,
     call_patterns: Vec<Regex>,
     pub keywords: HashSet<String>,
+    symbol_index: Option<Arc<SymbolIndex>>,
+    // Parsers are cached inside Sitter, which needs `&mut` even for lookups
+    sitter: RefCell<Sitter>,
 }
 
 impl CallExtractor {
@@ -32,9 +46,23 @@ impl CallExtractor {
             searcher: TextSearcher::new(base_dir),
             call_patterns: Self::default_call_patterns(),
             keywords: Self::common_keywords(),
+            symbol_index: None,
+            sitter: RefCell::new(Sitter::new()),
         }
     }
 
+    /// Parse extra file extensions with the given Tree-sitter languages
+    pub fn with_language_map(mut self, map: HashMap<String, SupportedLanguage>) -> Self {
+        self.sitter = RefCell::new(self.sitter.into_inner().with_language_map(map));
+        self
+    }
+
+    /// Answer `find_callers` from a prebuilt symbol index instead of scanning files
+    pub fn with_symbol_index(mut self, index: Arc<SymbolIndex>) -> Self {
+        self.symbol_index = Some(index);
+        self
+    }
+
     /// Default patterns for finding function calls across languages
     fn default_call_patterns() -> Vec<Regex> {
         vec![
@@ -149,6 +177,26 @@ impl CallExtractor {
             "Float",
             "Numeric",
             "File",
+            // Go keywords and built-ins
+            "func",
+            "go",
+            "defer",
+            "range",
+            "chan",
+            "struct",
+            "package",
+            "make",
+            "len",
+            "cap",
+            "append",
+            "panic",
+            "recover",
+            // Java keywords
+            "void",
+            "static",
+            "final",
+            "synchronized",
+            "throws",
             // Common programming constructs
             "return",
             "new",
@@ -164,10 +212,33 @@ impl CallExtractor {
     /// Extract function calls from a function body
     ///
     /// Reads the function definition and extracts all function calls within its body.
+    /// Languages supported by Tree-sitter use call expressions from the syntax tree;
+    /// other languages fall back to regex line scanning.
     /// Filters out language keywords and built-in functions.
     pub fn extract_calls(&self, func: &FunctionDef) -> Result<Vec<String>> {
         // Read the file
         let content = fs::read_to_string(&func.file)?;
+
+        let syntax_calls = self
+            .sitter
+            .borrow_mut()
+            .find_calls_in_function(&func.file, &content, &func.name, func.line);
+        if let Ok(Some(names)) = syntax_calls {
+            return Ok(names
+                .into_iter()
+                .filter(|name| {
+                    self.is_valid_function_name(name)
+                        && !self.keywords.contains(name)
+                        && *name != func.name
+                })
+                .collect());
+        }
+
+        self.extract_calls_with_regex(func, &content)
+    }
+
+    /// Regex fallback for languages without a Tree-sitter grammar
+    fn extract_calls_with_regex(&self, func: &FunctionDef, content: &str) -> Result<Vec<String>> {
         let lines: Vec<&str> = content.lines().collect();
 
         // Find the function body - be smarter about detecting function boundaries
@@ -334,11 +405,36 @@ impl CallExtractor {
         // Generate case variants for cross-case searching
         let variants = Self::generate_case_variants(func_name);
 
+        if let Some(index) = &self.symbol_index {
+            for variant in variants {
+                for site in index.call_sites(&variant) {
+                    if !callers.iter().any(|existing: &CallerInfo| {
+                        existing.caller_name == site.caller_name
+                            && existing.file == site.file
+                            && existing.line == site.line
+                    }) {
+                        callers.push(site.clone());
+                    }
+                }
+            }
+            return Ok(callers);
+        }
+
         // Search for each variant
         for variant in variants {
             let matches = self.searcher.search(&variant)?;
+            let mut parsed_files = HashSet::new();
 
             for m in matches {
+                // Languages with a Tree-sitter grammar take their call sites
+                // from the syntax tree, like the symbol index does
+                if self.sitter.borrow().is_supported(&m.file) {
+                    if parsed_files.insert(m.file.clone()) {
+                        self.add_syntax_callers(&m.file, &variant, &mut callers);
+                    }
+                    continue;
+                }
+
                 // Skip comment lines (JavaScript //, Ruby/Python #)
                 let trimmed = m.content.trim();
                 if trimmed.starts_with("//") || trimmed.starts_with("#") {
@@ -353,11 +449,7 @@ impl CallExtractor {
                 }
 
                 // Skip function definition lines where the variant is being defined
-                if (trimmed.starts_with("function ")
-                    || trimmed.starts_with("def ")
-                    || trimmed.starts_with("fn "))
-                    && trimmed.contains(&variant)
-                {
+                if Self::is_definition_line(trimmed) && trimmed.contains(&variant) {
                     continue;
                 }
 
@@ -382,6 +474,30 @@ impl CallExtractor {
         Ok(callers)
     }
 
+    /// Add the calls to `callee` found in the syntax tree of `file`
+    fn add_syntax_callers(&self, file: &PathBuf, callee: &str, callers: &mut Vec<CallerInfo>) {
+        let Ok(content) = fs::read_to_string(file) else {
+            return;
+        };
+        let calls = self
+            .sitter
+            .borrow_mut()
+            .find_calls(file, &content)
+            .unwrap_or_default();
+        for call in calls.into_iter().filter(|call| call.callee == callee) {
+            if !callers
+                .iter()
+                .any(|existing| existing.file == *file && existing.line == call.line)
+            {
+                callers.push(CallerInfo {
+                    caller_name: call.caller.unwrap_or_else(|| TOP_LEVEL.to_string()),
+                    file: file.clone(),
+                    line: call.line,
+                });
+            }
+        }
+    }
+
     /// Find the function that contains a given line (simplified implementation)
     ///
     /// Searches backwards from the given line to find the most recent function definition.
@@ -390,8 +506,16 @@ impl CallExtractor {
 
         let lines: Vec<&str> = content.lines().collect();
 
-        // Search backwards from the line to find a function definition
-        let function_patterns = vec![
+        Ok(Self::containing_function_name(
+            &Self::containing_function_patterns(),
+            &lines,
+            line,
+        ))
+    }
+
+    /// Patterns used to recognise the enclosing function of a call site
+    pub(crate) fn containing_function_patterns() -> Vec<Regex> {
+        vec![
             // JavaScript/TypeScript patterns
             Regex::new(r"function\s+(\w+)").unwrap(),
             Regex::new(r"(?:const|let|var)\s+(\w+)\s*=\s*(?:async\s+)?\([^)]*\)\s*=>").unwrap(),
@@ -409,8 +533,23 @@ impl CallExtractor {
             Regex::new(r"^\s*(\w+)\s*\([^)]*\)\s*\{").unwrap(),
             // Rust pattern (for completeness)
             Regex::new(r"fn\s+(\w+)").unwrap(),
-        ];
+        ]
+    }
+
+    /// Whether the trimmed `line` defines a function rather than calling one,
+    /// e.g. `function name(`, `def name` or `fn name(`.
+    pub(crate) fn is_definition_line(line: &str) -> bool {
+        ["function ", "def ", "fn "]
+            .iter()
+            .any(|keyword| line.starts_with(keyword))
+    }
 
+    /// Name of the function enclosing the 1-based `line`, or `"<top-level>"`
+    pub(crate) fn containing_function_name(
+        function_patterns: &[Regex],
+        lines: &[&str],
+        line: usize,
+    ) -> String {
         // Search backwards up to 100 lines or start of file
         let start = line.saturating_sub(100);
         for i in (start..line.saturating_sub(1)).rev() {
@@ -419,17 +558,21 @@ impl CallExtractor {
             }
 
             let line_content = lines[i];
-            for pattern in &function_patterns {
+            for pattern in function_patterns {
                 if let Some(captures) = pattern.captures(line_content) {
                     if let Some(name_match) = captures.get(1) {
-                        return Ok(name_match.as_str().to_string());
+                        // `if (x) {` on its own line looks like a method
+                        if CONTROL_KEYWORDS.contains(&name_match.as_str()) {
+                            continue;
+                        }
+                        return name_match.as_str().to_string();
                     }
                 }
             }
         }
 
         // If no containing function found, it might be top-level code
-        Ok("<top-level>".to_string())
+        TOP_LEVEL.to_string()
     }
 
     /// Generate case variants of a function name for cross-case searching
@@ -1,26 +1,86 @@
+use crate::cache::SymbolIndex;
 use crate::error::{Result, SearchError};
+use crate::parse::sitter::SupportedLanguage;
 use crate::parse::Sitter; // Import Sitter
 use crate::search::TextSearcher;
 use regex::Regex;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 /// Represents a function definition found in code
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FunctionDef {
     pub name: String,
+    /// Enclosing classes, modules, impl blocks and namespaces, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scope: Vec<String>,
     pub file: PathBuf,
     pub line: usize,
+    /// Source text from the definition onwards (not serialized; it can be the whole file tail)
+    #[serde(skip)]
     pub body: String,
 }
 
+impl FunctionDef {
+    /// The name with its scope, spelled the way the file's language would
+    /// (`Billing::Invoice#total`, `invoice::Invoice::total`, `Invoice.total`)
+    pub fn qualified_name(&self) -> String {
+        if self.scope.is_empty() {
+            return self.name.clone();
+        }
+        let extension = self.file.extension().and_then(|ext| ext.to_str());
+        match extension {
+            Some("rs") => format!("{}::{}", self.scope.join("::"), self.name),
+            Some("rb") => format!("{}#{}", self.scope.join("::"), self.name),
+            Some("php") => format!("{}::{}", self.scope.join("\\"), self.name),
+            _ => format!("{}.{}", self.scope.join("."), self.name),
+        }
+    }
+}
+
+/// A function name as typed by the user, optionally qualified by its
+/// enclosing scopes: `total`, `Invoice#total`, `invoice::Invoice::total`,
+/// `InvoiceService.Total` or `App\\Billing\\Invoice::total`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualifiedName {
+    /// Scopes given before the name, outermost first
+    pub scope: Vec<String>,
+    pub name: String,
+}
+
+impl QualifiedName {
+    pub fn parse(query: &str) -> Self {
+        let mut segments: Vec<String> = query
+            .split(['#', '.', '\\', ':'])
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| segment.trim().to_string())
+            .collect();
+        let name = segments.pop().unwrap_or_else(|| query.to_string());
+        Self {
+            scope: segments,
+            name,
+        }
+    }
+
+    /// Whether `def` lives in the scopes given by the query.
+    ///
+    /// The query may leave out outer scopes, so `Invoice#total` matches
+    /// `total` in `Billing::Invoice`.
+    pub fn matches_scope(&self, def: &FunctionDef) -> bool {
+        def.scope.ends_with(&self.scope)
+    }
+}
+
 /// Finds function definitions in code using Tree-sitter (primary) and pattern matching (fallback)
 pub struct FunctionFinder {
     searcher: TextSearcher,
     patterns: Vec<Regex>,
     base_dir: PathBuf,
     sitter: Sitter,
+    symbol_index: Option<Arc<SymbolIndex>>,
 }
 
 impl FunctionFinder {
@@ -34,11 +94,24 @@ impl FunctionFinder {
             patterns: Self::default_patterns(),
             base_dir,
             sitter: Sitter::new(),
+            symbol_index: None,
         }
     }
 
+    /// Parse extra file extensions with the given Tree-sitter languages
+    pub fn with_language_map(mut self, map: HashMap<String, SupportedLanguage>) -> Self {
+        self.sitter = self.sitter.with_language_map(map);
+        self
+    }
+
+    /// Look definitions up in a prebuilt symbol index instead of scanning files
+    pub fn with_symbol_index(mut self, index: Arc<SymbolIndex>) -> Self {
+        self.symbol_index = Some(index);
+        self
+    }
+
     /// Default patterns for finding function definitions across languages
-    fn default_patterns() -> Vec<Regex> {
+    pub(crate) fn default_patterns() -> Vec<Regex> {
         vec![
             // JavaScript/TypeScript - function declarations
             Regex::new(r"function\s+(\w+)\s*\(").unwrap(),
@@ -119,30 +192,88 @@ impl FunctionFinder {
         result
     }
 
-    /// Find a single function definition, preferring exact matches
+    /// Find a single function definition, preferring exact matches.
+    ///
+    /// When several functions match, the first by file and line is returned;
+    /// use [`find_functions`](Self::find_functions) to see all of them.
     pub fn find_function(&mut self, func_name: &str) -> Option<FunctionDef> {
-        if let Ok(mut defs) = self.find_definition(func_name) {
-            if let Some(def) = defs.pop() {
-                return Some(def);
-            }
-        }
-        let variants = Self::generate_case_variants(func_name);
-        for variant in variants {
-            if variant != func_name {
-                if let Ok(mut defs) = self.find_definition(&variant) {
-                    if let Some(def) = defs.pop() {
-                        return Some(def);
-                    }
+        self.find_functions(func_name).into_iter().next()
+    }
+
+    /// Find every definition matching a possibly qualified name (see
+    /// [`QualifiedName`]), sorted by file and line.
+    ///
+    /// Exact name matches win; case variants (`create_user` for `createUser`)
+    /// are only tried when the exact name has no definition in scope.
+    pub fn find_functions(&mut self, query: &str) -> Vec<FunctionDef> {
+        let qualified = QualifiedName::parse(query);
+        let mut names = vec![qualified.name.clone()];
+        let mut variants = Self::generate_case_variants(&qualified.name);
+        variants.sort();
+        names.extend(variants.into_iter().filter(|v| *v != qualified.name));
+
+        for name in names {
+            if let Ok(defs) = self.find_definition(&name) {
+                let in_scope: Vec<FunctionDef> = defs
+                    .into_iter()
+                    .filter(|def| qualified.matches_scope(def))
+                    .collect();
+                if !in_scope.is_empty() {
+                    return in_scope;
                 }
             }
         }
-        None
+        Vec::new()
+    }
+
+    /// Find the definition a call to `func_name` made at `file:line` most
+    /// likely refers to: the closest one above the call in the same file,
+    /// then any in the same file, then the first one elsewhere.
+    pub fn find_function_from(
+        &mut self,
+        func_name: &str,
+        file: &Path,
+        line: usize,
+    ) -> Option<FunctionDef> {
+        let mut defs = self.find_functions(func_name);
+        let same_file = |def: &FunctionDef| def.file == file;
+        let above = defs
+            .iter()
+            .rposition(|def| same_file(def) && def.line <= line)
+            .or_else(|| defs.iter().position(same_file))
+            .unwrap_or(0);
+        (above < defs.len()).then(|| defs.swap_remove(above))
     }
 
     /// Find all definitions of a function by name
     pub fn find_definition(&mut self, func_name: &str) -> Result<Vec<FunctionDef>> {
         let mut results = Vec::new();
 
+        if let Some(index) = &self.symbol_index {
+            for location in index.definitions(func_name) {
+                if Self::is_skipped_path(&self.base_dir, &location.file) {
+                    continue;
+                }
+                // Skip files that became unreadable since the index was refreshed
+                let Ok(file_content) = fs::read_to_string(&location.file) else {
+                    continue;
+                };
+                let body = file_content
+                    .lines()
+                    .skip(location.line - 1)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                results.push(FunctionDef {
+                    name: func_name.to_string(),
+                    scope: location.scope.clone(),
+                    file: location.file.clone(),
+                    line: location.line,
+                    body,
+                });
+            }
+            return Self::sorted_or_not_found(func_name, results);
+        }
+
         // 1. Search for files containing the function name
         // We still use grep to find candidate files quickly
         let matches = self.searcher.search(func_name)?;
@@ -150,24 +281,8 @@ impl FunctionFinder {
         // 2. Process each candidate file
         for m in matches {
             // Filter out tools/tests (same logic as before)
-            // Convert absolute path to relative path for filtering
-            let relative_path_buf = match m.file.strip_prefix(&self.base_dir) {
-                Ok(rel_path) => rel_path.to_path_buf(),
-                Err(_) => m.file.clone(),
-            };
-            let path_components: Vec<_> = relative_path_buf
-                .components()
-                .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
-                .collect();
-            if !path_components.is_empty() {
-                if path_components[0] == "src" {
-                    continue;
-                }
-                if path_components[0] == "tests"
-                    && (path_components.len() < 2 || path_components[1] != "fixtures")
-                {
-                    continue;
-                }
+            if Self::is_skipped_path(&self.base_dir, &m.file) {
+                continue;
             }
 
             let file_content = fs::read_to_string(&m.file)?;
@@ -188,6 +303,7 @@ impl FunctionFinder {
 
                             results.push(FunctionDef {
                                 name: func.name,
+                                scope: func.scope,
                                 file: m.file.clone(),
                                 line: func.start_line,
                                 body,
@@ -211,13 +327,17 @@ impl FunctionFinder {
                     if let Some(captures) = pattern.captures(content) {
                         if let Some(name_match) = captures.get(1) {
                             if name_match.as_str() == func_name {
-                                let body = file_content
-                                    .lines()
-                                    .skip(m.line - 1)
-                                    .collect::<Vec<_>>()
-                                    .join("\n");
+                                let lines: Vec<&str> = file_content.lines().collect();
+                                // The file may have shrunk since it was searched
+                                let Some(rest) =
+                                    lines.get(m.line - 1..).filter(|rest| !rest.is_empty())
+                                else {
+                                    break;
+                                };
+                                let body = rest.join("\n");
                                 results.push(FunctionDef {
                                     name: func_name.to_string(),
+                                    scope: Self::enclosing_scope(&lines, m.line - 1),
                                     file: m.file.clone(),
                                     line: m.line,
                                     body,
@@ -230,6 +350,13 @@ impl FunctionFinder {
             }
         }
 
+        Self::sorted_or_not_found(func_name, results)
+    }
+
+    fn sorted_or_not_found(
+        func_name: &str,
+        mut results: Vec<FunctionDef>,
+    ) -> Result<Vec<FunctionDef>> {
         if results.is_empty() {
             Err(SearchError::Generic(format!(
                 "Function '{}' not found",
@@ -237,9 +364,70 @@ impl FunctionFinder {
             )))
         } else {
             results.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+            // A file with several lines naming the function is parsed once per line
+            results.dedup_by(|a, b| a.file == b.file && a.line == b.line);
             Ok(results)
         }
     }
+
+    /// Enclosing scopes of the definition on `lines[idx]` in languages
+    /// without a Tree-sitter grammar, outermost first.
+    ///
+    /// The file's `package`/`namespace` comes first, then the less indented
+    /// `class`/`interface`/`object` declarations around the definition.
+    pub(crate) fn enclosing_scope(lines: &[&str], idx: usize) -> Vec<String> {
+        static FILE_SCOPE: OnceLock<Regex> = OnceLock::new();
+        static TYPE_DECL: OnceLock<Regex> = OnceLock::new();
+        let file_scope = FILE_SCOPE.get_or_init(|| {
+            Regex::new(r"^\s*(?:package|namespace)\s+([\w.\\]+)\s*;?\s*$").unwrap()
+        });
+        let type_decl = TYPE_DECL.get_or_init(|| {
+            Regex::new(r"\b(?:class|interface|trait|enum|object|record)\s+(\w+)").unwrap()
+        });
+
+        let mut scope: Vec<String> = lines[..idx]
+            .iter()
+            .find_map(|line| file_scope.captures(line))
+            .map(|c| c[1].split(['.', '\\']).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let indent = |line: &str| line.len() - line.trim_start().len();
+        let mut types = Vec::new();
+        let mut threshold = indent(lines[idx]);
+        for line in lines[..idx].iter().rev() {
+            if threshold == 0 {
+                break;
+            }
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('*') {
+                continue;
+            }
+            if indent(line) < threshold {
+                threshold = indent(line);
+                if let Some(decl) = type_decl.captures(trimmed) {
+                    types.push(decl[1].to_string());
+                }
+            }
+        }
+        scope.extend(types.into_iter().rev());
+        scope
+    }
+
+    /// Whether `file` lives in the tool's own `src/` or non-fixture `tests/` tree
+    pub(crate) fn is_skipped_path(base_dir: &Path, file: &Path) -> bool {
+        // Convert absolute path to relative path for filtering
+        let relative_path = file.strip_prefix(base_dir).unwrap_or(file);
+        let path_components: Vec<_> = relative_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+            .collect();
+        if path_components.is_empty() {
+            return false;
+        }
+        path_components[0] == "src"
+            || (path_components[0] == "tests"
+                && (path_components.len() < 2 || path_components[1] != "fixtures"))
+    }
 }
 
 impl Default for FunctionFinder {
@@ -264,6 +452,93 @@ mod tests {
         assert_eq!(patterns.len(), 9);
     }
 
+    #[test]
+    fn test_qualified_name_parse() {
+        for (query, scope, name) in [
+            ("total", vec![], "total"),
+            ("Invoice#total", vec!["Invoice"], "total"),
+            (
+                "invoice::Invoice::total",
+                vec!["invoice", "Invoice"],
+                "total",
+            ),
+            ("InvoiceService.Total", vec!["InvoiceService"], "Total"),
+            (
+                "App\\Billing\\Invoice::total",
+                vec!["App", "Billing", "Invoice"],
+                "total",
+            ),
+        ] {
+            let parsed = QualifiedName::parse(query);
+            assert_eq!(parsed.scope, scope, "{}", query);
+            assert_eq!(parsed.name, name, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_enclosing_scope_without_tree_sitter() {
+        let kotlin = [
+            "package com.acme.billing",
+            "",
+            "class Invoice {",
+            "    fun total(): Int {",
+            "        return 0",
+            "    }",
+            "",
+            "    object Line {",
+            "        fun total(): Int {",
+            "            return 1",
+            "        }",
+            "    }",
+            "}",
+            "fun helper(): Int {",
+        ];
+        assert_eq!(
+            FunctionFinder::enclosing_scope(&kotlin, 3),
+            vec!["com", "acme", "billing", "Invoice"]
+        );
+        assert_eq!(
+            FunctionFinder::enclosing_scope(&kotlin, 8),
+            vec!["com", "acme", "billing", "Invoice", "Line"]
+        );
+        assert_eq!(
+            FunctionFinder::enclosing_scope(&kotlin, 13),
+            vec!["com", "acme", "billing"]
+        );
+
+        let php = [
+            "<?php",
+            "namespace App\\Billing;",
+            "class Invoice {",
+            "    public function total() {",
+        ];
+        assert_eq!(
+            FunctionFinder::enclosing_scope(&php, 3),
+            vec!["App", "Billing", "Invoice"]
+        );
+    }
+
+    #[test]
+    fn test_indexed_definitions_skip_unreadable_files() {
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let kept = project.path().join("a.js");
+        let removed = project.path().join("b.js");
+        fs::write(&kept, "function helper() {\n  return 1;\n}\n").unwrap();
+        fs::write(&removed, "function helper() {\n  return 2;\n}\n").unwrap();
+
+        let mut index =
+            SymbolIndex::open_in(cache_dir.path().to_path_buf(), project.path()).unwrap();
+        index.refresh().unwrap();
+        fs::remove_file(&removed).unwrap();
+
+        let mut finder =
+            FunctionFinder::new(project.path().to_path_buf()).with_symbol_index(Arc::new(index));
+        let defs = finder.find_definition("helper").unwrap();
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].file, kept);
+    }
+
     #[test]
     fn test_js_function_pattern() {
         let patterns = FunctionFinder::default_patterns();
@@ -38,19 +38,24 @@
 
 use crate::error::Result;
 use crate::trace::{CallExtractor, FunctionDef, FunctionFinder};
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 
 /// Direction of the call graph trace
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TraceDirection {
     /// Trace forward: which functions does this function call?
     Forward,
     /// Trace backward: which functions call this function?
     Backward,
+    /// Trace both ways: callers above the function and callees below it
+    Both,
 }
 
 /// A node in the call graph tree
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallNode {
     /// The function definition for this node
     pub def: FunctionDef,
@@ -61,10 +66,83 @@ pub struct CallNode {
 }
 
 /// Represents a complete call graph tree
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CallTree {
     /// The root node of the tree (the starting function)
     pub root: CallNode,
+    /// Caller subtrees of the root, only populated for `TraceDirection::Both`.
+    ///
+    /// In that mode `root.children` holds the callees and each node here is a
+    /// backward trace (its children are its own callers).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub callers: Vec<CallNode>,
+}
+
+/// Call chains found by [`CallGraphBuilder::find_paths`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallPaths {
+    /// Every function on some path, each once
+    pub functions: Vec<FunctionDef>,
+    /// Every distinct chain from the start function to a target, as indices
+    /// into `functions`, each starting with the start function, shortest first
+    pub paths: Vec<Vec<usize>>,
+    /// Whether the node budget or the path limit ran out before the search
+    /// was complete
+    pub truncated: bool,
+}
+
+impl CallPaths {
+    /// The functions along each path, in order
+    pub fn chains(&self) -> impl Iterator<Item = Vec<&FunctionDef>> + '_ {
+        self.paths
+            .iter()
+            .map(|path| path.iter().map(|&i| &self.functions[i]).collect())
+    }
+
+    /// Add the paths of another search, e.g. from another definition of the
+    /// start function, keeping the shortest first
+    pub fn merge(&mut self, other: CallPaths) {
+        let offset = self.functions.len();
+        self.functions.extend(other.functions);
+        self.paths.extend(
+            other
+                .paths
+                .into_iter()
+                .map(|path| path.into_iter().map(|i| i + offset).collect()),
+        );
+        self.paths.sort_by_key(Vec::len);
+        self.truncated |= other.truncated;
+    }
+}
+
+/// Identifies a function during a path search without hashing its body
+type FunctionKey = (PathBuf, usize, String);
+
+/// State of a [`CallGraphBuilder::find_paths`] search. Functions are
+/// referred to by their index in `functions`.
+struct PathSearch {
+    functions: Vec<FunctionDef>,
+    ids: HashMap<FunctionKey, usize>,
+    targets: HashSet<usize>,
+    /// Visits left before the search gives up
+    budget: usize,
+    max_paths: usize,
+    /// Callees of every expanded function, so each is resolved only once
+    callees: HashMap<usize, Vec<usize>>,
+    paths: Vec<Vec<usize>>,
+    truncated: bool,
+}
+
+impl PathSearch {
+    /// The index of `def`, adding it on first sight
+    fn id(&mut self, def: FunctionDef) -> usize {
+        let key = (def.file.clone(), def.line, def.name.clone());
+        let functions = &mut self.functions;
+        *self.ids.entry(key).or_insert_with(|| {
+            functions.push(def);
+            functions.len() - 1
+        })
+    }
 }
 
 /// Builds a call graph by recursively tracing function calls.
@@ -178,10 +256,28 @@ impl<'a> CallGraphBuilder<'a> {
     pub fn build_trace(&mut self, start_fn: &FunctionDef) -> Result<Option<CallTree>> {
         let mut current_path = HashSet::new();
 
-        match self.build_node(start_fn, 0, &mut current_path) {
-            Some(root) => Ok(Some(CallTree { root })),
-            None => Ok(None),
+        if self.direction != TraceDirection::Both {
+            let direction = self.direction.clone();
+            return match self.build_node(start_fn, 0, &direction, &mut current_path) {
+                Some(root) => Ok(Some(CallTree {
+                    root,
+                    callers: vec![],
+                })),
+                None => Ok(None),
+            };
         }
+
+        // Both directions: callees hang off the root as usual, callers are
+        // traced separately so each side gets the full depth budget.
+        let Some(root) = self.build_node(start_fn, 0, &TraceDirection::Forward, &mut current_path)
+        else {
+            return Ok(None);
+        };
+
+        current_path.insert(start_fn.clone());
+        let callers = self.build_backward_children(start_fn, 0, &mut current_path);
+
+        Ok(Some(CallTree { root, callers }))
     }
 
     /// Recursively build a call tree node
@@ -192,6 +288,7 @@ impl<'a> CallGraphBuilder<'a> {
         &mut self,
         func: &FunctionDef,
         depth: usize,
+        direction: &TraceDirection,
         current_path: &mut HashSet<FunctionDef>,
     ) -> Option<CallNode> {
         // Check depth limit
@@ -215,9 +312,12 @@ impl<'a> CallGraphBuilder<'a> {
         // Add to current path for cycle detection
         current_path.insert(func.clone());
 
-        let children = match self.direction {
+        let children = match direction {
             TraceDirection::Forward => self.build_forward_children(func, depth, current_path),
             TraceDirection::Backward => self.build_backward_children(func, depth, current_path),
+            // Callers of a bidirectional trace are collected by `build_trace`,
+            // so below the root it behaves like a forward trace
+            TraceDirection::Both => self.build_forward_children(func, depth, current_path),
         };
 
         // Remove from current path (allows same function in different branches)
@@ -237,26 +337,144 @@ impl<'a> CallGraphBuilder<'a> {
         depth: usize,
         current_path: &mut HashSet<FunctionDef>,
     ) -> Vec<CallNode> {
+        let mut children = Vec::new();
+
+        for called_func in self.callees(func) {
+            // Recursively build the child node
+            if let Some(child_node) = self.build_node(
+                &called_func,
+                depth + 1,
+                &TraceDirection::Forward,
+                current_path,
+            ) {
+                children.push(child_node);
+            }
+        }
+
+        children
+    }
+
+    /// Definitions of the functions called from `func`'s body
+    fn callees(&mut self, func: &FunctionDef) -> Vec<FunctionDef> {
         // Extract function calls from this function's body
         let call_names = match self.extractor.extract_calls(func) {
             Ok(calls) => calls,
-            Err(_) => return vec![], // If extraction fails, return empty children
+            Err(_) => return vec![], // If extraction fails, there are no callees
         };
 
-        let mut children = Vec::new();
+        // Find the definition of each called function, preferring one next to
+        // the caller when several share the name. Functions that cannot be
+        // found are simply left out (graceful handling).
+        call_names
+            .iter()
+            .filter_map(|call_name| {
+                self.finder
+                    .find_function_from(call_name, &func.file, func.line)
+            })
+            .collect()
+    }
 
-        for call_name in call_names {
-            // Find the definition of the called function
-            if let Some(called_func) = self.finder.find_function(&call_name) {
-                // Recursively build the child node
-                if let Some(child_node) = self.build_node(&called_func, depth + 1, current_path) {
-                    children.push(child_node);
-                }
+    /// Find every call chain from `start` to any of `targets`, following calls
+    /// forward up to the builder's depth.
+    ///
+    /// The search is breadth-first, so shorter chains are found before longer
+    /// ones and a limit only ever drops the longest. Cycles are cut the same
+    /// way as in [`build_trace`](Self::build_trace), so every chain visits a
+    /// function at most once. Every function reached counts against
+    /// `max_nodes`, and at most `max_paths` chains are kept; the result is
+    /// marked as truncated when a limit stops the search before it is done.
+    pub fn find_paths(
+        &mut self,
+        start: &FunctionDef,
+        targets: &[FunctionDef],
+        max_nodes: usize,
+        max_paths: usize,
+    ) -> CallPaths {
+        let mut search = PathSearch {
+            functions: Vec::new(),
+            ids: HashMap::new(),
+            targets: HashSet::new(),
+            budget: max_nodes,
+            max_paths,
+            callees: HashMap::new(),
+            paths: Vec::new(),
+            truncated: false,
+        };
+        for target in targets {
+            let id = search.id(target.clone());
+            search.targets.insert(id);
+        }
+        let start = search.id(start.clone());
+        self.search_paths(start, &mut search);
+
+        // Two call names resolving to the same definition give the same chain
+        let mut seen = HashSet::new();
+        let mut paths = search.paths;
+        paths.retain(|path| seen.insert(path.clone()));
+
+        // Keep only the functions on some path
+        let mut found = CallPaths {
+            truncated: search.truncated,
+            ..CallPaths::default()
+        };
+        let mut renumbered = HashMap::new();
+        for mut path in paths {
+            for id in &mut path {
+                *id = *renumbered.entry(*id).or_insert_with(|| {
+                    found.functions.push(search.functions[*id].clone());
+                    found.functions.len() - 1
+                });
             }
-            // If function not found, we simply don't include it (graceful handling)
+            found.paths.push(path);
         }
+        found
+    }
 
-        children
+    /// Expand chains from `start` one call at a time, shortest first
+    fn search_paths(&mut self, start: usize, search: &mut PathSearch) {
+        let mut queue = VecDeque::from([vec![start]]);
+        while let Some(chain) = queue.pop_front() {
+            if chain.len() > self.max_depth {
+                continue;
+            }
+            let func = chain[chain.len() - 1];
+            let callees = match search.callees.get(&func) {
+                Some(callees) => callees.clone(),
+                None => {
+                    let def = search.functions[func].clone();
+                    let callees: Vec<usize> = self
+                        .callees(&def)
+                        .into_iter()
+                        .map(|callee| search.id(callee))
+                        .collect();
+                    search.callees.insert(func, callees.clone());
+                    callees
+                }
+            };
+
+            for callee in callees {
+                if chain.contains(&callee) {
+                    continue;
+                }
+                if search.budget == 0 {
+                    search.truncated = true;
+                    return;
+                }
+                search.budget -= 1;
+
+                let mut next = chain.clone();
+                next.push(callee);
+                if search.targets.contains(&callee) {
+                    if search.paths.len() == search.max_paths {
+                        search.truncated = true;
+                        return;
+                    }
+                    search.paths.push(next);
+                } else {
+                    queue.push_back(next);
+                }
+            }
+        }
     }
 
     /// Build children for backward tracing (who calls this function?)
@@ -275,15 +493,23 @@ impl<'a> CallGraphBuilder<'a> {
         let mut children = Vec::new();
 
         for caller_info in callers {
-            // Try to find the caller function definition
-            if let Some(caller_func) = self.finder.find_function(&caller_info.caller_name) {
+            // Try to find the caller function definition around the call site
+            if let Some(caller_func) = self.finder.find_function_from(
+                &caller_info.caller_name,
+                &caller_info.file,
+                caller_info.line,
+            ) {
                 // Avoid adding the same caller multiple times
                 if !children.iter().any(|child: &CallNode| {
                     child.def.name == caller_func.name && child.def.file == caller_func.file
                 }) {
                     // Recursively build the child node
-                    if let Some(child_node) = self.build_node(&caller_func, depth + 1, current_path)
-                    {
+                    if let Some(child_node) = self.build_node(
+                        &caller_func,
+                        depth + 1,
+                        &TraceDirection::Backward,
+                        current_path,
+                    ) {
                         children.push(child_node);
                     }
                 }
@@ -296,21 +522,32 @@ impl<'a> CallGraphBuilder<'a> {
 }
 
 impl CallTree {
-    /// Get the total number of nodes in the tree
+    /// Get the total number of nodes in the tree (callers included)
     pub fn node_count(&self) -> usize {
-        Self::count_nodes(&self.root)
+        Self::count_nodes(&self.root) + self.callers.iter().map(Self::count_nodes).sum::<usize>()
     }
 
-    /// Get the maximum depth of the tree
+    /// Get the maximum depth of the tree, in either direction
     pub fn max_depth(&self) -> usize {
-        Self::calculate_depth(&self.root, 0)
+        self.callers
+            .iter()
+            .map(|caller| Self::calculate_depth(caller, 1))
+            .fold(Self::calculate_depth(&self.root, 0), usize::max)
     }
 
     /// Check if the tree contains cycles
     pub fn has_cycles(&self) -> bool {
         let mut visited = HashSet::new();
         let mut path = HashSet::new();
-        Self::has_cycle_helper(&self.root, &mut visited, &mut path)
+        if Self::has_cycle_helper(&self.root, &mut visited, &mut path) {
+            return true;
+        }
+
+        self.callers.iter().any(|caller| {
+            let mut visited = HashSet::new();
+            let mut path = HashSet::new();
+            Self::has_cycle_helper(caller, &mut visited, &mut path)
+        })
     }
 
     fn count_nodes(node: &CallNode) -> usize {
@@ -364,6 +601,7 @@ mod tests {
     fn create_test_function(name: &str, file: &str, line: usize) -> FunctionDef {
         FunctionDef {
             name: name.to_string(),
+            scope: vec![],
             file: PathBuf::from(file),
             line,
             body: format!("function {}() {{}}", name),
@@ -375,6 +613,7 @@ mod tests {
         assert_eq!(TraceDirection::Forward, TraceDirection::Forward);
         assert_eq!(TraceDirection::Backward, TraceDirection::Backward);
         assert_ne!(TraceDirection::Forward, TraceDirection::Backward);
+        assert_ne!(TraceDirection::Both, TraceDirection::Forward);
     }
 
     #[test]
@@ -399,7 +638,10 @@ mod tests {
             children: vec![],
             truncated: false,
         };
-        let tree = CallTree { root };
+        let tree = CallTree {
+            root,
+            callers: vec![],
+        };
 
         assert_eq!(tree.root.def.name, "main");
     }
@@ -421,7 +663,10 @@ mod tests {
             truncated: false,
         };
 
-        let tree = CallTree { root };
+        let tree = CallTree {
+            root,
+            callers: vec![],
+        };
         assert_eq!(tree.node_count(), 2);
     }
 
@@ -450,10 +695,40 @@ mod tests {
             truncated: false,
         };
 
-        let tree = CallTree { root };
+        let tree = CallTree {
+            root,
+            callers: vec![],
+        };
         assert_eq!(tree.max_depth(), 2); // 0-indexed depth
     }
 
+    #[test]
+    fn test_call_tree_with_callers() {
+        let leaf = |name: &str, line: usize| CallNode {
+            def: create_test_function(name, "test.js", line),
+            children: vec![],
+            truncated: false,
+        };
+
+        // caller2 -> caller1 -> focus -> callee
+        let caller1 = CallNode {
+            children: vec![leaf("caller2", 30)],
+            ..leaf("caller1", 20)
+        };
+        let root = CallNode {
+            children: vec![leaf("callee", 10)],
+            ..leaf("focus", 1)
+        };
+
+        let tree = CallTree {
+            root,
+            callers: vec![caller1],
+        };
+        assert_eq!(tree.node_count(), 4);
+        assert_eq!(tree.max_depth(), 2);
+        assert!(!tree.has_cycles());
+    }
+
     #[test]
     fn test_call_graph_builder_creation() {
         use crate::trace::{CallExtractor, FunctionFinder};
@@ -487,7 +762,7 @@ mod tests {
 
         let test_func = create_test_function("test", "test.js", 1);
         let mut path = HashSet::new();
-        let result = builder.build_node(&test_func, 0, &mut path);
+        let result = builder.build_node(&test_func, 0, &TraceDirection::Forward, &mut path);
 
         assert!(result.is_some());
         let node = result.unwrap();
@@ -514,7 +789,7 @@ mod tests {
         // Add the function to path to simulate cycle detection
         path.insert(test_func.clone());
 
-        let result = builder.build_node(&test_func, 0, &mut path);
+        let result = builder.build_node(&test_func, 0, &TraceDirection::Forward, &mut path);
 
         assert!(result.is_some());
         let node = result.unwrap();
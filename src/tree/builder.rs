@@ -226,6 +226,7 @@ impl ReferenceTreeBuilder {
 
         // CLONE: Store owned copy of the translation value
         node.metadata = Some(entry.value.clone());
+        node.branch = entry.branch.clone();
 
         // MOVE: Transfer ownership of node to caller
         // Chapter 4.1: The node is moved out of this function
@@ -265,6 +266,7 @@ impl ReferenceTreeBuilder {
             TreeNode::with_location(NodeType::CodeRef, code_ref.context.clone(), location);
         // Store the key path (or search pattern) in metadata for highlighting
         node.metadata = Some(code_ref.key_path.clone());
+        node.confidence = Some(code_ref.confidence).filter(|c| c.is_possible());
         node
     }
 }
@@ -272,6 +274,7 @@ impl ReferenceTreeBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Confidence;
     use std::path::PathBuf;
 
     fn create_test_translation_entry() -> TranslationEntry {
@@ -280,6 +283,8 @@ mod tests {
             value: "add new".to_string(),
             line: 4,
             file: PathBuf::from("en.yml"),
+            branch: None,
+            locale: None,
         }
     }
 
@@ -292,6 +297,7 @@ mod tests {
             key_path: "invoice.labels.add_new".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         }
     }
 
@@ -32,6 +32,8 @@
 //! - `Eq` - For full equality (requires `PartialEq`)
 //! - `Copy` - For implicit copying (only for stack types)
 
+use crate::search::Confidence;
+use serde::Serialize;
 use std::path::PathBuf;
 
 /// Type of node in the reference tree.
@@ -76,7 +78,8 @@ use std::path::PathBuf;
 /// - Less code to write and maintain
 /// - Compiler-generated code is correct and efficient
 /// - Consistent behavior across the codebase
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NodeType {
     /// Root node containing the search text
     Root,
@@ -126,7 +129,7 @@ pub enum NodeType {
 /// - Locations can be compared: `loc1 == loc2`
 /// - Locations can be cloned: `loc.clone()`
 /// - Locations can be debugged: `println!("{:?}", loc)`
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Location {
     pub file: PathBuf,
     pub line: usize,
@@ -175,13 +178,20 @@ impl Location {
 /// - Accidental expensive operations
 /// - Unnecessary trait bound requirements
 /// - Compilation errors when adding non-Clone fields later
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TreeNode {
     pub node_type: NodeType,
     pub content: String,
     pub location: Option<Location>,
     pub children: Vec<TreeNode>,
     pub metadata: Option<String>,
+    /// Plural/select arm of a translation node (see `TranslationEntry::branch`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Set on code reference nodes for possible usages through computed
+    /// keys (see `CodeReference::confidence`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
 }
 
 impl TreeNode {
@@ -193,6 +203,8 @@ impl TreeNode {
             location: None,
             children: Vec::new(),
             metadata: None,
+            branch: None,
+            confidence: None,
         }
     }
 
@@ -204,6 +216,8 @@ impl TreeNode {
             location: Some(location),
             children: Vec::new(),
             metadata: None,
+            branch: None,
+            confidence: None,
         }
     }
 
@@ -243,7 +257,7 @@ impl TreeNode {
 }
 
 /// A reference tree representing the search results
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ReferenceTree {
     pub root: TreeNode,
 }
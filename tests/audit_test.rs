@@ -0,0 +1,172 @@
+use assert_cmd::{cargo_bin, Command};
+use serde_json::Value;
+use std::fs;
+use tempfile::tempdir;
+
+fn cs_cmd() -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd
+}
+
+#[test]
+fn test_unused_keys_rails_fixture() {
+    let output = cs_cmd()
+        .args(["unused-keys", "--simple"])
+        .current_dir("tests/fixtures/rails-app")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let keys: Vec<&str> = stdout
+        .lines()
+        .map(|l| l.rsplit(':').next().unwrap())
+        .collect();
+
+    assert!(keys.contains(&"invoice.errors.invalid_data"));
+    assert!(keys.contains(&"user.labels.signup"));
+    // Referenced directly, or through the cached `invoice.labels` namespace
+    assert!(!keys.contains(&"invoice.errors.not_found"));
+    assert!(!keys.contains(&"invoice.labels.save"));
+}
+
+#[test]
+fn test_unused_keys_json_report() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src/locales")).unwrap();
+    fs::write(
+        dir.path().join("src/locales/en.json"),
+        r#"{"nav": {"home": "Home", "about": "About"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("src/locales/de.json"),
+        r#"{"nav": {"home": "Start", "about": "Über"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("app.js"), "render(t('nav.home'));\n").unwrap();
+
+    let output = cs_cmd()
+        .args(["unused-keys", "--json"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["kind"], "unused-keys");
+    assert_eq!(json["keys_checked"], 2);
+
+    let unused = json["unused_keys"].as_array().unwrap();
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0]["key"], "nav.about");
+    // One definition per locale
+    assert_eq!(unused[0]["locations"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_unused_keys_all_used() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("locales")).unwrap();
+    fs::write(dir.path().join("locales/en.json"), r#"{"title": "Hi"}"#).unwrap();
+    fs::write(dir.path().join("page.js"), "t('title');\n").unwrap();
+
+    cs_cmd()
+        .arg("unused-keys")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "All 1 translation keys are referenced",
+        ));
+}
+
+#[test]
+fn test_missing_keys_rails_fixture() {
+    let output = cs_cmd()
+        .args(["missing-keys", "--simple"])
+        .current_dir("tests/fixtures/rails-app")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("fr.yml:missing:invoice.labels.save"));
+    assert!(stdout.contains("fr.yml:missing:user.messages.welcome"));
+    assert!(!stdout.contains("en.yml:"));
+}
+
+#[test]
+fn test_missing_keys_json_report() {
+    let dir = tempdir().unwrap();
+    for locale in ["en", "fr"] {
+        fs::create_dir_all(dir.path().join("locales").join(locale)).unwrap();
+    }
+    fs::write(
+        dir.path().join("locales/en/common.json"),
+        r#"{"nav": {"home": "Home", "about": "About"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("locales/fr/common.json"),
+        r#"{"nav": {"home": "Accueil", "blog": "Blog"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("app.js"),
+        "t('nav.home');\n$t('footer.copyright');\n",
+    )
+    .unwrap();
+
+    let output = cs_cmd()
+        .args(["missing-keys", "--json"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["kind"], "missing-keys");
+
+    let group = &json["groups"][0];
+    assert_eq!(group["pattern"], "locales/{locale}/common.json");
+    assert_eq!(group["base_locale"], "en");
+    let fr = group["locales"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["locale"] == "fr")
+        .unwrap();
+    assert_eq!(fr["missing"], serde_json::json!(["nav.about"]));
+    assert_eq!(fr["extra"], serde_json::json!(["nav.blog"]));
+
+    assert_eq!(json["undefined_keys"][0]["key"], "footer.copyright");
+    assert_eq!(json["undefined_keys"][0]["locations"][0]["line"], 2);
+}
+
+#[test]
+fn test_audit_command_names_can_still_be_searched() {
+    // Arguments the audit doesn't take make the name search text
+    cs_cmd()
+        .args(["missing-keys", "--trace"])
+        .current_dir("tests/fixtures/rails-app")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Function 'missing-keys' not found",
+        ));
+
+    // A flag before the name, or `--`, always searches
+    for args in [["--ignore-case", "unused-keys"], ["--", "unused-keys"]] {
+        cs_cmd()
+            .args(args)
+            .current_dir("tests/fixtures/rails-app")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "No matches found for 'unused-keys'",
+            ));
+    }
+}
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 fn test_extract_calls_from_js_function() {
     let func = FunctionDef {
         name: "processData".to_string(),
+        scope: vec![],
         file: PathBuf::from("tests/fixtures/call-graph/sample.js"),
         line: 3,
         body: "".to_string(),
@@ -26,6 +27,7 @@ fn test_extract_calls_from_js_function() {
 fn test_extract_calls_filters_keywords() {
     let func = FunctionDef {
         name: "validateInput".to_string(),
+        scope: vec![],
         file: PathBuf::from("tests/fixtures/call-graph/sample.js"),
         line: 10,
         body: "".to_string(),
@@ -45,6 +47,7 @@ fn test_extract_calls_filters_keywords() {
 fn test_extract_calls_from_ruby_function() {
     let func = FunctionDef {
         name: "process_order".to_string(),
+        scope: vec![],
         file: PathBuf::from("tests/fixtures/call-graph/sample.rb"),
         line: 3,
         body: "".to_string(),
@@ -132,3 +135,71 @@ fn test_caller_info_structure() {
         assert!(caller.line > 0);
     }
 }
+
+fn extract_from_source(file_name: &str, source: &str, func: &str, line: usize) -> Vec<String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join(file_name);
+    std::fs::write(&path, source).unwrap();
+
+    let def = FunctionDef {
+        name: func.to_string(),
+        scope: vec![],
+        file: path,
+        line,
+        body: String::new(),
+    };
+    let extractor = CallExtractor::new(dir.path().to_path_buf());
+    extractor.extract_calls(&def).unwrap()
+}
+
+#[test]
+fn test_syntax_tree_ignores_strings_and_block_comments() {
+    let source = r#"function render() {
+  const label = "notACall(1)";
+  /*
+   commentedOut(2)
+  */
+  draw(label);
+}
+
+function later() {
+  afterwards();
+}
+"#;
+    let calls = extract_from_source("render.js", source, "render", 1);
+    assert_eq!(calls, vec!["draw".to_string()]);
+}
+
+#[test]
+fn test_syntax_tree_uses_exact_function_range() {
+    // Python body ends at the dedent, even with a blank line and comment inside
+    let source = "def first():\n    helper()\n\n    # not_called()\n    return 1\n\ndef second():\n    other()\n";
+    let calls = extract_from_source("mod.py", source, "first", 1);
+    assert_eq!(calls, vec!["helper".to_string()]);
+}
+
+#[test]
+fn test_syntax_tree_includes_closures_and_method_calls() {
+    let source = r#"fn run(items: Vec<u32>) -> u32 {
+    let total = items.iter().map(|x| double(*x)).sum();
+    report::emit(total);
+    total
+}
+"#;
+    let calls = extract_from_source("lib.rs", source, "run", 1);
+    for expected in ["iter", "map", "double", "sum", "emit"] {
+        assert!(
+            calls.contains(&expected.to_string()),
+            "missing {} in {:?}",
+            expected,
+            calls
+        );
+    }
+}
+
+#[test]
+fn test_regex_fallback_for_unsupported_language() {
+    let source = "func main() {\n    helper()\n}\n";
+    let calls = extract_from_source("main.swift", source, "main", 1);
+    assert!(calls.contains(&"helper".to_string()));
+}
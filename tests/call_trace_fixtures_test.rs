@@ -120,3 +120,105 @@ fn test_python_nested_calls() {
     assert_eq!(tree.root.children[0].def.name, "py_b");
     assert_eq!(tree.root.children[0].children[0].def.name, "py_c");
 }
+
+#[test]
+fn test_go_and_java_nested_calls() {
+    for (a, b, c) in [("goA", "goB", "goC"), ("javaA", "javaB", "javaC")] {
+        for use_index in [false, true] {
+            let forward = TraceQuery::new(a.to_string(), TraceDirection::Forward, 3)
+                .with_base_dir(get_fixtures_dir())
+                .with_symbol_index(use_index);
+            let tree = run_trace(forward).unwrap().expect(a);
+            assert_eq!(tree.root.def.name, a);
+            let names: Vec<_> = tree.root.children.iter().map(|n| &n.def.name).collect();
+            assert_eq!(names, [b], "callees of {}", a);
+            let names: Vec<_> = tree.root.children[0]
+                .children
+                .iter()
+                .map(|n| &n.def.name)
+                .collect();
+            assert_eq!(names, [c], "callees of {}", b);
+
+            let backward = TraceQuery::new(c.to_string(), TraceDirection::Backward, 3)
+                .with_base_dir(get_fixtures_dir())
+                .with_symbol_index(use_index);
+            let tree = run_trace(backward).unwrap().expect(c);
+            let names: Vec<_> = tree.root.children.iter().map(|n| &n.def.name).collect();
+            assert_eq!(names, [b], "callers of {}", c);
+            assert_eq!(tree.root.children[0].children[0].def.name, a);
+        }
+    }
+}
+
+#[test]
+fn test_symbol_index_matches_scanning() {
+    use cs::CallNode;
+
+    fn shape(node: &CallNode) -> String {
+        let mut children: Vec<String> = node.children.iter().map(shape).collect();
+        children.sort();
+        format!(
+            "{}@{}:{}[{}]",
+            node.def.name,
+            node.def.file.display(),
+            node.def.line,
+            children.join(",")
+        )
+    }
+
+    for (name, direction) in [
+        ("a", TraceDirection::Forward),
+        ("c", TraceDirection::Backward),
+        ("cycleA", TraceDirection::Forward),
+    ] {
+        let scanned = run_trace(
+            TraceQuery::new(name.to_string(), direction.clone(), 4)
+                .with_base_dir(get_fixtures_dir())
+                .with_symbol_index(false),
+        )
+        .unwrap()
+        .unwrap();
+        let indexed = run_trace(
+            TraceQuery::new(name.to_string(), direction, 4).with_base_dir(get_fixtures_dir()),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            shape(&scanned.root),
+            shape(&indexed.root),
+            "trace of {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_cycle_exported_as_graph() {
+    use assert_cmd::{cargo_bin, Command};
+    use predicates::prelude::*;
+
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1")
+        .env("CS_DISABLE_CACHE_SERVER", "1")
+        .args(["cycleA", "--trace", "--no-index", "--format", "dot"])
+        .arg("--cluster=file")
+        .current_dir(get_fixtures_dir())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("digraph calls {"))
+        .stdout(predicate::str::contains("nested.js\";"))
+        .stdout(predicate::str::contains("n0 -> n1;"))
+        .stdout(predicate::str::contains(
+            "n1 -> n0 [style=dashed, label=\"cycle\"];",
+        ));
+
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1")
+        .env("CS_DISABLE_CACHE_SERVER", "1")
+        .args(["cycleA", "--trace", "--no-index", "--format", "mermaid"])
+        .current_dir(get_fixtures_dir())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("n1 -.->|cycle| n0"));
+}
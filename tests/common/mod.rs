@@ -0,0 +1,11 @@
+//! Helpers shared by the integration tests
+
+use std::fs;
+use std::path::Path;
+
+/// Write `content` to `relative` under `base`, creating parent directories
+pub fn write(base: &Path, relative: &str, content: &str) {
+    let path = base.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
@@ -0,0 +1,95 @@
+use assert_cmd::{cargo_bin, Command};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+/// `cs` with an empty user-level config dir, so the developer's own
+/// configuration can't leak into the tests
+fn cs_cmd(user_config_dir: &Path) -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd.env("CS_CONFIG_DIR", user_config_dir);
+    cmd
+}
+
+fn project_with_custom_helper() -> TempDir {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("locales")).unwrap();
+    fs::create_dir_all(dir.path().join("app/views")).unwrap();
+    fs::write(
+        dir.path().join("locales/en.json"),
+        r#"{"home": {"title": "Welcome home"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("app/views/home.js"),
+        "render(tr(\"home.title\"));\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_project_config_adds_i18n_pattern() {
+    let project = project_with_custom_helper();
+    let user = tempdir().unwrap();
+
+    // Without configuration the custom `tr()` helper is not recognised
+    cs_cmd(user.path())
+        .args(["Welcome home", "--simple"])
+        .current_dir(project.path())
+        .assert()
+        .stdout(predicate::str::contains("home.js").not());
+
+    fs::write(
+        project.path().join(".cs.toml"),
+        "[patterns]\nadd = ['tr\\(\"([^\"]+)\"\\)']\n",
+    )
+    .unwrap();
+
+    cs_cmd(user.path())
+        .args(["Welcome home", "--simple"])
+        .current_dir(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("app/views/home.js:1"));
+}
+
+#[test]
+fn test_user_config_is_applied() {
+    let project = project_with_custom_helper();
+    let user = tempdir().unwrap();
+    fs::write(
+        user.path().join("config.toml"),
+        "[patterns]\nadd = ['tr\\(\"([^\"]+)\"\\)']\n",
+    )
+    .unwrap();
+
+    cs_cmd(user.path())
+        .args(["unused-keys", "--simple"])
+        .arg(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_invalid_config_reports_line() {
+    let project = project_with_custom_helper();
+    let user = tempdir().unwrap();
+    fs::write(
+        project.path().join(".cs.toml"),
+        "[exclusions]\nadd = [\"tmp\"]\n\n[languages]\nmjs = \"cobol\"\n",
+    )
+    .unwrap();
+
+    cs_cmd(user.path())
+        .args(["Welcome home"])
+        .current_dir(project.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(".cs.toml:5"))
+        .stderr(predicate::str::contains("unknown language 'cobol'"));
+}
@@ -272,7 +272,7 @@ fn test_context_line_formatting_basic() {
 #[test]
 fn test_context_line_formatting_visual_distinction() {
     // Test visual distinction between match and context lines
-    use cs::{CodeReference, SearchResult, TreeFormatter};
+    use cs::{CodeReference, Confidence, SearchResult, TreeFormatter};
 
     let result = SearchResult {
         query: "target".to_string(),
@@ -285,6 +285,7 @@ fn test_context_line_formatting_visual_distinction() {
             key_path: "target".to_string(),
             context_before: vec!["line1".to_string(), "line2".to_string()],
             context_after: vec!["line4".to_string(), "line5".to_string()],
+            confidence: Confidence::Exact,
         }],
     };
 
@@ -346,7 +347,7 @@ fn test_context_line_formatting_multiple_matches() {
 #[test]
 fn test_context_line_formatting_overlap_handling() {
     // Test that overlapping context is handled correctly
-    use cs::{CodeReference, SearchResult, TreeFormatter};
+    use cs::{CodeReference, Confidence, SearchResult, TreeFormatter};
 
     let result = SearchResult {
         query: "target".to_string(),
@@ -360,6 +361,7 @@ fn test_context_line_formatting_overlap_handling() {
                 key_path: "target".to_string(),
                 context_before: vec!["line1".to_string()],
                 context_after: vec!["line3".to_string(), "line4".to_string()],
+                confidence: Confidence::Exact,
             },
             CodeReference {
                 file: std::path::PathBuf::from("test.txt"),
@@ -369,6 +371,7 @@ fn test_context_line_formatting_overlap_handling() {
                 key_path: "target".to_string(),
                 context_before: vec!["line2".to_string(), "line3".to_string()],
                 context_after: vec!["line5".to_string()],
+                confidence: Confidence::Exact,
             },
         ],
     };
@@ -394,12 +397,11 @@ fn test_context_line_formatting_overlap_handling() {
             if let Some(txt_end) = line.find("test.txt") {
                 let after_txt = &line[txt_end + 8..]; // "test.txt".len() = 8
                 if after_txt.len() > 1 {
-                    if let Some(colon_pos) = after_txt.find(':') {
-                        if colon_pos > 1 {
-                            let line_num_part = &after_txt[1..colon_pos]; // Skip the separator (: or -)
-                            if let Ok(line_num) = line_num_part.parse::<usize>() {
-                                line_numbers.push(line_num);
-                            }
+                    // Skip the separator (: or -) before looking for the colon after the number
+                    if let Some(colon_pos) = after_txt[1..].find(':') {
+                        let line_num_part = &after_txt[1..colon_pos + 1];
+                        if let Ok(line_num) = line_num_part.parse::<usize>() {
+                            line_numbers.push(line_num);
                         }
                     }
                 }
@@ -1 +1,123 @@
+use assert_cmd::{cargo_bin, Command};
+use cs::{run_search, SearchQuery};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
 
+fn cs_cmd() -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn code_project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    let mut lines: Vec<String> = (1..=12).map(|n| format!("line {}", n)).collect();
+    lines[2] = "let a = needle;".to_string(); // line 3
+    lines[4] = "let b = needle;".to_string(); // line 5
+    lines[10] = "let c = needle;".to_string(); // line 11
+    fs::write(project.path().join("app.rb"), lines.join("\n") + "\n").unwrap();
+    project
+}
+
+fn rails_project(base: &Path) {
+    fs::create_dir_all(base.join("config/locales")).unwrap();
+    fs::write(
+        base.join("config/locales/en.yml"),
+        "en:\n  invoice:\n    add_new: \"Add New\"\n",
+    )
+    .unwrap();
+    fs::write(
+        base.join("invoices.rb"),
+        "# one\n# two\nlabel = I18n.t('invoice.add_new')\n# four\n# five\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_after_and_before_context_are_independent() {
+    let project = code_project();
+    let out = stdout(
+        cs_cmd()
+            .args(["needle", "-A", "1", "-B", "0"])
+            .arg(project.path()),
+    );
+    let file = project.path().join("app.rb").display().to_string();
+
+    let expected = [
+        format!("{}:3:let a = needle;", file),
+        format!("{}-4:line 4", file),
+        format!("{}:5:let b = needle;", file),
+        format!("{}-6:line 6", file),
+        "--".to_string(),
+        format!("{}:11:let c = needle;", file),
+        format!("{}-12:line 12", file),
+    ]
+    .join("\n");
+    assert!(out.contains(&expected), "{}", out);
+}
+
+#[test]
+fn test_context_merges_adjacent_hits() {
+    let project = code_project();
+    let out = stdout(cs_cmd().args(["needle", "-C", "1"]).arg(project.path()));
+    let file = project.path().join("app.rb").display().to_string();
+
+    // Line 4 is context for both hits and shown once; line 5 stays a hit
+    // even though it is also context of the first
+    let expected = [
+        format!("{}-2:line 2", file),
+        format!("{}:3:let a = needle;", file),
+        format!("{}-4:line 4", file),
+        format!("{}:5:let b = needle;", file),
+        format!("{}-6:line 6", file),
+        "--".to_string(),
+        format!("{}-10:line 10", file),
+        format!("{}:11:let c = needle;", file),
+        format!("{}-12:line 12", file),
+    ]
+    .join("\n");
+    assert!(out.contains(&expected), "{}", out);
+}
+
+#[test]
+fn test_no_context_has_no_separators() {
+    let project = code_project();
+    let out = stdout(cs_cmd().args(["needle", "-C", "0"]).arg(project.path()));
+    assert!(!out.contains("--"), "{}", out);
+    assert!(!out.contains("line 4"), "{}", out);
+    assert_eq!(out.matches("needle").count(), 3);
+}
+
+#[test]
+fn test_context_applies_to_traced_key_usages() {
+    let project = TempDir::new().unwrap();
+    rails_project(project.path());
+
+    let query = SearchQuery::new("Add New".to_string())
+        .with_base_dir(project.path().into())
+        .with_before_context(1)
+        .with_after_context(2);
+    let result = run_search(query).expect("Search failed");
+    assert_eq!(result.code_references.len(), 1);
+    assert_eq!(result.code_references[0].context_before, vec!["# two"]);
+    assert_eq!(
+        result.code_references[0].context_after,
+        vec!["# four", "# five"]
+    );
+
+    // The flags switch translation searches to the flat format, which
+    // shows the context
+    let out = stdout(cs_cmd().args(["Add New", "-B", "1"]).arg(project.path()));
+    let file = project.path().join("invoices.rb").display().to_string();
+    assert!(out.contains("=== Translation Files ==="), "{}", out);
+    assert!(out.contains(&format!("{}-2:# two", file)), "{}", out);
+    assert!(!out.contains("# one"), "{}", out);
+}
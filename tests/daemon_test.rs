@@ -0,0 +1,349 @@
+// Waits for the daemon on its Unix socket
+#![cfg(unix)]
+
+use assert_cmd::cargo_bin;
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// A `cs --cache-server` daemon with its own cache directory, killed on drop
+struct Daemon {
+    child: Child,
+    cache_home: TempDir,
+}
+
+impl Daemon {
+    fn start() -> Self {
+        Self::start_with_idle_timeout(None)
+    }
+
+    /// Start a daemon that exits after `idle_timeout` seconds without requests
+    fn start_with_idle_timeout(idle_timeout: Option<u64>) -> Self {
+        let cache_home = TempDir::new().unwrap();
+        let mut command = std::process::Command::new(cargo_bin!("cs"));
+        if let Some(secs) = idle_timeout {
+            command.env("CS_CACHE_SERVER_IDLE_TIMEOUT", secs.to_string());
+        }
+        let child = command
+            .arg("--cache-server")
+            .env("XDG_CACHE_HOME", cache_home.path())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let socket = cache_home.path().join("cs/cache.sock");
+        let started = Instant::now();
+        while !socket.exists() {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "daemon did not start"
+            );
+            sleep(Duration::from_millis(20));
+        }
+        Daemon { child, cache_home }
+    }
+
+    /// `cs` talking to this daemon
+    fn cs(&self, dir: &Path) -> Command {
+        let mut cmd = Command::new(cargo_bin!("cs"));
+        cmd.current_dir(dir)
+            .env("NO_COLOR", "1")
+            .env("XDG_CACHE_HOME", self.cache_home.path());
+        cmd
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// `cs` searching in-process
+fn cs_local(dir: &Path) -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.current_dir(dir)
+        .env("NO_COLOR", "1")
+        .env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd
+}
+
+fn output(cmd: &mut Command) -> String {
+    String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+}
+
+fn socket(daemon: &Daemon) -> std::path::PathBuf {
+    daemon.cache_home.path().join("cs/cache.sock")
+}
+
+/// Retry `check` while the daemon's watcher catches up with a change
+fn eventually(mut check: impl FnMut() -> bool) {
+    let started = Instant::now();
+    while !check() {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "daemon never caught up"
+        );
+        sleep(Duration::from_millis(100));
+    }
+}
+
+fn project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    let root = project.path();
+    fs::create_dir_all(root.join("config/locales")).unwrap();
+    fs::write(
+        root.join("config/locales/en.yml"),
+        "en:\n  invoice:\n    labels:\n      add_new: \"Add New\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("app")).unwrap();
+    fs::write(
+        root.join("app/invoices.js"),
+        "function renderButton() {\n  return t('invoice.labels.add_new');\n}\n\nfunction renderPage() {\n  return renderButton();\n}\n",
+    )
+    .unwrap();
+    project
+}
+
+#[test]
+fn test_daemon_search_matches_in_process_search() {
+    let project = project();
+    let daemon = Daemon::start();
+
+    for args in [
+        vec!["Add New"],
+        vec!["Add New", "--json"],
+        vec!["Add New", "--simple", "."],
+        vec!["Add New", "config"],
+    ] {
+        let local = output(cs_local(project.path()).args(&args));
+        assert!(local.contains("invoice.labels.add_new"), "{}", local);
+        assert_eq!(output(daemon.cs(project.path()).args(&args)), local);
+        // Second run is answered from the warm project
+        assert_eq!(output(daemon.cs(project.path()).args(&args)), local);
+    }
+}
+
+#[test]
+fn test_daemon_sees_file_changes() {
+    let project = project();
+    let root = project.path();
+    let daemon = Daemon::start();
+    daemon
+        .cs(root)
+        .args(["Add New", "--simple"])
+        .assert()
+        .success();
+
+    // An edited locale file and a new code file are picked up
+    fs::write(
+        root.join("config/locales/en.yml"),
+        "en:\n  invoice:\n    labels:\n      add_new: \"Create invoice\"\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("app/toolbar.js"),
+        "const label = t('invoice.labels.add_new');\n",
+    )
+    .unwrap();
+
+    eventually(|| {
+        let found = output(daemon.cs(root).args(["Create invoice", "--simple"]));
+        found.contains("app/toolbar.js") && found.contains("app/invoices.js")
+    });
+    assert_eq!(
+        output(daemon.cs(root).args(["Add New", "--simple"])),
+        output(cs_local(root).args(["Add New", "--simple"]))
+    );
+}
+
+#[test]
+fn test_daemon_trace() {
+    let project = project();
+    let root = project.path();
+    let daemon = Daemon::start();
+
+    let local = output(cs_local(root).args(["--traceback", "renderButton"]));
+    assert!(local.contains("renderPage"), "{}", local);
+    assert_eq!(
+        output(daemon.cs(root).args(["--traceback", "renderButton"])),
+        local
+    );
+
+    // A new caller shows up once the watcher has seen it
+    fs::write(
+        root.join("app/toolbar.js"),
+        "function renderToolbar() {\n  return renderButton();\n}\n",
+    )
+    .unwrap();
+    eventually(|| {
+        output(daemon.cs(root).args(["--traceback", "renderButton"])).contains("renderToolbar")
+    });
+}
+
+#[test]
+fn test_cache_status_stats_and_stop() {
+    let project = project();
+    let root = project.path();
+    let mut daemon = Daemon::start();
+
+    let status = output(daemon.cs(root).args(["cache", "status"]));
+    assert!(status.contains("Cache server running"), "{}", status);
+    assert!(
+        status.contains(&format!("pid:           {}", daemon.child.id())),
+        "{}",
+        status
+    );
+    assert!(status.contains("cache.sock"), "{}", status);
+
+    daemon.cs(root).args(["Add New"]).assert().success();
+    let status = output(daemon.cs(root).args(["cache", "status"]));
+    assert!(status.contains("warm projects: 1"), "{}", status);
+    assert!(status.contains("turned away:   0"), "{}", status);
+
+    let stats = output(daemon.cs(root).args(["cache", "stats"]));
+    assert!(stats.contains("Cached files:"), "{}", stats);
+    assert!(stats.contains("Size on disk:"), "{}", stats);
+
+    daemon
+        .cs(root)
+        .args(["cache", "stop"])
+        .assert()
+        .success()
+        .stdout("Cache server stopped\n");
+    eventually(|| daemon.child.try_wait().unwrap().is_some());
+    assert!(!socket(&daemon).exists());
+
+    // Now there is nothing to stop, and stats read the database directly
+    daemon
+        .cs(root)
+        .args(["cache", "status"])
+        .assert()
+        .success()
+        .stdout("Cache server not running\n");
+    daemon
+        .cs(root)
+        .args(["cache", "stop"])
+        .assert()
+        .success()
+        .stdout("Cache server not running\n");
+    daemon.cs(root).args(["cache", "stats"]).assert().success();
+    daemon
+        .cs(root)
+        .args(["cache", "prune", "--older-than", "30d"])
+        .assert()
+        .success()
+        .stdout("Removed 0 cached files not used in 30d\n");
+}
+
+#[test]
+fn test_cache_server_exits_when_idle() {
+    let project = project();
+    let mut daemon = Daemon::start_with_idle_timeout(Some(1));
+    daemon
+        .cs(project.path())
+        .args(["Add New"])
+        .assert()
+        .success();
+
+    eventually(|| daemon.child.try_wait().unwrap().is_some());
+    assert!(!socket(&daemon).exists());
+}
+
+#[test]
+fn test_cache_prune_rejects_bad_ages() {
+    let project = project();
+    cs_local(project.path())
+        .args(["cache", "prune", "--older-than", "soon"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_searches_share_cached_parses() {
+    let project = project();
+    let cache_home = TempDir::new().unwrap();
+    let cs = || {
+        let mut cmd = cs_local(project.path());
+        cmd.env("XDG_CACHE_HOME", cache_home.path());
+        cmd
+    };
+    let stats = || output(cs().args(["cache", "stats"]));
+
+    cs().args(["Add New", "--simple"]).assert().success();
+    let first = stats();
+    assert!(first.contains("Cached files:   1\n"), "{}", first);
+    assert!(first.contains("Hits / misses:  0 /"), "{}", first);
+
+    // Another search text reuses the parsed locale file
+    cs().args(["Create invoice", "--simple"]).assert().success();
+    let second = stats();
+    assert!(second.contains("Cached files:   1\n"), "{}", second);
+    assert!(second.contains("Hits / misses:  1 /"), "{}", second);
+}
+
+#[test]
+fn test_daemon_reports_query_errors_without_rerunning() {
+    let project = project();
+    let root = project.path();
+    fs::write(
+        root.join("app/legacy.js"),
+        "function renderButton() {\n  return null;\n}\n",
+    )
+    .unwrap();
+    let daemon = Daemon::start();
+
+    // The ambiguity comes back with its candidates, as in-process
+    let local = cs_local(root)
+        .args(["--trace", "renderButton", "--json"])
+        .output()
+        .unwrap();
+    let remote = daemon
+        .cs(root)
+        .args(["--trace", "renderButton", "--json"])
+        .output()
+        .unwrap();
+    assert_eq!(remote.status.code(), Some(1));
+    assert_eq!(remote.stdout, local.stdout);
+    let json: serde_json::Value = serde_json::from_slice(&remote.stdout).unwrap();
+    assert_eq!(json["kind"], "trace-candidates");
+}
+
+#[test]
+fn test_cache_and_lsp_can_still_be_searched() {
+    let project = project();
+    let root = project.path();
+    fs::write(
+        root.join("app/store.js"),
+        "function cache() {\n  return load();\n}\n\nfunction warm() {\n  return cache();\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("config/locales/en.yml"),
+        "en:\n  settings:\n    clear: \"Clear cache\"\n",
+    )
+    .unwrap();
+
+    // A bare word with no subcommand arguments is search text
+    cs_local(root)
+        .arg("cache")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("settings.clear"));
+    cs_local(root)
+        .args(["--", "lsp"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No matches found for 'lsp'"));
+
+    // Search flags after the word still apply
+    let trace = output(cs_local(root).args(["cache", "--traceback"]));
+    assert!(trace.contains("warm"), "{}", trace);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use assert_cmd::{cargo_bin, Command};
+use common::write;
+use cs::{run_search, Confidence, SearchQuery};
+use predicates::prelude::*;
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn cs_cmd() -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd
+}
+
+fn invoice_project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    write(
+        project.path(),
+        "src/locales/en.json",
+        r#"{
+  "invoice": {
+    "paid": { "label": "Paid in full" },
+    "draft": { "label": "Draft" },
+    "add": "Add invoice",
+    "archive": "Archive invoice",
+    "unused": "Never shown"
+  }
+}"#,
+    );
+    write(
+        project.path(),
+        "src/Invoice.js",
+        r#"const ADD_KEY = 'invoice.add';
+
+export function Invoice({ status, action }) {
+  const label = t(`invoice.${status}.label`);
+  const button = t(ADD_KEY);
+  const menu = i18n.t('invoice.' + action);
+  return render(label, button, menu);
+}
+"#,
+    );
+    project
+}
+
+#[test]
+fn test_template_usage_is_reported_as_possible() {
+    let project = invoice_project();
+    let query = SearchQuery::new("Paid in full".to_string()).with_base_dir(project.path().into());
+    let result = run_search(query).expect("Search failed");
+
+    assert_eq!(result.translation_entries.len(), 1);
+    let refs: Vec<_> = result
+        .code_references
+        .iter()
+        .map(|r| (r.line, r.key_path.as_str(), r.confidence))
+        .collect();
+    // The template, and the concatenation that could also build the key
+    assert_eq!(
+        refs,
+        vec![
+            (4, "invoice.paid.label", Confidence::Low),
+            (6, "invoice.paid.label", Confidence::Low),
+        ]
+    );
+    assert_eq!(result.code_references[0].pattern, "invoice.*.label");
+}
+
+#[test]
+fn test_constant_usage_has_high_confidence() {
+    let project = invoice_project();
+    let query = SearchQuery::new("Add invoice".to_string()).with_base_dir(project.path().into());
+    let result = run_search(query).expect("Search failed");
+
+    let refs: Vec<_> = result
+        .code_references
+        .iter()
+        .map(|r| (r.line, r.confidence))
+        .collect();
+    // The constant itself, and the concatenation that could also build the key
+    assert_eq!(
+        refs,
+        vec![(5, Confidence::High), (6, Confidence::Low)],
+        "{:?}",
+        result.code_references
+    );
+}
+
+#[test]
+fn test_possible_usages_in_output() {
+    let project = invoice_project();
+
+    cs_cmd()
+        .arg("Paid in full")
+        .arg(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[possible, low confidence]"));
+
+    let output = cs_cmd()
+        .arg("Paid in full")
+        .arg(project.path())
+        .arg("--json")
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["code_references"][0]["confidence"], "low");
+}
+
+#[test]
+fn test_possibly_used_keys_are_not_unused() {
+    let project = TempDir::new().unwrap();
+    write(
+        project.path(),
+        "locales/en.json",
+        r#"{"nav": {"home": "Home", "about": "About"}, "footer": "Footer"}"#,
+    );
+    write(
+        project.path(),
+        "menu.js",
+        "const items = pages.map((page) => t(`nav.${page}`));\n",
+    );
+
+    let output = cs_cmd()
+        .args(["unused-keys", "--json"])
+        .arg(project.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let unused: Vec<_> = json["unused_keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|k| k["key"].as_str().unwrap())
+        .collect();
+    // Both `nav` keys may be built by the template
+    assert_eq!(unused, vec!["footer"]);
+}
@@ -0,0 +1,16 @@
+// Chain: javaA -> javaB -> javaC
+package nested;
+
+public class Nested {
+    public void javaA() {
+        javaB("x");
+    }
+
+    private static int javaB(String name) throws IllegalStateException {
+        return javaC(name.length());
+    }
+
+    int javaC(int n) {
+        return n;
+    }
+}
@@ -0,0 +1,18 @@
+// Chain: goA -> goB -> goC (a method with a receiver)
+package nested
+
+type Service struct{}
+
+func goA() {
+	s := &Service{}
+	s.goB()
+}
+
+func (s *Service) goB() error {
+	goC(1)
+	return nil
+}
+
+func goC(n int) int {
+	return n
+}
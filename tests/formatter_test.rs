@@ -1,6 +1,6 @@
 use cs::{
-    run_search, CodeReference, ReferenceTreeBuilder, SearchQuery, SearchResult, TranslationEntry,
-    TreeFormatter,
+    run_search, CodeReference, Confidence, ReferenceTreeBuilder, SearchQuery, SearchResult,
+    TranslationEntry, TreeFormatter,
 };
 use proptest::prelude::*;
 use std::path::PathBuf;
@@ -224,6 +224,8 @@ proptest! {
             value: translation_value.clone(),
             line: line_num as usize,
             file: PathBuf::from(&file_path),
+            branch: None,
+            locale: None,
         });
 
         // Add code reference
@@ -235,6 +237,7 @@ proptest! {
             key_path: "".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         });
 
         // Test simple format
@@ -291,6 +294,8 @@ fn test_simple_format_single_translation() {
             value: "test value".to_string(),
             line: 5,
             file: PathBuf::from("test.yml"),
+            branch: None,
+            locale: None,
         }],
         code_references: vec![],
     };
@@ -315,6 +320,7 @@ fn test_simple_format_single_code_reference() {
             key_path: "".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         }],
     };
 
@@ -335,12 +341,16 @@ fn test_simple_format_multiple_results() {
                 value: "value1".to_string(),
                 line: 1,
                 file: PathBuf::from("en.yml"),
+                branch: None,
+                locale: None,
             },
             TranslationEntry {
                 key: "test.key2".to_string(),
                 value: "value2".to_string(),
                 line: 2,
                 file: PathBuf::from("en.yml"),
+                branch: None,
+                locale: None,
             },
         ],
         code_references: vec![
@@ -352,6 +362,7 @@ fn test_simple_format_multiple_results() {
                 key_path: "test.key1".to_string(),
                 context_before: vec![],
                 context_after: vec![],
+                confidence: Confidence::Exact,
             },
             CodeReference {
                 file: PathBuf::from("app.ts"),
@@ -361,6 +372,7 @@ fn test_simple_format_multiple_results() {
                 key_path: "test.key2".to_string(),
                 context_before: vec![],
                 context_after: vec![],
+                confidence: Confidence::Exact,
             },
         ],
     };
@@ -385,6 +397,8 @@ fn test_simple_format_special_characters_in_paths() {
             value: "test value".to_string(),
             line: 1,
             file: PathBuf::from("path with spaces/file:name.yml"),
+            branch: None,
+            locale: None,
         }],
         code_references: vec![],
     };
@@ -409,6 +423,8 @@ fn test_simple_format_special_characters_in_content() {
             value: "value with\nnewlines\rand\ttabs".to_string(),
             line: 1,
             file: PathBuf::from("test.yml"),
+            branch: None,
+            locale: None,
         }],
         code_references: vec![CodeReference {
             file: PathBuf::from("test.ts"),
@@ -418,6 +434,7 @@ fn test_simple_format_special_characters_in_content() {
             key_path: "".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         }],
     };
 
@@ -453,6 +470,7 @@ fn test_simple_format_ansi_codes_stripped() {
             key_path: "".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         }],
     };
 
@@ -473,6 +491,8 @@ fn test_simple_format_different_search_types() {
             value: "test value".to_string(),
             line: 1,
             file: PathBuf::from("en.yml"),
+            branch: None,
+            locale: None,
         }],
         code_references: vec![],
     };
@@ -488,6 +508,7 @@ fn test_simple_format_different_search_types() {
             key_path: "".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         }],
     };
 
@@ -572,6 +593,8 @@ proptest! {
             value: translation_value.clone(),
             line: line_num as usize,
             file: PathBuf::from(&file_path),
+            branch: None,
+            locale: None,
         });
 
         // Add code reference with special characters
@@ -583,6 +606,7 @@ proptest! {
             key_path: "".to_string(),
             context_before: vec![],
             context_after: vec![],
+            confidence: Confidence::Exact,
         });
 
         // Test simple format with special characters
@@ -650,6 +674,8 @@ proptest! {
                 value: translation_value.clone(),
                 line: line_num as usize,
                 file: PathBuf::from(&file_path),
+                branch: None,
+                locale: None,
             }],
             code_references: vec![CodeReference {
                 file: PathBuf::from(&file_path),
@@ -659,6 +685,7 @@ proptest! {
                 key_path: translation_key.clone(),
                 context_before: vec!["context before line 1".to_string(), "context before line 2".to_string()],
                 context_after: vec!["context after line 1".to_string(), "context after line 2".to_string()],
+                confidence: Confidence::Exact,
             }],
         };
 
@@ -6,6 +6,7 @@ use std::path::PathBuf;
 fn create_test_function(name: &str, file: &str, line: usize) -> FunctionDef {
     FunctionDef {
         name: name.to_string(),
+        scope: vec![],
         file: PathBuf::from(file),
         line,
         body: String::new(),
@@ -34,7 +35,10 @@ fn test_format_forward_tree() {
         truncated: false,
     };
 
-    let tree = CallTree { root: root_node };
+    let tree = CallTree {
+        root: root_node,
+        callers: vec![],
+    };
     let formatter = TreeFormatter::new();
     let output = formatter.format_trace_tree(&tree, TraceDirection::Forward);
 
@@ -70,7 +74,10 @@ fn test_format_backward_tree() {
         truncated: false,
     };
 
-    let tree = CallTree { root: root_node };
+    let tree = CallTree {
+        root: root_node,
+        callers: vec![],
+    };
     let formatter = TreeFormatter::new();
     let output = formatter.format_trace_tree(&tree, TraceDirection::Backward);
 
@@ -90,9 +97,67 @@ fn test_format_truncated_node() {
         truncated: true,
     };
 
-    let tree = CallTree { root: root_node };
+    let tree = CallTree {
+        root: root_node,
+        callers: vec![],
+    };
     let formatter = TreeFormatter::new();
     let output = formatter.format_trace_tree(&tree, TraceDirection::Forward);
 
     assert!(output.contains("[depth limit reached]"));
 }
+
+#[test]
+fn test_format_bidirectional_tree() {
+    // Structure: caller -> focus -> callee
+    let caller_node = CallNode {
+        def: create_test_function("caller", "caller.rs", 20),
+        children: vec![],
+        truncated: false,
+    };
+    let callee_node = CallNode {
+        def: create_test_function("callee", "callee.rs", 10),
+        children: vec![],
+        truncated: false,
+    };
+    let root_node = CallNode {
+        def: create_test_function("focus", "focus.rs", 1),
+        children: vec![callee_node],
+        truncated: false,
+    };
+
+    let tree = CallTree {
+        root: root_node,
+        callers: vec![caller_node],
+    };
+    let formatter = TreeFormatter::new();
+    let output = strip_ansi_codes(&formatter.format_trace_tree(&tree, TraceDirection::Both));
+
+    let chain = output
+        .find("caller (caller.rs:20) -> focus (focus.rs:1)")
+        .expect("caller chain should end at the focus function");
+    let callee = output
+        .find("└─> callee (callee.rs:10)")
+        .expect("callee should hang below the focus function");
+    assert!(chain < callee, "callers must be rendered above callees");
+}
+
+#[test]
+fn test_format_bidirectional_tree_without_callers() {
+    let root_node = CallNode {
+        def: create_test_function("lonely", "lonely.rs", 1),
+        children: vec![],
+        truncated: false,
+    };
+
+    let tree = CallTree {
+        root: root_node,
+        callers: vec![],
+    };
+    let formatter = TreeFormatter::new();
+    let output = strip_ansi_codes(&formatter.format_trace_tree(&tree, TraceDirection::Both));
+
+    assert!(output.contains("(No incoming calls found)"));
+    assert!(output.contains("(No outgoing calls found)"));
+    assert!(output.contains("lonely (lonely.rs:1)"));
+}
@@ -0,0 +1,86 @@
+use cs::{run_search, SearchQuery};
+use std::fs;
+use tempfile::TempDir;
+
+fn gettext_project() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let base_dir = temp_dir.path();
+
+    let messages = base_dir.join("locale/fr/LC_MESSAGES");
+    fs::create_dir_all(&messages).unwrap();
+    fs::write(
+        messages.join("messages.po"),
+        r#"msgid ""
+msgstr ""
+"Language: fr\n"
+
+#: app/views.py:3
+msgid "Save changes"
+msgstr "Enregistrer les modifications"
+
+msgctxt "menu"
+msgid "Open"
+msgstr "Ouvrir"
+
+msgid "%d file"
+msgid_plural "%d files"
+msgstr[0] "%d fichier"
+msgstr[1] "%d fichiers"
+"#,
+    )
+    .unwrap();
+
+    fs::create_dir_all(base_dir.join("app")).unwrap();
+    fs::write(
+        base_dir.join("app/views.py"),
+        r#"from gettext import gettext as _, ngettext, pgettext
+
+button = _("Save changes")
+label = pgettext("menu", "Open")
+count = ngettext("%d file", "%d files", n) % n
+"#,
+    )
+    .unwrap();
+
+    temp_dir
+}
+
+#[test]
+fn test_po_translation_traces_to_gettext_call() {
+    let project = gettext_project();
+    let base_dir = project.path().to_path_buf();
+
+    let query = SearchQuery::new("Enregistrer".to_string()).with_base_dir(base_dir.clone());
+    let result = run_search(query).expect("Search failed");
+
+    assert_eq!(result.translation_entries.len(), 1);
+    let entry = &result.translation_entries[0];
+    assert_eq!(entry.key, "Save changes");
+    assert_eq!(entry.line, 6);
+
+    assert_eq!(result.code_references.len(), 1);
+    let code_ref = &result.code_references[0];
+    assert_eq!(code_ref.key_path, "Save changes");
+    assert_eq!(code_ref.file, base_dir.join("app/views.py"));
+    assert_eq!(code_ref.line, 3);
+}
+
+#[test]
+fn test_po_context_and_plural_forms() {
+    let project = gettext_project();
+    let base_dir = project.path().to_path_buf();
+
+    let query = SearchQuery::new("Ouvrir".to_string()).with_base_dir(base_dir.clone());
+    let result = run_search(query).expect("Search failed");
+    assert_eq!(result.translation_entries[0].key, "Open");
+    assert_eq!(result.code_references.len(), 1);
+    assert_eq!(result.code_references[0].line, 4);
+
+    // The plural form is searchable and resolves to the singular msgid
+    let query = SearchQuery::new("fichiers".to_string()).with_base_dir(base_dir);
+    let result = run_search(query).expect("Search failed");
+    assert_eq!(result.translation_entries.len(), 1);
+    assert_eq!(result.translation_entries[0].key, "%d file");
+    assert_eq!(result.code_references.len(), 1);
+    assert_eq!(result.code_references[0].line, 5);
+}
@@ -77,6 +77,7 @@ fn test_depth_limit() {
     use cs::trace::FunctionDef;
     let test_fn = FunctionDef {
         name: "testFunction".to_string(),
+        scope: vec![],
         file: PathBuf::from("test.js"),
         line: 1,
         body: "function testFunction() { return 42; }".to_string(),
@@ -102,6 +103,7 @@ fn test_cycle_detection() {
     use cs::trace::FunctionDef;
     let recursive_fn = FunctionDef {
         name: "recursiveFunction".to_string(),
+        scope: vec![],
         file: PathBuf::from("recursive.js"),
         line: 1,
         body: "function recursiveFunction() { return recursiveFunction(); }".to_string(),
@@ -127,6 +129,7 @@ fn test_empty_function_handling() {
     use cs::trace::FunctionDef;
     let empty_fn = FunctionDef {
         name: "emptyFunction".to_string(),
+        scope: vec![],
         file: PathBuf::from("empty.js"),
         line: 1,
         body: "function emptyFunction() {}".to_string(),
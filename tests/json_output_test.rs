@@ -0,0 +1,122 @@
+use assert_cmd::{cargo_bin, Command};
+use serde_json::Value;
+
+fn cs_cmd() -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd
+}
+
+fn run_json(args: &[&str], dir: &str) -> (bool, Value) {
+    let output = cs_cmd()
+        .args(args)
+        .arg("--json")
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json = serde_json::from_str(&stdout)
+        .unwrap_or_else(|e| panic!("stdout is not valid JSON ({}): {}", e, stdout));
+    (output.status.success(), json)
+}
+
+#[test]
+fn test_json_search_output() {
+    let (success, json) = run_json(&["add new"], "tests/fixtures/rails-app");
+    assert!(success);
+
+    assert_eq!(json["schema_version"], cs::output::JSON_SCHEMA_VERSION);
+    assert_eq!(json["kind"], "search");
+    assert_eq!(json["query"], "add new");
+
+    let entries = json["translation_entries"].as_array().unwrap();
+    assert!(entries
+        .iter()
+        .any(|e| e["key"] == "invoice.labels.add_new" && e["line"] == 4));
+
+    let refs = json["code_references"].as_array().unwrap();
+    assert!(refs
+        .iter()
+        .any(|r| r["key_path"] == "invoice.labels.add_new"
+            && r["file"].as_str().unwrap().ends_with("invoice_list.ts")));
+}
+
+#[test]
+fn test_json_search_no_results_is_still_json() {
+    let (success, json) = run_json(&["zzz-no-such-text-anywhere"], "tests/fixtures/rails-app");
+    assert!(success);
+    assert_eq!(json["kind"], "search");
+    assert!(json["translation_entries"].as_array().unwrap().is_empty());
+    assert!(json["code_references"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_json_search_error_is_still_json() {
+    let (success, json) = run_json(&["a(", "--regex"], "tests/fixtures/rails-app");
+    assert!(!success);
+    assert_eq!(json["schema_version"], cs::output::JSON_SCHEMA_VERSION);
+    assert_eq!(json["kind"], "error");
+    assert_eq!(json["error"], "other");
+    assert!(json["message"].as_str().unwrap().contains("unclosed group"));
+}
+
+#[test]
+fn test_json_trace_output() {
+    let (success, json) = run_json(&["processData", "--trace"], "tests/fixtures/call-graph");
+    assert!(success);
+    assert_eq!(json["kind"], "trace");
+    assert_eq!(json["direction"], "forward");
+    assert_eq!(json["root"]["def"]["name"], "processData");
+    assert!(json["root"]["def"]["line"].as_u64().unwrap() > 0);
+
+    let children: Vec<&str> = json["root"]["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["def"]["name"].as_str().unwrap())
+        .collect();
+    assert!(children.contains(&"validateInput"));
+}
+
+#[test]
+fn test_json_trace_not_found() {
+    let (success, json) = run_json(
+        &["noSuchFunctionAnywhere", "--traceback"],
+        "tests/fixtures/call-graph",
+    );
+    assert!(!success);
+    assert_eq!(json["kind"], "trace");
+    assert!(json["root"].is_null());
+}
+
+#[test]
+fn test_json_conflicts_with_simple() {
+    cs_cmd()
+        .args(["foo", "--json", "--simple"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_json_trace_all_includes_callers_and_callees() {
+    let (success, json) = run_json(&["processData", "--trace-all"], "tests/fixtures/call-graph");
+    assert!(success);
+    assert_eq!(json["direction"], "both");
+
+    let callees: Vec<&str> = json["root"]["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["def"]["name"].as_str().unwrap())
+        .collect();
+    assert!(callees.contains(&"validateInput"));
+
+    let callers: Vec<&str> = json["callers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["def"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(callers, vec!["handleClick"]);
+}
@@ -0,0 +1,282 @@
+use assert_cmd::cargo_bin;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use tempfile::TempDir;
+
+/// A scripted LSP client talking to `cs lsp` over stdio
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl Client {
+    fn start(root: &Path) -> Self {
+        let mut child = Command::new(cargo_bin!("cs"))
+            .arg("lsp")
+            .current_dir(root)
+            .env("NO_COLOR", "1")
+            .env("CS_DISABLE_CACHE_SERVER", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut client = Client {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            next_id: 0,
+        };
+
+        let result = client.request(
+            "initialize",
+            json!({
+                "processId": null,
+                "rootUri": uri(root),
+                "capabilities": {},
+            }),
+        );
+        assert_eq!(result["capabilities"]["hoverProvider"], true);
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.stdout.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}));
+        loop {
+            let message = self.receive();
+            if message["id"] == id {
+                assert!(message.get("error").is_none(), "{}", message);
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({"jsonrpc": "2.0", "method": method, "params": params}));
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+fn uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn at(path: &Path, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": {"uri": uri(path)},
+        "position": {"line": line, "character": character},
+    })
+}
+
+fn project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    let root = project.path();
+    fs::create_dir_all(root.join("config/locales")).unwrap();
+    fs::write(
+        root.join("config/locales/en.yml"),
+        "en:\n  invoice:\n    labels:\n      add_new: \"Add New\"\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("config/locales/fr.yml"),
+        "fr:\n  invoice:\n    labels:\n      add_new: \"Ajouter\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("app")).unwrap();
+    fs::write(
+        root.join("app/invoices.js"),
+        r#"function renderButton() {
+  return t('invoice.labels.add_new');
+}
+
+function renderPage() {
+  return renderButton();
+}
+"#,
+    )
+    .unwrap();
+    project
+}
+
+#[test]
+fn test_hover_shows_every_locale() {
+    let project = project();
+    let root = project.path().canonicalize().unwrap();
+    let code = root.join("app/invoices.js");
+    let mut client = Client::start(&root);
+
+    let hover = client.request("textDocument/hover", at(&code, 1, 18));
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(value.contains("**invoice.labels.add_new**"), "{}", value);
+    assert!(value.contains("| en | Add New |"), "{}", value);
+    assert!(value.contains("| fr | Ajouter |"), "{}", value);
+    assert_eq!(hover["range"]["start"]["character"], 12);
+
+    // Nothing outside a translation call
+    let hover = client.request("textDocument/hover", at(&code, 0, 12));
+    assert!(hover.is_null());
+
+    client.shutdown();
+}
+
+#[test]
+fn test_definition_and_references() {
+    let project = project();
+    let root = project.path().canonicalize().unwrap();
+    let code = root.join("app/invoices.js");
+    let en = root.join("config/locales/en.yml");
+    let mut client = Client::start(&root);
+
+    let definitions = client.request("textDocument/definition", at(&code, 1, 18));
+    let mut targets: Vec<_> = definitions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| {
+            (
+                l["uri"].as_str().unwrap().to_string(),
+                l["range"]["start"]["line"].as_u64().unwrap(),
+                l["range"]["start"]["character"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    targets.sort();
+    assert_eq!(
+        targets,
+        vec![
+            (uri(&en), 3, 6),
+            (uri(&root.join("config/locales/fr.yml")), 3, 6),
+        ]
+    );
+
+    // From the key's line in a locale file to the code using it
+    let mut params = at(&en, 3, 8);
+    params["context"] = json!({"includeDeclaration": false});
+    let references = client.request("textDocument/references", params);
+    assert_eq!(
+        references,
+        json!([{
+            "uri": uri(&code),
+            "range": {
+                "start": {"line": 1, "character": 12},
+                "end": {"line": 1, "character": 34},
+            },
+        }])
+    );
+
+    client.shutdown();
+}
+
+#[test]
+fn test_call_hierarchy() {
+    let project = project();
+    let root = project.path().canonicalize().unwrap();
+    let code = root.join("app/invoices.js");
+    let mut client = Client::start(&root);
+
+    let items = client.request("textDocument/prepareCallHierarchy", at(&code, 0, 12));
+    let items = items.as_array().unwrap();
+    assert_eq!(items.len(), 1, "{:?}", items);
+    assert_eq!(items[0]["name"], "renderButton");
+    assert_eq!(items[0]["selectionRange"]["start"]["line"], 0);
+
+    let incoming = client.request(
+        "callHierarchy/incomingCalls",
+        json!({"item": items[0].clone()}),
+    );
+    let incoming = incoming.as_array().unwrap();
+    assert_eq!(incoming.len(), 1, "{:?}", incoming);
+    assert_eq!(incoming[0]["from"]["name"], "renderPage");
+    assert_eq!(incoming[0]["fromRanges"][0]["start"]["line"], 5);
+
+    let page = client.request("textDocument/prepareCallHierarchy", at(&code, 4, 12));
+    let outgoing = client.request(
+        "callHierarchy/outgoingCalls",
+        json!({"item": page[0].clone()}),
+    );
+    let outgoing = outgoing.as_array().unwrap();
+    assert_eq!(outgoing.len(), 1, "{:?}", outgoing);
+    assert_eq!(outgoing[0]["to"]["name"], "renderButton");
+    assert_eq!(outgoing[0]["fromRanges"][0]["start"]["line"], 5);
+
+    client.shutdown();
+}
+
+#[test]
+fn test_open_documents_and_saved_translations() {
+    let project = project();
+    let root = project.path().canonicalize().unwrap();
+    let code = root.join("app/invoices.js");
+    let en = root.join("config/locales/en.yml");
+    let mut client = Client::start(&root);
+
+    // Unsaved buffer content is used for lookups
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": uri(&code),
+            "languageId": "javascript",
+            "version": 1,
+            "text": "const title = t('invoice.labels.add_new');\n",
+        }}),
+    );
+    let hover = client.request("textDocument/hover", at(&code, 0, 20));
+    assert!(hover["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .contains("Add New"));
+
+    // Saving a locale file reloads translations
+    fs::write(
+        &en,
+        "en:\n  invoice:\n    labels:\n      add_new: \"Create invoice\"\n",
+    )
+    .unwrap();
+    client.notify(
+        "textDocument/didSave",
+        json!({"textDocument": {"uri": uri(&en)}}),
+    );
+    let hover = client.request("textDocument/hover", at(&code, 0, 20));
+    assert!(hover["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .contains("| en | Create invoice |"));
+
+    client.shutdown();
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use assert_cmd::{cargo_bin, Command};
+use common::write;
+use cs::{run_search, SearchQuery};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn cs_cmd() -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd
+}
+
+fn icu_project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    write(
+        project.path(),
+        "src/locales/en.json",
+        r#"{
+  "cart": {
+    "summary": "You have {count, plural, =0 {no items} one {# item} other {# items}} in your cart"
+  }
+}"#,
+    );
+    write(
+        project.path(),
+        "src/Cart.js",
+        "const label = i18n.t('cart.summary');\n",
+    );
+    project
+}
+
+#[test]
+fn test_icu_plural_arm_match_reports_branch() {
+    let project = icu_project();
+    let query =
+        SearchQuery::new("# items in your".to_string()).with_base_dir(project.path().into());
+    let result = run_search(query).expect("Search failed");
+
+    // The query spans the plural argument and the text after it
+    assert_eq!(result.translation_entries.len(), 1);
+    let entry = &result.translation_entries[0];
+    assert_eq!(entry.key, "cart.summary");
+    assert_eq!(entry.branch.as_deref(), Some("count:other"));
+    assert_eq!(result.code_references.len(), 1);
+}
+
+#[test]
+fn test_icu_syntax_is_not_matched_as_text() {
+    let project = icu_project();
+    let query = SearchQuery::new("plural".to_string()).with_base_dir(project.path().into());
+    let result = run_search(query).expect("Search failed");
+    assert!(result.translation_entries.is_empty());
+}
+
+#[test]
+fn test_icu_shared_text_has_no_branch() {
+    let project = icu_project();
+    let query = SearchQuery::new("in your cart".to_string()).with_base_dir(project.path().into());
+    let result = run_search(query).expect("Search failed");
+    assert_eq!(result.translation_entries.len(), 1);
+    assert_eq!(result.translation_entries[0].branch, None);
+}
+
+#[test]
+fn test_fluent_select_variant_traces_to_code() {
+    let project = TempDir::new().unwrap();
+    write(
+        project.path(),
+        "locales/en-US/main.ftl",
+        r#"# Inbox
+emails =
+    { $unreadEmails ->
+        [one] You have one unread email.
+       *[other] You have { $unreadEmails } unread emails.
+    }
+"#,
+    );
+    write(
+        project.path(),
+        "src/inbox.js",
+        "const text = i18n.t('emails');\n",
+    );
+
+    cs_cmd()
+        .arg("unread emails")
+        .arg(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "emails: 'You have { $unreadEmails } unread emails.' [$unreadEmails:other]",
+        ))
+        .stdout(predicate::str::contains("src/inbox.js:1"));
+
+    cs_cmd()
+        .arg("unread emails")
+        .arg(project.path())
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""branch": "$unreadEmails:other""#,
+        ));
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::write;
+use cs::{run_search, SearchQuery};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn references(query: &str, base_dir: &Path) -> Vec<(String, usize)> {
+    let query = SearchQuery::new(query.to_string()).with_base_dir(base_dir.to_path_buf());
+    let result = run_search(query).expect("Search failed");
+    let mut refs: Vec<_> = result
+        .code_references
+        .iter()
+        .map(|r| {
+            let relative = r.file.strip_prefix(base_dir).unwrap_or(&r.file);
+            (relative.to_string_lossy().into_owned(), r.line)
+        })
+        .collect();
+    refs.sort();
+    refs
+}
+
+#[test]
+fn test_android_strings_trace_to_resource_references() {
+    let project = TempDir::new().unwrap();
+    let base = project.path();
+
+    write(
+        base,
+        "app/src/main/res/values/strings.xml",
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<resources>
+    <string name="add_new">Add New</string>
+</resources>
+"#,
+    );
+    write(
+        base,
+        "app/src/main/res/values-fr/strings.xml",
+        r#"<resources>
+    <string name="add_new">Ajouter</string>
+</resources>
+"#,
+    );
+    write(
+        base,
+        "app/src/main/java/com/example/MainActivity.kt",
+        "class MainActivity {\n    val label = getString(R.string.add_new)\n}\n",
+    );
+    write(
+        base,
+        "app/src/main/res/layout/activity_main.xml",
+        "<Button\n    android:text=\"@string/add_new\" />\n",
+    );
+
+    assert_eq!(
+        references("Add New", base),
+        vec![
+            (
+                "app/src/main/java/com/example/MainActivity.kt".to_string(),
+                2
+            ),
+            ("app/src/main/res/layout/activity_main.xml".to_string(), 2),
+        ]
+    );
+
+    // The French resource resolves to the same name
+    let query = SearchQuery::new("Ajouter".to_string()).with_base_dir(base.to_path_buf());
+    let result = run_search(query).expect("Search failed");
+    assert_eq!(result.translation_entries.len(), 1);
+    assert_eq!(result.translation_entries[0].key, "add_new");
+    assert_eq!(result.translation_entries[0].line, 2);
+}
+
+#[test]
+fn test_apple_strings_trace_to_localized_calls() {
+    let project = TempDir::new().unwrap();
+    let base = project.path();
+
+    write(
+        base,
+        "App/en.lproj/Localizable.strings",
+        "/* Toolbar button */\n\"add_new\" = \"Add New\";\n",
+    );
+    write(
+        base,
+        "App/Localizable.xcstrings",
+        r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "welcome_title" : {
+      "localizations" : {
+        "en" : { "stringUnit" : { "state" : "translated", "value" : "Welcome aboard" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#,
+    );
+    write(
+        base,
+        "App/ViewController.swift",
+        r#"let add = NSLocalizedString("add_new", comment: "Toolbar button")
+let title = String(localized: "welcome_title")
+"#,
+    );
+
+    assert_eq!(
+        references("Add New", base),
+        vec![("App/ViewController.swift".to_string(), 1)]
+    );
+    assert_eq!(
+        references("Welcome aboard", base),
+        vec![("App/ViewController.swift".to_string(), 2)]
+    );
+}
@@ -1,4 +1,4 @@
-use cs::{run_search, SearchQuery};
+use cs::{run_search, run_search_with_file_matches, SearchQuery};
 use std::path::PathBuf;
 
 #[test]
@@ -240,3 +240,28 @@ fn test_end_to_end_workflow_with_orchestrator() {
     assert!(!result.translation_entries.is_empty());
     assert!(!result.code_references.is_empty());
 }
+
+#[test]
+fn test_run_search_with_file_matches_agrees_with_run_search() {
+    let query = SearchQuery::new("invoice".to_string())
+        .with_case_sensitive(false)
+        .with_base_dir(PathBuf::from("tests/fixtures/rails-app"));
+
+    let (result, file_matches) =
+        run_search_with_file_matches(query.clone()).expect("Search failed");
+    let plain = run_search(query).expect("Search failed");
+
+    // File names come from the same walk as the content search
+    assert!(file_matches.iter().all(|m| m
+        .path
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .contains("invoice")));
+    assert!(!file_matches.is_empty());
+    assert_eq!(result.code_references, plain.code_references);
+    assert_eq!(
+        result.translation_entries.len(),
+        plain.translation_entries.len()
+    );
+}
@@ -0,0 +1,234 @@
+use assert_cmd::{cargo_bin, Command};
+use cs::{run_path_search, PathQuery};
+use predicates::prelude::*;
+use std::fs;
+use tempfile::{tempdir, TempDir};
+
+/// handleSubmit reaches chargeCard directly through validate, through
+/// submitOrder, and through submitOrder's retry loop
+fn checkout_project() -> TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("checkout.js"),
+        r#"function handleSubmit(form) {
+    validate(form);
+    submitOrder(form);
+}
+
+function validate(form) {
+    chargeCard(form.card);
+}
+
+function submitOrder(form) {
+    retry(form);
+    chargeCard(form.card);
+}
+
+function retry(form) {
+    submitOrder(form);
+    chargeCard(form.card);
+}
+
+function chargeCard(card) {
+    return card;
+}
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn path_query(project: &TempDir, from: &str, to: &str, depth: usize) -> PathQuery {
+    PathQuery::new(from.to_string(), to.to_string(), depth)
+        .with_base_dir(project.path().to_path_buf())
+        .with_symbol_index(false)
+}
+
+fn names(found: &cs::CallPaths) -> Vec<Vec<&str>> {
+    found
+        .chains()
+        .map(|chain| chain.iter().map(|def| def.name.as_str()).collect())
+        .collect()
+}
+
+#[test]
+fn test_paths_are_distinct_and_shortest_first() {
+    let project = checkout_project();
+
+    let found = run_path_search(path_query(&project, "handleSubmit", "chargeCard", 5))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        names(&found),
+        vec![
+            vec!["handleSubmit", "submitOrder", "chargeCard"],
+            vec!["handleSubmit", "validate", "chargeCard"],
+            vec!["handleSubmit", "submitOrder", "retry", "chargeCard"],
+        ]
+    );
+    assert!(!found.truncated);
+    // Functions shared by several paths are stored once
+    assert_eq!(found.functions.len(), 5);
+}
+
+#[test]
+fn test_path_search_respects_depth_and_node_budget() {
+    let project = checkout_project();
+
+    // Two calls deep only leaves the direct chains
+    let found = run_path_search(path_query(&project, "handleSubmit", "chargeCard", 2))
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.paths.len(), 2);
+
+    // Reaching only submitOrder cannot reach chargeCard
+    let found =
+        run_path_search(path_query(&project, "handleSubmit", "chargeCard", 5).with_max_nodes(1))
+            .unwrap()
+            .unwrap();
+    assert!(found.paths.is_empty());
+    assert!(found.truncated);
+
+    // Every function reached counts, but calls back into the chain do not:
+    // the whole search reaches six functions
+    let found =
+        run_path_search(path_query(&project, "handleSubmit", "chargeCard", 5).with_max_nodes(5))
+            .unwrap()
+            .unwrap();
+    assert_eq!(found.paths.len(), 2);
+    assert!(found.truncated);
+    let found =
+        run_path_search(path_query(&project, "handleSubmit", "chargeCard", 5).with_max_nodes(6))
+            .unwrap()
+            .unwrap();
+    assert_eq!(found.paths.len(), 3);
+    assert!(!found.truncated);
+
+    // The path limit stops the search at the first path beyond it
+    let found =
+        run_path_search(path_query(&project, "handleSubmit", "chargeCard", 5).with_max_paths(1))
+            .unwrap()
+            .unwrap();
+    assert_eq!(found.paths.len(), 1);
+    assert!(found.truncated);
+    let found =
+        run_path_search(path_query(&project, "handleSubmit", "chargeCard", 5).with_max_paths(3))
+            .unwrap()
+            .unwrap();
+    assert_eq!(found.paths.len(), 3);
+    assert!(!found.truncated);
+
+    assert!(
+        run_path_search(path_query(&project, "handleSubmit", "missing", 5))
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// start reaches target through four calls via alpha, or two via zeta
+fn branching_project() -> TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("branching.js"),
+        r#"function start() {
+    alpha();
+    zeta();
+}
+
+function alpha() {
+    beta();
+}
+
+function beta() {
+    gamma();
+}
+
+function gamma() {
+    target();
+}
+
+function zeta() {
+    target();
+}
+
+function target() {
+    return 1;
+}
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_limits_keep_the_shortest_paths() {
+    let project = branching_project();
+    let shortest = vec![vec!["start", "zeta", "target"]];
+
+    let found = run_path_search(path_query(&project, "start", "target", 5).with_max_paths(1))
+        .unwrap()
+        .unwrap();
+    assert_eq!(names(&found), shortest);
+    assert!(found.truncated);
+
+    // alpha, zeta, beta and target are reached before gamma
+    let found = run_path_search(path_query(&project, "start", "target", 5).with_max_nodes(4))
+        .unwrap()
+        .unwrap();
+    assert_eq!(names(&found), shortest);
+    assert!(found.truncated);
+
+    let found = run_path_search(path_query(&project, "start", "target", 5).with_max_paths(2))
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.paths.len(), 2);
+    assert!(!found.truncated);
+}
+
+#[test]
+fn test_cli_keeps_the_shortest_path_within_the_limits() {
+    let project = branching_project();
+
+    for limit in [["--max-paths", "1"], ["--max-nodes", "4"]] {
+        let mut cmd = Command::new(cargo_bin!("cs"));
+        cmd.env("NO_COLOR", "1")
+            .env("CS_DISABLE_CACHE_SERVER", "1")
+            .args(["--path-between", "start", "target", "--no-index"])
+            .args(limit)
+            .current_dir(project.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("-> zeta ("))
+            .stdout(predicate::str::contains("alpha").not());
+    }
+}
+
+#[test]
+fn test_cli_prints_call_paths() {
+    let project = checkout_project();
+
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1")
+        .env("CS_DISABLE_CACHE_SERVER", "1")
+        .args(["--path-between", "handleSubmit", "chargeCard", "--no-index"])
+        .current_dir(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 call paths from handleSubmit to chargeCard",
+        ))
+        .stdout(predicate::str::contains("2. handleSubmit ("))
+        .stdout(predicate::str::contains("checkout.js:1) -> validate ("))
+        .stdout(predicate::str::contains("checkout.js:10) -> retry ("));
+
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1")
+        .env("CS_DISABLE_CACHE_SERVER", "1")
+        .args(["--path-between", "chargeCard", "handleSubmit", "--no-index"])
+        .current_dir(project.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No call path from 'chargeCard' to 'handleSubmit'",
+        ));
+}
@@ -339,3 +339,28 @@ fn test_end_to_end_pattern_matching_workflow() {
     println!("   Translation entries found: {}", matching_entries.len());
     println!("   Code references found: {}", all_code_refs.len());
 }
+
+#[test]
+fn test_batched_usages_are_attributed_to_entries() {
+    use cs::parse::YamlParser;
+    use cs::search::KeyMatcher;
+
+    let entries = YamlParser::parse_file(&PathBuf::from(
+        "tests/fixtures/rails-app/config/locales/en.yml",
+    ))
+    .expect("Should parse YAML");
+    let keys = KeyMatcher::with_partial_keys(&entries);
+    let matcher = PatternMatcher::new(PathBuf::from("tests/fixtures/rails-app"));
+
+    let code_refs = matcher.find_usages_of(&keys).expect("Batch search failed");
+
+    // One search covers every key, and each hit maps back to its entry
+    for key in ["invoice.labels.add_new", "invoice.labels.delete"] {
+        let refs: Vec<_> = code_refs.iter().filter(|r| r.key_path == key).collect();
+        assert!(!refs.is_empty(), "Should find usages of {}", key);
+        let owners = keys.owners(key);
+        assert_eq!(owners.len(), 1);
+        assert_eq!(entries[owners[0]].key, key);
+    }
+    assert!(code_refs.iter().all(|r| keys.contains(&r.key_path)));
+}
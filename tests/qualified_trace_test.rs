@@ -0,0 +1,212 @@
+use assert_cmd::{cargo_bin, Command};
+use cs::{run_trace, FunctionFinder, SearchError, TraceDirection, TraceQuery};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+fn cs_cmd(user_config_dir: &Path) -> Command {
+    let mut cmd = Command::new(cargo_bin!("cs"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("CS_DISABLE_CACHE_SERVER", "1");
+    cmd.env("CS_CONFIG_DIR", user_config_dir);
+    cmd
+}
+
+/// Two Ruby classes, a Rust impl block and a Go method, all defining `total`
+fn project_with_shared_names() -> TempDir {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("app/models")).unwrap();
+    fs::write(
+        dir.path().join("app/models/invoice.rb"),
+        "module Billing\n  class Invoice\n    def total\n      subtotal()\n    end\n\n    def subtotal\n      1\n    end\n  end\nend\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("app/models/report.rb"),
+        "class Report\n  def total\n    0\n  end\nend\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("invoice.rs"),
+        "mod invoice {\n    pub struct Invoice;\n\n    impl Invoice {\n        pub fn total(&self) -> u32 {\n            0\n        }\n    }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("service.go"),
+        "package billing\n\nfunc (s *InvoiceService) Total() int {\n\treturn 0\n}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn trace_query(project: &TempDir, name: &str) -> TraceQuery {
+    TraceQuery::new(name.to_string(), TraceDirection::Forward, 3)
+        .with_base_dir(project.path().to_path_buf())
+        .with_symbol_index(false)
+}
+
+#[test]
+fn test_definitions_carry_their_scope() {
+    let project = project_with_shared_names();
+    let mut finder = FunctionFinder::new(project.path().to_path_buf());
+
+    let defs = finder.find_functions("total");
+    let mut names: Vec<String> = defs.iter().map(|d| d.qualified_name()).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "Billing::Invoice#total",
+            "Report#total",
+            "invoice::Invoice::total"
+        ]
+    );
+
+    let go = finder.find_functions("Total");
+    assert_eq!(go.len(), 1);
+    assert_eq!(go[0].scope, vec!["billing", "InvoiceService"]);
+}
+
+#[test]
+fn test_qualified_queries_pick_one_definition() {
+    let project = project_with_shared_names();
+    let mut finder = FunctionFinder::new(project.path().to_path_buf());
+
+    for (query, file) in [
+        ("Billing::Invoice#total", "invoice.rb"),
+        ("Report.total", "report.rb"),
+        ("invoice::Invoice::total", "invoice.rs"),
+        ("InvoiceService.Total", "service.go"),
+    ] {
+        let defs = finder.find_functions(query);
+        assert_eq!(defs.len(), 1, "{}", query);
+        assert!(defs[0].file.ends_with(file), "{}", query);
+    }
+
+    // Outer scopes may be left out, so this matches Ruby and Rust alike
+    assert_eq!(finder.find_functions("Invoice#total").len(), 2);
+    assert!(finder.find_functions("Order#total").is_empty());
+}
+
+#[test]
+fn test_ambiguous_trace_reports_candidates() {
+    let project = project_with_shared_names();
+
+    match run_trace(trace_query(&project, "total")) {
+        Err(SearchError::AmbiguousFunction { name, candidates }) => {
+            assert_eq!(name, "total");
+            assert_eq!(candidates.len(), 3);
+        }
+        other => panic!("expected an ambiguity error, got {:?}", other),
+    }
+
+    let tree = run_trace(trace_query(&project, "Billing::Invoice#total"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(tree.root.def.scope, vec!["Billing", "Invoice"]);
+    assert_eq!(tree.root.children[0].def.name, "subtotal");
+
+    // Pinning the definition resolves the ambiguity too
+    let pinned = trace_query(&project, "total")
+        .with_definition(project.path().join("app/models/report.rb"), 2);
+    let tree = run_trace(pinned).unwrap().unwrap();
+    assert_eq!(tree.root.def.qualified_name(), "Report#total");
+}
+
+#[test]
+fn test_cli_lists_or_traces_every_candidate() {
+    let project = project_with_shared_names();
+    let user = tempdir().unwrap();
+
+    cs_cmd(user.path())
+        .args(["total", "--trace", "--no-index"])
+        .current_dir(project.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'total' matches 3 functions"))
+        .stderr(predicate::str::contains("Report#total"))
+        .stderr(predicate::str::contains("--all-candidates"));
+
+    cs_cmd(user.path())
+        .args(["total", "--trace", "--no-index", "--all-candidates"])
+        .current_dir(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Billing::Invoice#total"))
+        .stdout(predicate::str::contains("Report#total"))
+        .stdout(predicate::str::contains("invoice::Invoice::total"));
+
+    let output = cs_cmd(user.path())
+        .args(["total", "--trace", "--no-index", "--json"])
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["kind"], "trace-candidates");
+    assert_eq!(json["candidates"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_cli_pins_a_definition_when_no_qualified_name_is_unique() {
+    let project = tempdir().unwrap();
+    let user = tempdir().unwrap();
+    for dir in ["app/models", "lib/legacy"] {
+        fs::create_dir_all(project.path().join(dir)).unwrap();
+        fs::write(
+            project.path().join(dir).join("invoice.rb"),
+            "class Invoice\n  def total\n    0\n  end\nend\n",
+        )
+        .unwrap();
+    }
+
+    // Both are `Invoice#total`, so qualifying the name cannot tell them apart
+    cs_cmd(user.path())
+        .args(["total", "--trace", "--no-index"])
+        .current_dir(project.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Qualify the name").not())
+        .stderr(predicate::str::contains("--defined-at "))
+        .stderr(predicate::str::contains("invoice.rb:2"));
+
+    cs_cmd(user.path())
+        .args([
+            "total",
+            "--trace",
+            "--no-index",
+            "--defined-at",
+            "lib/legacy/invoice.rb:2",
+        ])
+        .current_dir(project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Invoice#total"));
+}
+
+#[test]
+fn test_cli_suggests_a_qualified_name_matching_one_candidate() {
+    let project = tempdir().unwrap();
+    let user = tempdir().unwrap();
+    fs::write(
+        project.path().join("invoice.rb"),
+        "class Invoice\n  def total\n    0\n  end\nend\n",
+    )
+    .unwrap();
+    fs::write(
+        project.path().join("billing.rb"),
+        "module Billing\n  class Invoice\n    def total\n      0\n    end\n  end\nend\n",
+    )
+    .unwrap();
+
+    // `Invoice#total` also matches `Billing::Invoice#total`, so only the
+    // longer name picks a single function
+    cs_cmd(user.path())
+        .args(["total", "--trace", "--no-index"])
+        .current_dir(project.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Qualify the name: cs --trace 'Billing::Invoice#total'",
+        ));
+}
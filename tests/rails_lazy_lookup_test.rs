@@ -0,0 +1,108 @@
+mod common;
+
+use common::write;
+use cs::{run_search, SearchQuery};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn rails_project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    let base = project.path();
+    write(
+        base,
+        "config/locales/en.yml",
+        r#"en:
+  invoices:
+    index:
+      add_new: "Add New Invoice"
+    form:
+      save: "Save Invoice"
+"#,
+    );
+    write(
+        base,
+        "config/locales/pt-BR.yml",
+        r#"pt-BR:
+  invoices:
+    index:
+      add_new: "Nova Fatura"
+"#,
+    );
+    write(
+        base,
+        "app/views/invoices/index.html.erb",
+        "<h1>Invoices</h1>\n<%= link_to t('.add_new'), new_invoice_path %>\n<%= t('.add_new') %>\n",
+    );
+    write(
+        base,
+        "app/views/invoices/_form.html.erb",
+        "<%= f.submit t('.save') %>\n<%= t('.missing') %>\n",
+    );
+    // Same lazy key in another view resolves to a different full key
+    write(
+        base,
+        "app/views/reports/index.html.erb",
+        "<%= t('.add_new') %>\n",
+    );
+    project
+}
+
+fn references(query: &str, base: &Path) -> Vec<(String, usize, String)> {
+    let query = SearchQuery::new(query.to_string()).with_base_dir(base.to_path_buf());
+    let result = run_search(query).expect("Search failed");
+    result
+        .code_references
+        .iter()
+        .map(|r| {
+            let relative = r.file.strip_prefix(base).unwrap_or(&r.file);
+            (
+                relative.to_string_lossy().into_owned(),
+                r.line,
+                r.key_path.clone(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_locale_root_is_recorded_on_entries() {
+    let project = rails_project();
+    let query = SearchQuery::new("Nova Fatura".to_string()).with_base_dir(project.path().into());
+    let result = run_search(query).expect("Search failed");
+
+    assert_eq!(result.translation_entries.len(), 1);
+    let entry = &result.translation_entries[0];
+    assert_eq!(entry.key, "invoices.index.add_new");
+    assert_eq!(entry.locale.as_deref(), Some("pt-BR"));
+}
+
+#[test]
+fn test_lazy_lookups_resolve_to_view_scoped_keys() {
+    let project = rails_project();
+
+    assert_eq!(
+        references("Add New Invoice", project.path()),
+        vec![
+            (
+                "app/views/invoices/index.html.erb".to_string(),
+                2,
+                "invoices.index.add_new".to_string()
+            ),
+            (
+                "app/views/invoices/index.html.erb".to_string(),
+                3,
+                "invoices.index.add_new".to_string()
+            ),
+        ]
+    );
+
+    // Partials drop their leading underscore
+    assert_eq!(
+        references("Save Invoice", project.path()),
+        vec![(
+            "app/views/invoices/_form.html.erb".to_string(),
+            1,
+            "invoices.form.save".to_string()
+        )]
+    );
+}
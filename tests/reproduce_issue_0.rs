@@ -61,13 +61,18 @@ en-US:
         1,
         "Should find translation entry"
     );
+    // The locale root is detected and stripped from the key
     assert_eq!(
         result.translation_entries[0].key,
-        "en-US.app.production_and_service.new_page.labels.add_new"
+        "app.production_and_service.new_page.labels.add_new"
+    );
+    assert_eq!(
+        result.translation_entries[0].locale.as_deref(),
+        Some("en-US")
     );
 
     // We expect to find the code reference
-    // This will fail if generate_partial_keys doesn't handle stripping 'en.app'
+    // This will fail if generate_partial_keys doesn't handle stripping 'app'
     assert!(
         !result.code_references.is_empty(),
         "Should find code reference"
@@ -97,3 +97,144 @@ fn test_sitter_erb_ignoring() {
         "Should not find function in ERB (unsupported)"
     );
 }
+
+#[test]
+fn test_sitter_custom_extension_mapping() {
+    use cs::parse::sitter::SupportedLanguage;
+    use cs::parse::Sitter;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    let code = "export function boot() {\n  start();\n}\n";
+    let path = Path::new("app.mjs");
+
+    let mut plain = Sitter::new();
+    assert!(!plain.is_supported(path));
+    assert!(plain.find_functions(path, code).unwrap().is_empty());
+
+    let map = HashMap::from([("mjs".to_string(), SupportedLanguage::JavaScript)]);
+    let mut mapped = Sitter::new().with_language_map(map);
+    assert!(mapped.is_supported(path));
+    let functions = mapped.find_functions(path, code).unwrap();
+    assert!(functions.iter().any(|f| f.name == "boot"));
+}
+
+#[test]
+fn test_sitter_go_functions_and_calls() {
+    use cs::parse::Sitter;
+    use std::path::Path;
+
+    let code = r#"package billing
+
+// func commentedOut() {}
+type InvoiceService struct{}
+
+func (s *InvoiceService) Total(lines []Line) int {
+	sum := 0
+	for _, line := range lines {
+		sum += s.amount(line)
+	}
+	return round(sum)
+}
+
+func Map[T any](items []T, f func(T) T) []T {
+	log.Printf("skip() in a string")
+	return items
+}
+"#;
+    let path = Path::new("billing.go");
+    let mut sitter = Sitter::new();
+
+    let functions = sitter.find_functions(path, code).unwrap();
+    let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["Total", "Map"]);
+    assert_eq!(functions[0].scope, ["billing", "InvoiceService"]);
+    assert_eq!((functions[0].start_line, functions[0].end_line), (6, 12));
+    assert_eq!(functions[1].scope, ["billing"]);
+
+    let calls = sitter
+        .find_calls_in_function(path, code, "Total", 6)
+        .unwrap()
+        .unwrap();
+    assert_eq!(calls, ["amount", "round"]);
+    let calls = sitter
+        .find_calls_in_function(path, code, "Map", 14)
+        .unwrap()
+        .unwrap();
+    assert_eq!(calls, ["Printf"]);
+}
+
+#[test]
+fn test_sitter_java_functions_and_calls() {
+    use cs::parse::Sitter;
+    use std::path::Path;
+
+    let code = r#"package com.acme.billing;
+
+public class Invoice {
+    public Invoice(List<Line> lines) {
+        this.lines = List.copyOf(lines);
+    }
+
+    public int total()
+            throws IllegalStateException {
+        // return legacyTotal();
+        return lines.stream().mapToInt(Line::amount).sum();
+    }
+
+    abstract static class Line {
+        abstract int amount();
+    }
+}
+"#;
+    let path = Path::new("Invoice.java");
+    let mut sitter = Sitter::new();
+
+    // A signature split over lines and a body-less declaration are both found
+    let functions = sitter.find_functions(path, code).unwrap();
+    let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["Invoice", "total", "amount"]);
+    assert_eq!(functions[1].scope, ["com", "acme", "billing", "Invoice"]);
+    assert_eq!(
+        functions[2].scope,
+        ["com", "acme", "billing", "Invoice", "Line"]
+    );
+
+    let calls = sitter
+        .find_calls_in_function(path, code, "total", 8)
+        .unwrap()
+        .unwrap();
+    assert_eq!(calls, ["mapToInt", "stream", "sum"]);
+}
+
+#[test]
+fn test_sitter_java_trace_skips_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    let base_dir = temp_dir.path().to_path_buf();
+
+    fs::write(
+        base_dir.join("Report.java"),
+        r#"
+public class Report {
+    public String render(
+            Invoice invoice) {
+        return format(invoice);
+    }
+
+    // public String legacyRender() {
+}
+        "#,
+    )
+    .unwrap();
+
+    let mut finder = FunctionFinder::new(base_dir);
+
+    let func = finder
+        .find_function("render")
+        .expect("Should find render in Java");
+    assert_eq!(func.scope, ["Report"]);
+    assert!(
+        finder.find_function("legacyRender").is_none(),
+        "Should not find legacyRender in comment"
+    );
+}